@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use super::{Aria2Event, Aria2Notification};
+
+/// When each stopped download of an instance finished, as Unix time. aria2
+/// reports no such timestamp, so it is taken from the completion, error and
+/// stop events, or from the first time a stopped download is seen otherwise.
+#[derive(Debug, Default)]
+pub struct CompletionTracker {
+    times: Mutex<HashMap<String, i64>>,
+    /// Set when the times changed since they were last persisted
+    dirty: AtomicBool,
+}
+
+impl CompletionTracker {
+    /// Records the time of a download that stopped, and forgets downloads
+    /// that started again.
+    pub fn record(&self, notification: &Aria2Notification) {
+        let gid = notification.gid();
+        if gid.is_empty() {
+            return;
+        }
+        match notification.method {
+            Aria2Event::DownloadComplete | Aria2Event::DownloadError | Aria2Event::DownloadStop => {
+                self.insert(gid, chrono::Utc::now().timestamp());
+            }
+            Aria2Event::DownloadStart => self.forget(gid),
+            Aria2Event::DownloadPause
+            | Aria2Event::BtDownloadComplete
+            | Aria2Event::RetryGivenUp => {}
+        }
+    }
+
+    fn insert(&self, gid: &str, time: i64) {
+        self.times.lock().unwrap().insert(gid.to_string(), time);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn stopped_at(&self, gid: &str) -> Option<i64> {
+        self.times.lock().unwrap().get(gid).copied()
+    }
+
+    /// Returns the recorded time for `gid`, recording `now` if there is none.
+    pub fn stopped_at_or(&self, gid: &str, now: i64) -> i64 {
+        if let Some(time) = self.stopped_at(gid) {
+            return time;
+        }
+        self.insert(gid, now);
+        now
+    }
+
+    pub fn forget(&self, gid: &str) {
+        if self.times.lock().unwrap().remove(gid).is_some() {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Keeps only the downloads for which `keep` returns true.
+    pub fn retain(&self, mut keep: impl FnMut(&str) -> bool) {
+        let mut times = self.times.lock().unwrap();
+        let before = times.len();
+        times.retain(|gid, _| keep(gid));
+        if times.len() != before {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    #[must_use]
+    pub fn snapshot(&self) -> HashMap<String, i64> {
+        self.times.lock().unwrap().clone()
+    }
+
+    /// Adds persisted times without replacing ones recorded since startup.
+    pub fn restore(&self, saved: &HashMap<String, i64>) {
+        let mut times = self.times.lock().unwrap();
+        for (gid, time) in saved {
+            times.entry(gid.clone()).or_insert(*time);
+        }
+    }
+
+    /// Returns whether the times changed since the last call.
+    pub fn take_dirty(&self) -> bool {
+        self.dirty.swap(false, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(event: &str, gid: &str) -> Aria2Notification {
+        serde_json::from_value(serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": event,
+            "params": [{ "gid": gid }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_record_completion_events() {
+        let tracker = CompletionTracker::default();
+        tracker.record(&notification("aria2.onDownloadComplete", "1"));
+        tracker.record(&notification("aria2.onDownloadError", "2"));
+        tracker.record(&notification("aria2.onDownloadPause", "3"));
+        assert!(tracker.stopped_at("1").is_some());
+        assert!(tracker.stopped_at("2").is_some());
+        assert!(tracker.stopped_at("3").is_none());
+        assert!(tracker.take_dirty());
+        assert!(!tracker.take_dirty());
+
+        tracker.record(&notification("aria2.onDownloadStart", "1"));
+        assert!(tracker.stopped_at("1").is_none());
+        assert!(tracker.take_dirty());
+    }
+
+    #[test]
+    fn test_stopped_at_or_and_restore() {
+        let tracker = CompletionTracker::default();
+        assert_eq!(tracker.stopped_at_or("1", 100), 100);
+        assert_eq!(tracker.stopped_at_or("1", 200), 100);
+
+        tracker.restore(&HashMap::from([
+            ("1".to_string(), 50),
+            ("2".to_string(), 60),
+        ]));
+        assert_eq!(tracker.stopped_at("1"), Some(100));
+        assert_eq!(tracker.stopped_at("2"), Some(60));
+
+        tracker.retain(|gid| gid == "2");
+        assert_eq!(tracker.snapshot(), HashMap::from([("2".to_string(), 60)]));
+    }
+}
@@ -1,16 +1,43 @@
+use crate::bandwidth::BandwidthOverrides;
+use crate::config::RpcTransport;
+use crate::error::Error;
+use crate::history::{HistoryEntry, HistoryStore};
+use crate::metrics::InstanceCounters;
+use crate::stats::StatsRecorder;
+use crate::tools::rss::FeedLocks;
 use crate::Config;
-use anyhow::Result;
-use futures_util::StreamExt;
+use anyhow::{Context, Result};
+use base64::prelude::*;
+use recovery::{RetryLineage, RetryStates, SavedRecovery, TrackerInjections};
 use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
 use std::sync::Arc;
 use tokio::net::TcpStream;
 use tokio::sync::RwLock;
 use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
 
+pub mod completions;
+pub mod multicall;
 pub mod notifications;
 pub mod recovery;
+pub mod supervisor;
+pub mod types;
+mod ws;
 
+/// Downloads fetched per `aria2.tellWaiting` or `aria2.tellStopped` request.
+const PAGE_SIZE: i32 = 1000;
+
+pub use completions::CompletionTracker;
+pub use multicall::MultiCall;
 pub use notifications::{Aria2Event, Aria2Notification};
+pub use supervisor::{Aria2Supervisor, ProcessState, SupervisorStatus};
+pub use types::{
+    BitTorrentInfo, DownloadState, DownloadStatus, FileEntry, GlobalStat, Peer, QueueSnapshot,
+    SessionInfo, UriEntry, UriStatus, VersionInfo,
+};
+pub use ws::{ConnectionState, ConnectionStatus};
 
 #[allow(dead_code)]
 #[derive(Clone)]
@@ -19,6 +46,24 @@ pub struct Aria2Client {
     client: Client,
     pub name: String,
     pub state_manager: Arc<crate::state::StateManager>,
+    /// Archive of finished downloads whose results were removed
+    pub history: Arc<HistoryStore>,
+    /// Throughput samples taken by the stats sampler
+    pub stats: Arc<StatsRecorder>,
+    /// Event counters exported by `/metrics`
+    pub counters: Arc<InstanceCounters>,
+    /// Stalled torrents and the trackers injected into them
+    pub trackers: Arc<TrackerInjections>,
+    /// GIDs of failed downloads and the downloads that retried them
+    pub lineage: Arc<RetryLineage>,
+    /// Retry state of failed downloads seen by the recovery task
+    pub retries: Arc<RetryStates>,
+    /// Per-download limits applied by the active bandwidth profile
+    pub bandwidth: Arc<BandwidthOverrides>,
+    /// Serializes the runs of each RSS feed
+    pub feed_locks: Arc<FeedLocks>,
+    ws: Arc<ws::WsRpc>,
+    completions: Arc<CompletionTracker>,
 }
 
 impl Aria2Client {
@@ -41,55 +86,30 @@ impl Aria2Client {
         Ok(ws_stream)
     }
 
+    /// Forwards aria2 notifications to `tx`. The notifications arrive on the
+    /// same persistent WebSocket used for RPC in `websocket` transport mode.
     pub fn start_notifications(
         &self,
         tx: tokio::sync::mpsc::Sender<Aria2Notification>,
     ) -> Result<()> {
-        let client = self.clone();
-        tokio::spawn(async move {
-            let mut backoff = tokio::time::Duration::from_secs(1);
-            let max_backoff = tokio::time::Duration::from_secs(60);
-
-            loop {
-                match client.connect_notifications().await {
-                    Ok(mut ws_stream) => {
-                        log::info!("Connected to aria2 WebSocket for instance: {}", client.name);
-                        backoff = tokio::time::Duration::from_secs(1); // Reset backoff on success
-                        while let Some(msg) = ws_stream.next().await {
-                            match msg {
-                                Ok(tokio_tungstenite::tungstenite::Message::Text(text)) => {
-                                    if let Ok(notification) =
-                                        serde_json::from_str::<Aria2Notification>(&text)
-                                    {
-                                        if tx.send(notification).await.is_err() {
-                                            log::error!("Notification channel closed");
-                                            return;
-                                        }
-                                    }
-                                }
-                                Ok(tokio_tungstenite::tungstenite::Message::Close(_)) => break,
-                                Err(e) => {
-                                    log::error!("WebSocket error: {e}");
-                                    break;
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        log::error!(
-                            "Failed to connect to aria2 WebSocket for {}: {e}. Retrying in {backoff:?}...",
-                            client.name
-                        );
-                    }
-                }
-                tokio::time::sleep(backoff).await;
-                backoff = std::cmp::min(backoff * 2, max_backoff);
-            }
-        });
+        self.ws.set_notification_sender(tx);
+        self.ensure_ws_running();
         Ok(())
     }
 
+    /// Sends an event raised by this server for `gid` to the same channel as
+    /// aria2's notifications.
+    pub fn emit_event(&self, event: Aria2Event, gid: &str) {
+        self.ws.notify(Aria2Notification {
+            jsonrpc: "2.0".to_string(),
+            method: event,
+            params: vec![notifications::Aria2EventParams {
+                gid: gid.to_string(),
+            }],
+            instance: Some(self.name.clone()),
+        });
+    }
+
     #[must_use]
     pub fn new(config: Config) -> Self {
         let client = Client::builder()
@@ -105,6 +125,18 @@ impl Aria2Client {
             state_manager: Arc::new(crate::state::StateManager::new(std::path::PathBuf::from(
                 "aria2_mcp_state.json",
             ))),
+            history: Arc::new(HistoryStore::new(std::path::PathBuf::from(
+                "aria2_mcp_history.jsonl",
+            ))),
+            stats: Arc::new(StatsRecorder::default()),
+            counters: Arc::new(InstanceCounters::default()),
+            trackers: Arc::new(TrackerInjections::default()),
+            lineage: Arc::new(RetryLineage::default()),
+            retries: Arc::new(RetryStates::default()),
+            bandwidth: Arc::new(BandwidthOverrides::default()),
+            feed_locks: Arc::new(FeedLocks::default()),
+            ws: Arc::new(ws::WsRpc::default()),
+            completions: Arc::new(CompletionTracker::default()),
         }
     }
 
@@ -113,6 +145,9 @@ impl Aria2Client {
         let mut config = config;
         config.rpc_url = instance.rpc_url;
         config.rpc_secret = instance.rpc_secret;
+        if let Some(transport) = instance.rpc_transport {
+            config.rpc_transport = transport;
+        }
         let name = instance.name.clone();
 
         let client = Client::builder()
@@ -128,6 +163,18 @@ impl Aria2Client {
             state_manager: Arc::new(crate::state::StateManager::new(std::path::PathBuf::from(
                 "aria2_mcp_state.json",
             ))),
+            history: Arc::new(HistoryStore::new(std::path::PathBuf::from(
+                "aria2_mcp_history.jsonl",
+            ))),
+            stats: Arc::new(StatsRecorder::default()),
+            counters: Arc::new(InstanceCounters::default()),
+            trackers: Arc::new(TrackerInjections::default()),
+            lineage: Arc::new(RetryLineage::default()),
+            retries: Arc::new(RetryStates::default()),
+            bandwidth: Arc::new(BandwidthOverrides::default()),
+            feed_locks: Arc::new(FeedLocks::default()),
+            ws: Arc::new(ws::WsRpc::default()),
+            completions: Arc::new(CompletionTracker::default()),
         }
     }
 
@@ -141,23 +188,189 @@ impl Aria2Client {
         Arc::clone(&self.state_manager)
     }
 
+    /// State of the WebSocket connection used for notifications and, with
+    /// the WebSocket transport, for RPC.
+    #[must_use]
+    pub fn connection_status(&self) -> ConnectionStatus {
+        self.ws.status()
+    }
+
+    /// When this instance's stopped downloads finished.
+    #[must_use]
+    pub fn completions(&self) -> Arc<CompletionTracker> {
+        Arc::clone(&self.completions)
+    }
+
+    /// Saves this instance's rules, schedules, RSS feeds, completion times,
+    /// recovery state and bandwidth overrides. Other instances' entries in
+    /// the state file are kept.
     pub async fn save_state(&self) -> Result<()> {
-        let state_data = {
+        let (bandwidth_profiles, bandwidth_schedules, organize_rules, feeds, removed_feeds) = {
             let config_guard = self.config.read().await;
-            crate::state::StateData {
-                bandwidth_profiles: config_guard.bandwidth_profiles.clone(),
-                bandwidth_schedules: config_guard.bandwidth_schedules.clone(),
-                organize_rules: config_guard.organize_rules.clone(),
-                rules: std::collections::HashMap::new(),
-            }
+            (
+                config_guard.bandwidth_profiles.clone(),
+                config_guard.bandwidth_schedules.clone(),
+                config_guard.organize_rules.clone(),
+                config_guard.rss_config.feeds.clone(),
+                config_guard.rss_config.removed_feeds.clone(),
+            )
+        };
+        let completion_times = self.completions.snapshot();
+        let recovery = SavedRecovery {
+            retries: self.retries.snapshot(),
+            lineage: self.lineage.links(),
         };
+        let bandwidth = self.bandwidth.snapshot();
+
         self.state_manager
-            .save(&state_data)
+            .update(|state_data| {
+                state_data.bandwidth_profiles = bandwidth_profiles;
+                state_data.bandwidth_schedules = bandwidth_schedules;
+                state_data.organize_rules = organize_rules;
+                if feeds.is_empty() {
+                    state_data.rss_feeds.remove(&self.name);
+                } else {
+                    state_data.rss_feeds.insert(self.name.clone(), feeds);
+                }
+                if removed_feeds.is_empty() {
+                    state_data.removed_rss_feeds.remove(&self.name);
+                } else {
+                    state_data
+                        .removed_rss_feeds
+                        .insert(self.name.clone(), removed_feeds);
+                }
+                if completion_times.is_empty() {
+                    state_data.completion_times.remove(&self.name);
+                } else {
+                    state_data
+                        .completion_times
+                        .insert(self.name.clone(), completion_times);
+                }
+                if recovery.is_empty() {
+                    state_data.recovery.remove(&self.name);
+                } else {
+                    state_data.recovery.insert(self.name.clone(), recovery);
+                }
+                if bandwidth.is_empty() {
+                    state_data.bandwidth_overrides.remove(&self.name);
+                } else {
+                    state_data
+                        .bandwidth_overrides
+                        .insert(self.name.clone(), bandwidth);
+                }
+            })
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to save state: {e}"))?;
+            .map_err(|e| anyhow::anyhow!("Failed to save state: {e}"))
+    }
+
+    /// Restores this instance's RSS feeds, completion times, recovery state
+    /// and bandwidth overrides. Must run before anything calls `save_state`,
+    /// which would otherwise overwrite the saved entries with empty ones.
+    pub async fn restore_state(&self) -> Result<()> {
+        self.restore_rss_feeds().await?;
+        self.restore_completion_times().await?;
+        self.restore_recovery().await?;
+        self.restore_bandwidth_overrides().await
+    }
+
+    /// Merges the RSS feeds saved for this instance into its config. Feeds
+    /// also defined in the config keep that definition and regain their
+    /// download history; those removed with `remove_rss_feed` are dropped.
+    pub async fn restore_rss_feeds(&self) -> Result<()> {
+        let state_data = self
+            .state_manager
+            .load()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to load state: {e}"))?;
+
+        let mut config_guard = self.config.write().await;
+        if let Some(removed) = state_data.removed_rss_feeds.get(&self.name) {
+            for name in removed {
+                config_guard.rss_config.mark_removed(name);
+            }
+            config_guard
+                .rss_config
+                .feeds
+                .retain(|f| !removed.contains(&f.name));
+        }
+
+        let Some(saved) = state_data.rss_feeds.get(&self.name) else {
+            return Ok(());
+        };
+        for feed in saved {
+            match config_guard
+                .rss_config
+                .feeds
+                .iter_mut()
+                .find(|f| f.name == feed.name)
+            {
+                Some(existing) => existing
+                    .download_history
+                    .extend(feed.download_history.clone()),
+                None => config_guard.rss_config.feeds.push(feed.clone()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the completion times saved for this instance.
+    pub async fn restore_completion_times(&self) -> Result<()> {
+        let state_data = self
+            .state_manager
+            .load()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to load state: {e}"))?;
+        if let Some(saved) = state_data.completion_times.get(&self.name) {
+            self.completions.restore(saved);
+        }
         Ok(())
     }
 
+    /// Loads the retry state and lineage saved for this instance.
+    pub async fn restore_recovery(&self) -> Result<()> {
+        let state_data = self
+            .state_manager
+            .load()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to load state: {e}"))?;
+        if let Some(saved) = state_data.recovery.get(&self.name) {
+            self.retries.restore(&saved.retries);
+            self.lineage.restore(&saved.lineage);
+        }
+        Ok(())
+    }
+
+    /// Loads the bandwidth overrides saved for this instance, so options
+    /// changed before a restart are still restored when the profile ends.
+    pub async fn restore_bandwidth_overrides(&self) -> Result<()> {
+        let state_data = self
+            .state_manager
+            .load()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to load state: {e}"))?;
+        if let Some(saved) = state_data.bandwidth_overrides.get(&self.name) {
+            self.bandwidth.restore(saved);
+        }
+        Ok(())
+    }
+
+    /// Appends to the purge history in the state file, keeping the newest
+    /// `limit` records.
+    pub async fn record_purges(
+        &self,
+        records: Vec<crate::state::PurgeRecord>,
+        limit: usize,
+    ) -> Result<()> {
+        self.state_manager
+            .update(|state_data| {
+                state_data.purge_history.extend(records);
+                let excess = state_data.purge_history.len().saturating_sub(limit);
+                state_data.purge_history.drain(..excess);
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to save state: {e}"))
+    }
+
     pub async fn ws_url(&self) -> Result<String> {
         let rpc_url = {
             let config = self.config.read().await;
@@ -173,735 +386,540 @@ impl Aria2Client {
         }
     }
 
-    pub async fn tell_active(&self, keys: Option<Vec<String>>) -> Result<serde_json::Value> {
-        let (rpc_url, rpc_secret) = {
+    /// Sends a JSON-RPC request to aria2 and deserializes its `result`.
+    ///
+    /// The request goes over HTTP or the persistent WebSocket depending on
+    /// the configured `rpc_transport`.
+    ///
+    /// The `token:` secret is prepended to `params` when configured, except for
+    /// `system.*` methods which aria2 does not authenticate. An aria2 error
+    /// response is returned as [`crate::error::Error::Aria2`].
+    pub(crate) async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Vec<Value>,
+    ) -> Result<T> {
+        let result = self.send_call(method, params).await;
+        if result.is_err() {
+            InstanceCounters::add(&self.counters.rpc_failures, 1);
+        }
+        result
+    }
+
+    async fn send_call<T: DeserializeOwned>(&self, method: &str, params: Vec<Value>) -> Result<T> {
+        let (rpc_url, rpc_secret, transport) = {
             let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
+            (
+                config.rpc_url.clone(),
+                config.rpc_secret.clone(),
+                config.rpc_transport,
+            )
         };
 
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        if let Some(k) = keys {
-            params.push(serde_json::json!(k));
+        let mut all_params = Vec::with_capacity(params.len() + 1);
+        if let Some(secret) = rpc_secret
+            .as_ref()
+            .filter(|_| !method.starts_with("system."))
+        {
+            all_params.push(json!(format!("token:{secret}")));
         }
+        all_params.extend(params);
 
-        let body = serde_json::json!({
+        let body = json!({
             "jsonrpc": "2.0",
             "id": "aria2-mcp",
-            "method": "aria2.tellActive",
-            "params": params,
+            "method": method,
+            "params": all_params,
         });
 
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-
-        let res: serde_json::Value = resp.json().await?;
+        let res: RpcResponse = match transport {
+            RpcTransport::WebSocket => serde_json::from_value(self.ws_request(body).await?)?,
+            RpcTransport::Http => {
+                let resp = self.client.post(&rpc_url).json(&body).send().await?;
+                resp.json().await?
+            }
+        };
 
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
+        if let Some(err) = res.error {
+            return Err(Error::Aria2 {
+                code: err.code,
+                message: err.message,
+            }
+            .into());
         }
 
-        Ok(res["result"].clone())
+        serde_json::from_value(res.result.unwrap_or(Value::Null))
+            .with_context(|| format!("Unexpected response to {method}"))
     }
 
-    pub async fn tell_waiting(
+    /// Sends a batch of calls in one `system.multicall` request. The outer
+    /// result fails only if the request itself fails; each call's outcome is
+    /// returned individually, in order.
+    pub async fn multicall(
         &self,
-        offset: i32,
-        num: i32,
-        keys: Option<Vec<String>>,
-    ) -> Result<serde_json::Value> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        params.push(serde_json::json!(offset));
-        params.push(serde_json::json!(num));
-        if let Some(k) = keys {
-            params.push(serde_json::json!(k));
+        calls: &MultiCall,
+    ) -> Result<Vec<std::result::Result<Value, Error>>> {
+        if calls.is_empty() {
+            return Ok(Vec::new());
         }
 
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.tellWaiting",
-            "params": params,
-        });
+        let rpc_secret = {
+            let config = self.config.read().await;
+            config.rpc_secret.clone()
+        };
 
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
+        let entries: Vec<Value> = self
+            .call(
+                "system.multicall",
+                vec![calls.to_params(rpc_secret.as_deref())],
+            )
+            .await?;
+
+        if entries.len() != calls.len() {
+            return Err(anyhow::anyhow!(
+                "system.multicall returned {} results for {} calls",
+                entries.len(),
+                calls.len()
+            ));
+        }
 
-        let res: serde_json::Value = resp.json().await?;
+        Ok(entries.into_iter().map(multicall::parse_entry).collect())
+    }
+
+    /// Fetches active, waiting and stopped downloads plus the global stat in a
+    /// single request, so the four views are consistent with each other.
+    /// Waiting and stopped lists longer than a page are completed with
+    /// further requests. Entries that cannot be parsed are logged and left
+    /// out rather than failing the whole snapshot.
+    pub async fn queue_snapshot(&self, keys: Option<Vec<String>>) -> Result<QueueSnapshot> {
+        let mut calls = MultiCall::new();
+        calls
+            .push("aria2.tellActive", keys.iter().map(|k| json!(k)).collect())
+            .push(
+                "aria2.tellWaiting",
+                paged_params(0, PAGE_SIZE, keys.clone()),
+            )
+            .push(
+                "aria2.tellStopped",
+                paged_params(0, PAGE_SIZE, keys.clone()),
+            )
+            .push("aria2.getGlobalStat", vec![]);
+
+        let mut results = self.multicall(&calls).await?.into_iter();
+        let mut next = || -> Result<Value> {
+            results
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("Missing result in queue snapshot"))?
+                .map_err(anyhow::Error::from)
+        };
 
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
+        let active = serde_json::from_value(next()?)?;
+        let waiting = serde_json::from_value(next()?)?;
+        let stopped = serde_json::from_value(next()?)?;
+        let global_stat = serde_json::from_value(next()?)?;
+
+        let waiting = self
+            .remaining_pages("aria2.tellWaiting", waiting, &keys)
+            .await?;
+        let stopped = self
+            .remaining_pages("aria2.tellStopped", stopped, &keys)
+            .await?;
+        Ok(QueueSnapshot {
+            active: parse_downloads(active),
+            waiting: parse_downloads(waiting),
+            stopped: parse_downloads(stopped),
+            global_stat,
+        })
+    }
+
+    /// Appends the pages after `entries`, the first page of the paged
+    /// listing `method`, until a page comes back short.
+    async fn remaining_pages(
+        &self,
+        method: &str,
+        mut entries: Vec<Value>,
+        keys: &Option<Vec<String>>,
+    ) -> Result<Vec<Value>> {
+        let mut page_len = entries.len();
+        while page_len >= PAGE_SIZE as usize {
+            let offset = i32::try_from(entries.len()).unwrap_or(i32::MAX);
+            let page: Vec<Value> = self
+                .call(method, paged_params(offset, PAGE_SIZE, keys.clone()))
+                .await?;
+            page_len = page.len();
+            entries.extend(page);
         }
+        Ok(entries)
+    }
 
-        Ok(res["result"].clone())
+    pub async fn tell_active(&self, keys: Option<Vec<String>>) -> Result<Value> {
+        self.call(
+            "aria2.tellActive",
+            keys.into_iter().map(|k| json!(k)).collect(),
+        )
+        .await
     }
 
-    pub async fn tell_stopped(
+    pub async fn tell_waiting(
         &self,
         offset: i32,
         num: i32,
         keys: Option<Vec<String>>,
-    ) -> Result<serde_json::Value> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        params.push(serde_json::json!(offset));
-        params.push(serde_json::json!(num));
-        if let Some(k) = keys {
-            params.push(serde_json::json!(k));
-        }
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.tellStopped",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
-
-        Ok(res["result"].clone())
+    ) -> Result<Value> {
+        self.call("aria2.tellWaiting", paged_params(offset, num, keys))
+            .await
     }
 
-    pub async fn get_global_stat(&self) -> Result<serde_json::Value> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.getGlobalStat",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
-
-        Ok(res["result"].clone())
+    pub async fn tell_stopped(
+        &self,
+        offset: i32,
+        num: i32,
+        keys: Option<Vec<String>>,
+    ) -> Result<Value> {
+        self.call("aria2.tellStopped", paged_params(offset, num, keys))
+            .await
     }
 
-    pub async fn get_global_option(&self) -> Result<serde_json::Value> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
+    pub async fn tell_status(&self, gid: &str) -> Result<Value> {
+        self.call("aria2.tellStatus", vec![json!(gid)]).await
+    }
 
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.getGlobalOption",
-            "params": params,
-        });
+    pub async fn get_global_stat(&self) -> Result<Value> {
+        self.call("aria2.getGlobalStat", vec![]).await
+    }
 
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
+    pub async fn get_files(&self, gid: &str) -> Result<Value> {
+        self.call("aria2.getFiles", vec![json!(gid)]).await
+    }
 
-        let res: serde_json::Value = resp.json().await?;
+    pub async fn get_uris(&self, gid: &str) -> Result<Value> {
+        self.call("aria2.getUris", vec![json!(gid)]).await
+    }
 
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
+    pub async fn get_peers(&self, gid: &str) -> Result<Value> {
+        self.call("aria2.getPeers", vec![json!(gid)]).await
+    }
 
-        Ok(res["result"].clone())
+    pub async fn active_downloads(&self, keys: Option<Vec<String>>) -> Result<Vec<DownloadStatus>> {
+        self.call(
+            "aria2.tellActive",
+            keys.into_iter().map(|k| json!(k)).collect(),
+        )
+        .await
     }
 
-    pub async fn get_option(&self, gid: &str) -> Result<serde_json::Value> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        params.push(serde_json::json!(gid));
+    pub async fn waiting_downloads(
+        &self,
+        offset: i32,
+        num: i32,
+        keys: Option<Vec<String>>,
+    ) -> Result<Vec<DownloadStatus>> {
+        self.call("aria2.tellWaiting", paged_params(offset, num, keys))
+            .await
+    }
 
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.getOption",
-            "params": params,
-        });
+    pub async fn stopped_downloads(
+        &self,
+        offset: i32,
+        num: i32,
+        keys: Option<Vec<String>>,
+    ) -> Result<Vec<DownloadStatus>> {
+        self.call("aria2.tellStopped", paged_params(offset, num, keys))
+            .await
+    }
 
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
+    pub async fn download_status(&self, gid: &str) -> Result<DownloadStatus> {
+        self.call("aria2.tellStatus", vec![json!(gid)]).await
+    }
 
-        let res: serde_json::Value = resp.json().await?;
+    pub async fn global_stat(&self) -> Result<GlobalStat> {
+        self.call("aria2.getGlobalStat", vec![]).await
+    }
 
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
+    pub async fn download_files(&self, gid: &str) -> Result<Vec<FileEntry>> {
+        self.call("aria2.getFiles", vec![json!(gid)]).await
+    }
 
-        Ok(res["result"].clone())
+    pub async fn download_uris(&self, gid: &str) -> Result<Vec<UriEntry>> {
+        self.call("aria2.getUris", vec![json!(gid)]).await
     }
 
-    pub async fn change_global_option(&self, options: serde_json::Value) -> Result<()> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        params.push(options);
+    pub async fn download_peers(&self, gid: &str) -> Result<Vec<Peer>> {
+        self.call("aria2.getPeers", vec![json!(gid)]).await
+    }
 
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.changeGlobalOption",
-            "params": params,
-        });
+    pub async fn version_info(&self) -> Result<VersionInfo> {
+        self.call("aria2.getVersion", vec![]).await
+    }
 
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
+    pub async fn get_version(&self) -> Result<String> {
+        Ok(self.version_info().await?.version)
+    }
 
-        let res: serde_json::Value = resp.json().await?;
+    pub async fn get_global_option(&self) -> Result<Value> {
+        self.call("aria2.getGlobalOption", vec![]).await
+    }
 
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
+    pub async fn get_option(&self, gid: &str) -> Result<Value> {
+        self.call("aria2.getOption", vec![json!(gid)]).await
+    }
 
+    pub async fn change_global_option(&self, options: Value) -> Result<()> {
+        self.call::<Value>("aria2.changeGlobalOption", vec![options])
+            .await?;
         Ok(())
     }
 
-    pub async fn change_option(&self, gid: &str, options: serde_json::Value) -> Result<()> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        params.push(serde_json::json!(gid));
-        params.push(options);
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.changeOption",
-            "params": params,
-        });
+    pub async fn change_option(&self, gid: &str, options: Value) -> Result<()> {
+        self.call::<Value>("aria2.changeOption", vec![json!(gid), options])
+            .await?;
+        Ok(())
+    }
 
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
+    pub async fn session_info(&self) -> Result<SessionInfo> {
+        self.call("aria2.getSessionInfo", vec![]).await
+    }
 
-        let res: serde_json::Value = resp.json().await?;
+    /// Saves the current session to the file given by aria2's `--save-session`.
+    pub async fn save_session(&self) -> Result<()> {
+        self.call::<Value>("aria2.saveSession", vec![]).await?;
+        Ok(())
+    }
 
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
+    /// Stops aria2 after it has stopped its downloads and saved its session.
+    pub async fn shutdown(&self) -> Result<()> {
+        self.call::<Value>("aria2.shutdown", vec![]).await?;
+        Ok(())
+    }
 
+    /// Stops aria2 without waiting for slow actions such as contacting
+    /// BitTorrent trackers.
+    pub async fn force_shutdown(&self) -> Result<()> {
+        self.call::<Value>("aria2.forceShutdown", vec![]).await?;
         Ok(())
     }
 
     pub async fn pause(&self, gid: &str) -> Result<()> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        params.push(serde_json::json!(gid));
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.pause",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
-
+        self.call::<Value>("aria2.pause", vec![json!(gid)]).await?;
         Ok(())
     }
 
     pub async fn force_pause(&self, gid: &str) -> Result<()> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        params.push(serde_json::json!(gid));
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.forcePause",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
-
+        self.call::<Value>("aria2.forcePause", vec![json!(gid)])
+            .await?;
         Ok(())
     }
 
     pub async fn unpause(&self, gid: &str) -> Result<()> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        params.push(serde_json::json!(gid));
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.unpause",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
-
+        self.call::<Value>("aria2.unpause", vec![json!(gid)])
+            .await?;
         Ok(())
     }
 
     pub async fn remove(&self, gid: &str) -> Result<()> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        params.push(serde_json::json!(gid));
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.remove",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
-
+        self.call::<Value>("aria2.remove", vec![json!(gid)]).await?;
         Ok(())
     }
 
     pub async fn force_remove(&self, gid: &str) -> Result<()> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        params.push(serde_json::json!(gid));
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.forceRemove",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
-
+        self.call::<Value>("aria2.forceRemove", vec![json!(gid)])
+            .await?;
         Ok(())
     }
 
     pub async fn move_position(&self, gid: &str, pos: i32, how: &str) -> Result<i32> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        params.push(serde_json::json!(gid));
-        params.push(serde_json::json!(pos));
-        params.push(serde_json::json!(how));
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.changePosition",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
-
-        let new_pos = res["result"]
-            .as_i64()
-            .ok_or_else(|| anyhow::anyhow!("Failed to get new position from response"))?;
-
-        Ok(new_pos as i32)
-    }
-
-    pub async fn tell_status(&self, gid: &str) -> Result<serde_json::Value> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        params.push(serde_json::json!(gid));
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.tellStatus",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
-
-        Ok(res["result"].clone())
+        self.call(
+            "aria2.changePosition",
+            vec![json!(gid), json!(pos), json!(how)],
+        )
+        .await
     }
 
-    pub async fn add_uri(
+    /// Removes `del_uris` from and adds `add_uris` to the file at `file_index`
+    /// (1-based) of a download, inserting them at `position` in its URI list
+    /// or appending them. Returns how many URIs were deleted and added.
+    pub async fn change_uri(
+        &self,
+        gid: &str,
+        file_index: u64,
+        del_uris: &[String],
+        add_uris: &[String],
+        position: Option<u64>,
+    ) -> Result<(u64, u64)> {
+        let mut params = vec![
+            json!(gid),
+            json!(file_index),
+            json!(del_uris),
+            json!(add_uris),
+        ];
+        params.extend(position.map(|p| json!(p)));
+        self.call("aria2.changeUri", params).await
+    }
+
+    pub async fn add_uri(&self, uris: Vec<String>, options: Option<Value>) -> Result<String> {
+        let mut params = vec![json!(uris)];
+        params.extend(options);
+        self.call("aria2.addUri", params).await
+    }
+
+    /// Adds a download at `position` in the waiting queue.
+    pub async fn add_uri_at(
         &self,
         uris: Vec<String>,
-        options: Option<serde_json::Value>,
+        options: Value,
+        position: u64,
     ) -> Result<String> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        params.push(serde_json::json!(uris));
-        if let Some(opts) = options {
-            params.push(opts);
-        }
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.addUri",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
-
-        let gid = res["result"]
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("Failed to get GID from response"))?;
-
-        Ok(gid.to_string())
+        self.call("aria2.addUri", vec![json!(uris), options, json!(position)])
+            .await
     }
 
-    pub async fn get_version(&self) -> Result<String> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.getVersion",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
-
-        let version = res["result"]["version"]
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("Failed to get version from response"))?;
-
-        Ok(version.to_string())
+    pub async fn add_torrent(
+        &self,
+        torrent: &[u8],
+        uris: Vec<String>,
+        options: Option<Value>,
+    ) -> Result<String> {
+        let mut params = vec![json!(BASE64_STANDARD.encode(torrent)), json!(uris)];
+        params.extend(options);
+        self.call("aria2.addTorrent", params).await
     }
 
-    pub async fn get_files(&self, gid: &str) -> Result<serde_json::Value> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        params.push(serde_json::json!(gid));
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.getFiles",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
-
-        Ok(res["result"].clone())
+    pub async fn add_metalink(
+        &self,
+        metalink: &[u8],
+        options: Option<Value>,
+    ) -> Result<Vec<String>> {
+        let mut params = vec![json!(BASE64_STANDARD.encode(metalink))];
+        params.extend(options);
+        self.call("aria2.addMetalink", params).await
     }
 
-    pub async fn get_uris(&self, gid: &str) -> Result<serde_json::Value> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        params.push(serde_json::json!(gid));
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.getUris",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
-
-        Ok(res["result"].clone())
+    pub async fn pause_all(&self) -> Result<()> {
+        self.call::<Value>("aria2.pauseAll", vec![]).await?;
+        Ok(())
     }
 
-    pub async fn get_peers(&self, gid: &str) -> Result<serde_json::Value> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-        params.push(serde_json::json!(gid));
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.getPeers",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
-
-        Ok(res["result"].clone())
+    pub async fn unpause_all(&self) -> Result<()> {
+        self.call::<Value>("aria2.unpauseAll", vec![]).await?;
+        Ok(())
     }
 
-    pub async fn pause_all(&self) -> Result<()> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
+    /// Removes every stopped download result, then archives the removed ones
+    /// to the download history. The listed results are removed one by one
+    /// rather than with `aria2.purgeDownloadResult`, so results that stop in
+    /// the meantime are kept for the next purge instead of being lost
+    /// unarchived. Nothing is removed if the results cannot be listed.
+    pub async fn purge_download_result(&self) -> Result<()> {
+        let stopped = self.all_stopped_downloads().await?;
+        let mut calls = MultiCall::new();
+        for download in &stopped {
+            calls.push("aria2.removeDownloadResult", vec![json!(download.gid)]);
         }
+        let outcomes = self.multicall(&calls).await?;
 
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.pauseAll",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
+        let mut removed = Vec::with_capacity(stopped.len());
+        for (download, outcome) in stopped.into_iter().zip(outcomes) {
+            match outcome {
+                Ok(_) => removed.push(download),
+                Err(e) => log::warn!("Failed to remove download result {}: {e}", download.gid),
+            }
         }
-
+        // Only once removed, so a failed purge that is retried does not
+        // archive the same results twice
+        self.archive_downloads(&removed).await;
         Ok(())
     }
 
-    pub async fn unpause_all(&self) -> Result<()> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.unpauseAll",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
+    /// Every stopped download, fetched a page at a time since
+    /// `--max-download-result` may keep more than one page.
+    async fn all_stopped_downloads(&self) -> Result<Vec<DownloadStatus>> {
+        let mut stopped = Vec::new();
+        loop {
+            let offset = i32::try_from(stopped.len()).unwrap_or(i32::MAX);
+            let page = self.stopped_downloads(offset, PAGE_SIZE, None).await?;
+            let last = page.len() < PAGE_SIZE as usize;
+            stopped.extend(page);
+            if last {
+                return Ok(stopped);
+            }
         }
-
-        Ok(())
     }
 
-    pub async fn purge_download_result(&self) -> Result<()> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
+    /// Removes a stopped download result, then archives it to the download
+    /// history.
+    pub async fn remove_download_result(&self, gid: &str) -> Result<()> {
+        let download = match self.download_status(gid).await {
+            Ok(download) => Some(download),
+            Err(e) => {
+                log::warn!("Could not archive download {gid} before removing it: {e}");
+                None
+            }
         };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
-        }
-
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.purgeDownloadResult",
-            "params": params,
-        });
-
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-        let res: serde_json::Value = resp.json().await?;
-
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
+        self.call::<Value>("aria2.removeDownloadResult", vec![json!(gid)])
+            .await?;
+        if let Some(download) = download {
+            self.archive_downloads(std::slice::from_ref(&download))
+                .await;
         }
-
         Ok(())
     }
 
-    pub async fn remove_download_result(&self, gid: &str) -> Result<()> {
-        let (rpc_url, rpc_secret) = {
-            let config = self.config.read().await;
-            (config.rpc_url.clone(), config.rpc_secret.clone())
-        };
-        let mut params = Vec::new();
-        if let Some(secret) = &rpc_secret {
-            params.push(serde_json::json!(format!("token:{}", secret)));
+    /// Appends the finished downloads among `downloads` to the download
+    /// history. Failures are logged so they never block the removal.
+    async fn archive_downloads(&self, downloads: &[DownloadStatus]) {
+        let now = chrono::Utc::now().timestamp();
+        let entries: Vec<HistoryEntry> = downloads
+            .iter()
+            .filter(|d| {
+                matches!(
+                    d.status,
+                    Some(DownloadState::Complete | DownloadState::Error | DownloadState::Removed)
+                )
+            })
+            .map(|d| HistoryEntry::new(d, &self.name, self.completions.stopped_at(&d.gid), now))
+            .collect();
+        if let Err(e) = self.history.append(&entries).await {
+            log::error!("Failed to archive download history: {e}");
         }
-        params.push(serde_json::json!(gid));
+    }
+}
 
-        let body = serde_json::json!({
-            "jsonrpc": "2.0",
-            "id": "aria2-mcp",
-            "method": "aria2.removeDownloadResult",
-            "params": params,
-        });
+#[derive(Deserialize)]
+struct RpcResponse {
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<RpcError>,
+}
 
-        let resp = self.client.post(&rpc_url).json(&body).send().await?;
-        let res: serde_json::Value = resp.json().await?;
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
 
-        if let Some(err) = res.get("error") {
-            return Err(anyhow::anyhow!("aria2 error: {err}"));
-        }
+/// Parses each download of a listing on its own, so one malformed entry is
+/// skipped instead of failing the whole listing.
+fn parse_downloads(entries: Vec<Value>) -> Vec<DownloadStatus> {
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let gid = entry
+                .get("gid")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown")
+                .to_string();
+            serde_json::from_value(entry)
+                .inspect_err(|e| log::warn!("Skipping malformed download {gid}: {e}"))
+                .ok()
+        })
+        .collect()
+}
 
-        Ok(())
-    }
+fn paged_params(offset: i32, num: i32, keys: Option<Vec<String>>) -> Vec<Value> {
+    let mut params = vec![json!(offset), json!(num)];
+    params.extend(keys.map(|k| json!(k)));
+    params
 }
 
 #[cfg(test)]
@@ -1019,31 +1037,56 @@ mod tests {
     async fn test_force_remove_error() {
         let config = Config::default();
         let client = Aria2Client::new(config);
-        let result = client.force_remove("dummy").await;
+        let result = client.force_remove("dummy").await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_move_position_error() {
+        let config = Config::default();
+        let client = Aria2Client::new(config);
+        let result = client.move_position("dummy", 0, "dummy").await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_session_methods_error() {
+        let client = Aria2Client::new(Config::default());
+        assert!(client.session_info().await.is_err());
+        assert!(client.save_session().await.is_err());
+        assert!(client.force_shutdown().await.is_err());
+        assert!(client.change_uri("dummy", 1, &[], &[], None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tell_status_error() {
+        let config = Config::default();
+        let client = Aria2Client::new(config);
+        let result = client.tell_status("dummy").await;
         assert!(result.is_err());
     }
 
     #[tokio::test]
-    async fn test_move_position_error() {
+    async fn test_add_uri_error() {
         let config = Config::default();
         let client = Aria2Client::new(config);
-        let result = client.move_position("dummy", 0, "dummy").await;
+        let result = client.add_uri(vec!["dummy".to_string()], None).await;
         assert!(result.is_err());
     }
 
     #[tokio::test]
-    async fn test_tell_status_error() {
+    async fn test_add_torrent_error() {
         let config = Config::default();
         let client = Aria2Client::new(config);
-        let result = client.tell_status("dummy").await;
+        let result = client.add_torrent(b"dummy", vec![], None).await;
         assert!(result.is_err());
     }
 
     #[tokio::test]
-    async fn test_add_uri_error() {
+    async fn test_add_metalink_error() {
         let config = Config::default();
         let client = Aria2Client::new(config);
-        let result = client.add_uri(vec!["dummy".to_string()], None).await;
+        let result = client.add_metalink(b"dummy", None).await;
         assert!(result.is_err());
     }
 
@@ -1126,9 +1169,165 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_remove_download_result_archives_history() {
+        use wiremock::matchers::{body_string_contains, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_string_contains("aria2.tellStatus"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": {
+                    "gid": "abc",
+                    "status": "complete",
+                    "totalLength": "100",
+                    "completedLength": "100",
+                    "files": [{ "path": "/downloads/file.iso", "uris": [] }]
+                }
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_string_contains("aria2.removeDownloadResult"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": "OK"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut client = Aria2Client::new(Config {
+            rpc_url: format!("{}/jsonrpc", mock_server.uri()),
+            ..Config::default()
+        });
+        client.history = Arc::new(HistoryStore::new(dir.path().join("history.jsonl")));
+
+        client.remove_download_result("abc").await.unwrap();
+
+        let entries = client
+            .history
+            .query(&crate::history::HistoryQuery::default())
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].gid, "abc");
+        assert_eq!(entries[0].name.as_deref(), Some("file.iso"));
+        assert_eq!(entries[0].total_length, 100);
+    }
+
+    #[tokio::test]
+    async fn test_purge_download_result_archives_every_page() {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        async fn mock_rpc(server: &MockServer, body: Value, response: ResponseTemplate) {
+            Mock::given(method("POST"))
+                .and(body_partial_json(body))
+                .respond_with(response)
+                .mount(server)
+                .await;
+        }
+        fn ok(result: Value) -> ResponseTemplate {
+            ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": result
+            }))
+        }
+        async fn mock_stopped(server: &MockServer) {
+            for (offset, len) in [(0, 1000), (1000, 5)] {
+                let page: Vec<Value> = (offset..offset + len)
+                    .map(|i| json!({ "gid": format!("g{i}"), "status": "complete" }))
+                    .collect();
+                mock_rpc(
+                    server,
+                    json!({ "method": "aria2.tellStopped", "params": [offset, 1000] }),
+                    ok(json!(page)),
+                )
+                .await;
+            }
+        }
+
+        let mock_server = MockServer::start().await;
+        mock_stopped(&mock_server).await;
+        mock_rpc(
+            &mock_server,
+            json!({ "method": "system.multicall" }),
+            ResponseTemplate::new(500),
+        )
+        .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut client = Aria2Client::new(Config::new(mock_server.uri(), None));
+        client.history = Arc::new(HistoryStore::new(dir.path().join("history.jsonl")));
+        let query = crate::history::HistoryQuery {
+            limit: Some(5000),
+            ..Default::default()
+        };
+
+        // Nothing is archived when the removal fails
+        assert!(client.purge_download_result().await.is_err());
+        assert!(client.history.query(&query).await.unwrap().is_empty());
+
+        mock_server.reset().await;
+        mock_stopped(&mock_server).await;
+        // Every removal succeeds except the one of g3
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "method": "system.multicall" })))
+            .respond_with(|request: &wiremock::Request| {
+                let body: Value = serde_json::from_slice(&request.body).unwrap();
+                let entries: Vec<Value> = body["params"][0]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|call| {
+                        if call["params"][0] == "g3" {
+                            json!({ "code": 1, "message": "Could not remove download result" })
+                        } else {
+                            json!(["OK"])
+                        }
+                    })
+                    .collect();
+                ok(json!(entries))
+            })
+            .mount(&mock_server)
+            .await;
+
+        client.purge_download_result().await.unwrap();
+        let archived = client.history.query(&query).await.unwrap();
+        assert_eq!(archived.len(), 1004);
+        assert!(archived.iter().all(|entry| entry.gid != "g3"));
+
+        // Nothing is removed when the results cannot be listed
+        mock_server.reset().await;
+        mock_rpc(
+            &mock_server,
+            json!({ "method": "aria2.tellStopped" }),
+            ResponseTemplate::new(500),
+        )
+        .await;
+        assert!(client.purge_download_result().await.is_err());
+        let multicalls = mock_server
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|r| String::from_utf8_lossy(&r.body).contains("system.multicall"))
+            .count();
+        assert_eq!(multicalls, 0);
+    }
+
     #[tokio::test]
     async fn test_aria2_client_methods_success() {
-        use wiremock::matchers::{method, path};
+        use wiremock::matchers::{body_string_contains, method, path};
         use wiremock::{Mock, MockServer, ResponseTemplate};
 
         let mock_server = MockServer::start().await;
@@ -1167,9 +1366,73 @@ mod tests {
 
         client.pause_all().await.unwrap();
         client.unpause_all().await.unwrap();
+
+        // Mock tellStopped with no stopped downloads
+        Mock::given(method("POST"))
+            .and(body_string_contains("aria2.tellStopped"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": []
+            })))
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+
         client.purge_download_result().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_aria2_client_session_and_uri_methods() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let client = Aria2Client::new(Config {
+            rpc_url: format!("{}/jsonrpc", mock_server.uri()),
+            ..Config::default()
+        });
+
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_partial_json(
+                serde_json::json!({ "method": "aria2.getSessionInfo" }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": { "sessionId": "abc123" }
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_partial_json(serde_json::json!({
+                "method": "aria2.changeUri",
+                "params": ["2089b05ecca3d829", 1, ["http://dead/a"], ["http://alive/a"], 0]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": [1, 1]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        assert_eq!(client.session_info().await.unwrap().session_id, "abc123");
+        let changed = client
+            .change_uri(
+                "2089b05ecca3d829",
+                1,
+                &["http://dead/a".to_string()],
+                &["http://alive/a".to_string()],
+                Some(0),
+            )
+            .await
+            .unwrap();
+        assert_eq!(changed, (1, 1));
+    }
+
     #[tokio::test]
     async fn test_ws_url_error() {
         let config = Config {
@@ -1216,9 +1479,242 @@ mod tests {
             .mount(&mock_server)
             .await;
 
-        let result = client.get_version().await;
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("aria2 error"));
+        let err = client.get_version().await.unwrap_err();
+        match err.downcast_ref::<Error>() {
+            Some(Error::Aria2 { code, message }) => {
+                assert_eq!(*code, 1);
+                assert_eq!(message, "Some error");
+            }
+            other => panic!("expected Error::Aria2, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_aria2_client_multicall() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let config = Config {
+            rpc_url: format!("{}/jsonrpc", mock_server.uri()),
+            rpc_secret: Some("secret".to_string()),
+            ..Config::default()
+        };
+        let client = Aria2Client::new(config);
+
+        // The token goes into each call, not the outer system.multicall
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_partial_json(serde_json::json!({
+                "method": "system.multicall",
+                "params": [[
+                    { "methodName": "aria2.pause", "params": ["token:secret", "1"] },
+                    { "methodName": "aria2.pause", "params": ["token:secret", "2"] }
+                ]]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": [["1"], { "code": 1, "message": "GID 2 is not found" }]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let mut calls = MultiCall::new();
+        calls
+            .push("aria2.pause", vec![json!("1")])
+            .push("aria2.pause", vec![json!("2")]);
+
+        let results = client.multicall(&calls).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap(), &json!("1"));
+        assert!(matches!(results[1], Err(Error::Aria2 { code: 1, .. })));
+
+        assert!(client
+            .multicall(&MultiCall::new())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_aria2_client_queue_snapshot() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let config = Config {
+            rpc_url: format!("{}/jsonrpc", mock_server.uri()),
+            ..Config::default()
+        };
+        let client = Aria2Client::new(config);
+
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_partial_json(
+                serde_json::json!({ "method": "system.multicall" }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": [
+                    [[{ "gid": "1", "status": "active", "downloadSpeed": "100" }]],
+                    [[{ "gid": "2", "status": "waiting" }]],
+                    [[]],
+                    [{ "numActive": "1", "numWaiting": "1", "numStopped": "0" }]
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let snapshot = client.queue_snapshot(None).await.unwrap();
+        assert_eq!(snapshot.active[0].download_speed, 100);
+        assert_eq!(snapshot.waiting[0].gid, "2");
+        assert!(snapshot.stopped.is_empty());
+        assert_eq!(snapshot.global_stat.num_active, 1);
+    }
+
+    #[tokio::test]
+    async fn test_aria2_client_websocket_transport() {
+        use futures_util::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Fake aria2: emits a notification before every response and answers
+        // each request with the id it was sent
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let req: Value = serde_json::from_str(&text).unwrap();
+                assert_eq!(req["params"][0], "token:secret");
+                let notification = json!({
+                    "jsonrpc": "2.0",
+                    "method": "aria2.onDownloadStart",
+                    "params": [{ "gid": "1" }]
+                });
+                ws.send(Message::text(notification.to_string()))
+                    .await
+                    .unwrap();
+                let resp = json!({
+                    "jsonrpc": "2.0",
+                    "id": req["id"],
+                    "result": { "version": "1.37.0", "enabledFeatures": [] }
+                });
+                ws.send(Message::text(resp.to_string())).await.unwrap();
+            }
+        });
+
+        let config = Config {
+            rpc_url: format!("http://{addr}/jsonrpc"),
+            rpc_secret: Some("secret".to_string()),
+            rpc_transport: RpcTransport::WebSocket,
+            ..Config::default()
+        };
+        let client = Aria2Client::new(config);
+        let (tx, mut rx) = tokio::sync::mpsc::channel(10);
+        client.start_notifications(tx).unwrap();
+
+        let (a, b) = tokio::join!(client.get_version(), client.get_version());
+        assert_eq!(a.unwrap(), "1.37.0");
+        assert_eq!(b.unwrap(), "1.37.0");
+
+        let notification = rx.recv().await.unwrap();
+        assert_eq!(notification.method, Aria2Event::DownloadStart);
+    }
+
+    #[tokio::test]
+    async fn test_new_with_instance_rpc_transport() {
+        let config = Config {
+            rpc_transport: RpcTransport::WebSocket,
+            ..Config::default()
+        };
+        let instance = crate::config::Aria2Instance {
+            name: "http-only".to_string(),
+            rpc_url: "http://localhost:6800/jsonrpc".to_string(),
+            rpc_secret: None,
+            rpc_transport: Some(RpcTransport::Http),
+        };
+        let client = Aria2Client::new_with_instance(config.clone(), instance.clone());
+        assert_eq!(
+            client.config().read().await.rpc_transport,
+            RpcTransport::Http
+        );
+
+        let inherited = crate::config::Aria2Instance {
+            rpc_transport: None,
+            ..instance
+        };
+        let client = Aria2Client::new_with_instance(config, inherited);
+        assert_eq!(
+            client.config().read().await.rpc_transport,
+            RpcTransport::WebSocket
+        );
+    }
+
+    #[tokio::test]
+    async fn test_queue_snapshot_pages_and_skips_malformed_entries() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let config = Config {
+            rpc_url: format!("{}/jsonrpc", mock_server.uri()),
+            ..Config::default()
+        };
+        let client = Aria2Client::new(config);
+
+        let first_page: Vec<Value> = (0..1000)
+            .map(|i| json!({ "gid": i.to_string(), "status": "waiting" }))
+            .collect();
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_partial_json(json!({ "method": "system.multicall" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": [
+                    [[]],
+                    [first_page],
+                    [[
+                        { "gid": "bad", "status": "complete", "totalLength": {} },
+                        { "gid": "done", "status": "complete" }
+                    ]],
+                    [{ "numActive": "0", "numWaiting": "1001", "numStopped": "2" }]
+                ]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_partial_json(json!({
+                "method": "aria2.tellWaiting",
+                "params": [1000, 1000]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": [{ "gid": "1000", "status": "waiting" }]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let snapshot = client.queue_snapshot(None).await.unwrap();
+        assert_eq!(snapshot.waiting.len(), 1001);
+        assert_eq!(snapshot.waiting[1000].gid, "1000");
+        assert_eq!(snapshot.stopped.len(), 1);
+        assert_eq!(snapshot.stopped[0].gid, "done");
+    }
+
+    #[tokio::test]
+    async fn test_queue_snapshot_error() {
+        let client = Aria2Client::new(Config::default());
+        assert!(client.queue_snapshot(None).await.is_err());
     }
 
     #[tokio::test]
@@ -1304,4 +1800,75 @@ mod tests {
         let result = client.get_option("dummy").await.unwrap();
         assert_eq!(result["dir"], "/tmp");
     }
+
+    #[tokio::test]
+    async fn test_aria2_client_add_torrent_success() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let rpc_url = format!("{}/jsonrpc", mock_server.uri());
+
+        let config = Config {
+            rpc_url,
+            ..Config::default()
+        };
+        let client = Aria2Client::new(config);
+
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_partial_json(serde_json::json!({
+                "method": "aria2.addTorrent",
+                "params": ["dG9ycmVudA==", ["http://seed.example.com/file"]]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": "2089b05ecca3d829"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let gid = client
+            .add_torrent(
+                b"torrent",
+                vec!["http://seed.example.com/file".to_string()],
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(gid, "2089b05ecca3d829");
+    }
+
+    #[tokio::test]
+    async fn test_aria2_client_add_metalink_success() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let rpc_url = format!("{}/jsonrpc", mock_server.uri());
+
+        let config = Config {
+            rpc_url,
+            ..Config::default()
+        };
+        let client = Aria2Client::new(config);
+
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_partial_json(serde_json::json!({
+                "method": "aria2.addMetalink",
+                "params": ["bWV0YWxpbms="]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": ["gid1", "gid2"]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let gids = client.add_metalink(b"metalink", None).await.unwrap();
+        assert_eq!(gids, vec!["gid1".to_string(), "gid2".to_string()]);
+    }
 }
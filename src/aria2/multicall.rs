@@ -0,0 +1,104 @@
+use serde_json::{json, Value};
+
+use super::RpcError;
+use crate::error::Error;
+
+/// A batch of aria2 RPC calls sent in a single `system.multicall` request.
+///
+/// Calls are added without the `token:` secret; the client injects it into
+/// every call when the batch is sent.
+#[derive(Debug, Clone, Default)]
+pub struct MultiCall {
+    calls: Vec<(String, Vec<Value>)>,
+}
+
+impl MultiCall {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, method: &str, params: Vec<Value>) -> &mut Self {
+        self.calls.push((method.to_string(), params));
+        self
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    pub(crate) fn to_params(&self, secret: Option<&str>) -> Value {
+        let methods: Vec<Value> = self
+            .calls
+            .iter()
+            .map(|(method, params)| {
+                let mut all_params = Vec::with_capacity(params.len() + 1);
+                if let Some(secret) = secret {
+                    all_params.push(json!(format!("token:{secret}")));
+                }
+                all_params.extend(params.iter().cloned());
+                json!({ "methodName": method, "params": all_params })
+            })
+            .collect();
+        json!(methods)
+    }
+}
+
+/// Converts one `system.multicall` result entry: a successful call is wrapped
+/// in a one-element array, a failed call is a fault struct.
+pub(crate) fn parse_entry(entry: Value) -> Result<Value, Error> {
+    match entry {
+        Value::Array(mut values) if values.len() == 1 => Ok(values.remove(0)),
+        other => {
+            let err: RpcError = serde_json::from_value(other)?;
+            Err(Error::Aria2 {
+                code: err.code,
+                message: err.message,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_params_injects_token() {
+        let mut calls = MultiCall::new();
+        calls
+            .push("aria2.pause", vec![json!("1")])
+            .push("aria2.getGlobalStat", vec![]);
+        assert_eq!(calls.len(), 2);
+
+        let params = calls.to_params(Some("secret"));
+        assert_eq!(params[0]["methodName"], "aria2.pause");
+        assert_eq!(params[0]["params"], json!(["token:secret", "1"]));
+        assert_eq!(params[1]["params"], json!(["token:secret"]));
+
+        let params = calls.to_params(None);
+        assert_eq!(params[0]["params"], json!(["1"]));
+    }
+
+    #[test]
+    fn test_parse_entry() {
+        assert_eq!(parse_entry(json!(["OK"])).unwrap(), json!("OK"));
+
+        let err = parse_entry(json!({ "code": 1, "message": "GID 1 is not found" })).unwrap_err();
+        match err {
+            Error::Aria2 { code, message } => {
+                assert_eq!(code, 1);
+                assert_eq!(message, "GID 1 is not found");
+            }
+            other => panic!("expected Error::Aria2, got {other:?}"),
+        }
+
+        assert!(parse_entry(json!("garbage")).is_err());
+    }
+}
@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+use super::{Aria2Client, DownloadState, DownloadStatus};
+
+/// JSON-RPC method of the MCP notification sent for every aria2 event.
+pub const MCP_EVENT_METHOD: &str = "notifications/aria2/event";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Aria2Event {
     #[serde(rename = "aria2.onDownloadStart")]
     DownloadStart,
@@ -14,6 +19,40 @@ pub enum Aria2Event {
     DownloadError,
     #[serde(rename = "aria2.onBtDownloadComplete")]
     BtDownloadComplete,
+    /// Sent by this server, not aria2, when recovery stops retrying a download
+    #[serde(rename = "aria2mcp.onRetryGivenUp")]
+    RetryGivenUp,
+}
+
+impl Aria2Event {
+    pub const ALL: [Self; 7] = [
+        Self::DownloadStart,
+        Self::DownloadPause,
+        Self::DownloadStop,
+        Self::DownloadComplete,
+        Self::DownloadError,
+        Self::BtDownloadComplete,
+        Self::RetryGivenUp,
+    ];
+
+    /// Name used for the event in MCP notifications and event filters.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::DownloadStart => "download_start",
+            Self::DownloadPause => "download_pause",
+            Self::DownloadStop => "download_stop",
+            Self::DownloadComplete => "download_complete",
+            Self::DownloadError => "download_error",
+            Self::BtDownloadComplete => "bt_download_complete",
+            Self::RetryGivenUp => "retry_given_up",
+        }
+    }
+
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.name() == name)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +60,9 @@ pub struct Aria2Notification {
     pub jsonrpc: String,
     pub method: Aria2Event,
     pub params: Vec<Aria2EventParams>,
+    /// Name of the instance that emitted the event, set when it is received
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,27 +70,65 @@ pub struct Aria2EventParams {
     pub gid: String,
 }
 
+/// State of the download an event refers to, fetched when the event arrives.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadSnapshot {
+    pub name: Option<String>,
+    pub total_length: u64,
+    pub completed_length: u64,
+    pub status: Option<DownloadState>,
+    pub error_code: Option<u64>,
+    pub error_message: Option<String>,
+}
+
+impl From<&DownloadStatus> for DownloadSnapshot {
+    fn from(status: &DownloadStatus) -> Self {
+        Self {
+            name: status.name(),
+            total_length: status.total_length,
+            completed_length: status.completed_length,
+            status: status.status,
+            error_code: status.error_code,
+            error_message: status.error_message.clone().filter(|m| !m.is_empty()),
+        }
+    }
+}
+
 impl Aria2Notification {
     #[must_use]
-    pub fn to_mcp_notification(&self) -> serde_json::Value {
-        let event_name = match self.method {
-            Aria2Event::DownloadStart => "download_start",
-            Aria2Event::DownloadPause => "download_pause",
-            Aria2Event::DownloadStop => "download_stop",
-            Aria2Event::DownloadComplete => "download_complete",
-            Aria2Event::DownloadError => "download_error",
-            Aria2Event::BtDownloadComplete => "bt_download_complete",
-        };
+    pub fn gid(&self) -> &str {
+        self.params.first().map_or("", |p| p.gid.as_str())
+    }
+
+    /// Params of the `notifications/aria2/event` MCP notification.
+    #[must_use]
+    pub fn event_params(&self, download: Option<&DownloadSnapshot>) -> serde_json::Value {
+        serde_json::json!({
+            "event": self.method.name(),
+            "gid": self.gid(),
+            "instance": self.instance,
+            "download": download
+        })
+    }
 
-        let gid = self.params.first().map_or("", |p| p.gid.as_str());
+    /// Looks up the download on `client` to build the event's snapshot.
+    pub async fn snapshot(&self, client: &Aria2Client) -> Option<DownloadSnapshot> {
+        match client.download_status(self.gid()).await {
+            Ok(status) => Some(DownloadSnapshot::from(&status)),
+            Err(e) => {
+                log::debug!("Failed to fetch status for event on {}: {e}", self.gid());
+                None
+            }
+        }
+    }
 
+    #[must_use]
+    pub fn to_mcp_notification(&self) -> serde_json::Value {
         serde_json::json!({
             "jsonrpc": "2.0",
-            "method": "notifications/aria2/event",
-            "params": {
-                "event": event_name,
-                "gid": gid
-            }
+            "method": MCP_EVENT_METHOD,
+            "params": self.event_params(None)
         })
     }
 }
@@ -66,6 +146,7 @@ mod tests {
             (Aria2Event::DownloadComplete, "download_complete"),
             (Aria2Event::DownloadError, "download_error"),
             (Aria2Event::BtDownloadComplete, "bt_download_complete"),
+            (Aria2Event::RetryGivenUp, "retry_given_up"),
         ];
 
         for (event, expected_name) in events {
@@ -75,6 +156,7 @@ mod tests {
                 params: vec![Aria2EventParams {
                     gid: "123".to_string(),
                 }],
+                instance: None,
             };
             let mcp = notification.to_mcp_notification();
             assert_eq!(mcp["params"]["event"], expected_name);
@@ -88,8 +170,67 @@ mod tests {
             jsonrpc: "2.0".to_string(),
             method: Aria2Event::DownloadStart,
             params: vec![],
+            instance: None,
         };
         let mcp = notification.to_mcp_notification();
         assert_eq!(mcp["params"]["gid"], "");
     }
+
+    #[test]
+    fn test_event_names_round_trip() {
+        for event in Aria2Event::ALL {
+            assert_eq!(Aria2Event::from_name(event.name()), Some(event));
+        }
+        assert_eq!(Aria2Event::from_name("unknown"), None);
+    }
+
+    #[test]
+    fn test_event_params_with_snapshot() {
+        let notification: Aria2Notification = serde_json::from_value(serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "aria2.onDownloadError",
+            "params": [{ "gid": "abc" }]
+        }))
+        .unwrap();
+        let notification = Aria2Notification {
+            instance: Some("remote".to_string()),
+            ..notification
+        };
+
+        let status: DownloadStatus = serde_json::from_value(serde_json::json!({
+            "gid": "abc",
+            "status": "error",
+            "totalLength": "2048",
+            "completedLength": "1024",
+            "errorCode": "3",
+            "errorMessage": "Resource not found",
+            "files": [{ "path": "/downloads/file.iso" }]
+        }))
+        .unwrap();
+        let snapshot = DownloadSnapshot::from(&status);
+
+        let params = notification.event_params(Some(&snapshot));
+        assert_eq!(params["event"], "download_error");
+        assert_eq!(params["gid"], "abc");
+        assert_eq!(params["instance"], "remote");
+        assert_eq!(params["download"]["name"], "file.iso");
+        assert_eq!(params["download"]["totalLength"], 2048);
+        assert_eq!(params["download"]["status"], "error");
+        assert_eq!(params["download"]["errorCode"], 3);
+        assert_eq!(params["download"]["errorMessage"], "Resource not found");
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_unreachable_client() {
+        let client = Aria2Client::new(crate::Config::default());
+        let notification = Aria2Notification {
+            jsonrpc: "2.0".to_string(),
+            method: Aria2Event::DownloadComplete,
+            params: vec![Aria2EventParams {
+                gid: "1".to_string(),
+            }],
+            instance: None,
+        };
+        assert!(notification.snapshot(&client).await.is_none());
+    }
 }
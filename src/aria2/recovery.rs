@@ -1,14 +1,31 @@
-use crate::aria2::Aria2Client;
+use crate::aria2::{Aria2Client, DownloadStatus, MultiCall, UriStatus};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 use tokio::sync::RwLock;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct RetryConfig {
     pub max_retries: u32,
     pub initial_backoff_secs: u64,
+    /// How failed downloads are recovered, checked in order. Errors no policy
+    /// matches are left alone
+    pub policies: Vec<ErrorPolicy>,
     pub tracker_injection_enabled: bool,
     pub tracker_list_url: Option<String>,
+    /// Local file with one tracker per line. Used instead of `tracker_list_url` if set
+    pub tracker_list_path: Option<String>,
+    /// How long the fetched tracker list is reused before it is fetched again
+    pub tracker_refresh_secs: u64,
+    /// How long a torrent must have no seeders and no connections before
+    /// trackers are injected
+    pub stall_timeout_secs: u64,
+    /// Downloads that failed longer ago than this are no longer retried, and
+    /// their retry state and lineage are forgotten
+    pub state_max_age_secs: u64,
 }
 
 impl Default for RetryConfig {
@@ -16,35 +33,140 @@ impl Default for RetryConfig {
         Self {
             max_retries: 3,
             initial_backoff_secs: 5,
+            policies: ErrorPolicy::defaults(),
             tracker_injection_enabled: false,
             tracker_list_url: None,
+            tracker_list_path: None,
+            tracker_refresh_secs: 86_400,
+            stall_timeout_secs: 600,
+            state_max_age_secs: 604_800,
         }
     }
 }
 
-pub struct ErrorAnalyzer;
+/// How a failed download is recovered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryStrategy {
+    /// Re-add the download after an exponential backoff
+    #[default]
+    Backoff,
+    /// Re-add it with `split` and `max-connection-per-server` halved
+    ReduceConnections,
+    /// Re-add it with the URIs aria2 has not tried yet first
+    AlternateMirror,
+    /// Re-add it on the next configured instance
+    SwitchInstance,
+    /// Leave it failed and send a `retry_given_up` event
+    GiveUp,
+}
+
+/// Maps aria2 errors to a [`RecoveryStrategy`]. A policy matches when the
+/// download's error code is one of `error_codes` (or `error_codes` is empty)
+/// and its error message matches `message_pattern` (if set).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorPolicy {
+    #[serde(default)]
+    pub error_codes: Vec<u64>,
+    /// Regular expression matched against the download's `errorMessage`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_pattern: Option<String>,
+    #[serde(default)]
+    pub strategy: RecoveryStrategy,
+}
+
+impl ErrorPolicy {
+    /// Backoff for timeouts, network problems, name resolution failures and
+    /// bad HTTP/FTP responses.
+    #[must_use]
+    pub fn defaults() -> Vec<Self> {
+        vec![Self {
+            error_codes: vec![2, 6, 17, 19, 20, 21, 22],
+            message_pattern: None,
+            strategy: RecoveryStrategy::Backoff,
+        }]
+    }
+}
+
+/// Keys requested from `tellActive` when looking for stalled torrents.
+const STALL_KEYS: &[&str] = &[
+    "gid",
+    "status",
+    "totalLength",
+    "completedLength",
+    "connections",
+    "numSeeders",
+    "seeder",
+    "infoHash",
+];
+
+pub struct ErrorAnalyzer {
+    policies: Vec<(ErrorPolicy, Option<regex::Regex>)>,
+}
 
 impl ErrorAnalyzer {
     #[must_use]
     pub fn new() -> Self {
-        Self
+        Self::with_policies(&ErrorPolicy::defaults())
+    }
+
+    /// Policies whose `message_pattern` is not a valid regex are skipped.
+    #[must_use]
+    pub fn with_policies(policies: &[ErrorPolicy]) -> Self {
+        let policies = policies
+            .iter()
+            .filter_map(|policy| match &policy.message_pattern {
+                Some(pattern) => match regex::Regex::new(pattern) {
+                    Ok(re) => Some((policy.clone(), Some(re))),
+                    Err(e) => {
+                        log::warn!("Ignoring error policy with invalid pattern {pattern:?}: {e}");
+                        None
+                    }
+                },
+                None => Some((policy.clone(), None)),
+            })
+            .collect();
+        Self { policies }
+    }
+
+    /// The strategy of the first policy matching the error, if any.
+    #[must_use]
+    pub fn classify(&self, error_code: &str, error_message: &str) -> Option<RecoveryStrategy> {
+        let code = error_code.parse::<u64>().ok();
+        self.policies
+            .iter()
+            .find(|(policy, re)| {
+                (policy.error_codes.is_empty()
+                    || code.is_some_and(|c| policy.error_codes.contains(&c)))
+                    && re.as_ref().is_none_or(|re| re.is_match(error_message))
+            })
+            .map(|(policy, _)| policy.strategy)
     }
 
     #[must_use]
     pub fn is_retryable(&self, error_code: &str) -> bool {
-        matches!(error_code, "2" | "6" | "17" | "19" | "20" | "21" | "22")
+        self.classify(error_code, "")
+            .is_some_and(|s| s != RecoveryStrategy::GiveUp)
     }
 
+    /// The strategy for a download in the `error` state, if a policy matches.
     #[must_use]
-    pub fn should_retry(&self, status: &serde_json::Value) -> bool {
-        if let Some(s) = status.get("status").and_then(|v| v.as_str()) {
-            if s == "error" {
-                if let Some(code) = status.get("errorCode").and_then(|v| v.as_str()) {
-                    return self.is_retryable(code);
-                }
-            }
+    pub fn strategy_for(&self, status: &serde_json::Value) -> Option<RecoveryStrategy> {
+        if status.get("status").and_then(|v| v.as_str()) != Some("error") {
+            return None;
         }
-        false
+        let code = status.get("errorCode").and_then(|v| v.as_str())?;
+        let message = status
+            .get("errorMessage")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        self.classify(code, message)
+    }
+
+    #[must_use]
+    pub fn should_retry(&self, status: &serde_json::Value) -> bool {
+        self.strategy_for(status)
+            .is_some_and(|s| s != RecoveryStrategy::GiveUp)
     }
 }
 
@@ -57,162 +179,829 @@ impl Default for ErrorAnalyzer {
 pub struct RecoveryManager {
     config: RetryConfig,
     analyzer: ErrorAnalyzer,
-    retry_counts: Arc<RwLock<HashMap<String, u32>>>,
-    pending_retries: Arc<RwLock<HashSet<String>>>,
+    /// Tracker list shared by every instance, with the time it was fetched
+    tracker_cache: RwLock<Option<(i64, Vec<String>)>>,
+}
+
+/// What the recovery task does about a failed download.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecoveryAction {
+    Retry {
+        strategy: RecoveryStrategy,
+        backoff_secs: u64,
+        /// Unix time the retry is scheduled for
+        due_at: i64,
+    },
+    /// Recovery stopped for good; a `retry_given_up` event should be sent
+    GiveUp { reason: String },
 }
 
 impl RecoveryManager {
     #[must_use]
     pub fn new(config: RetryConfig) -> Self {
         Self {
+            analyzer: ErrorAnalyzer::with_policies(&config.policies),
             config,
-            analyzer: ErrorAnalyzer::new(),
-            retry_counts: Arc::new(RwLock::new(HashMap::new())),
-            pending_retries: Arc::new(RwLock::new(HashSet::new())),
+            tracker_cache: RwLock::new(None),
         }
     }
 
-    pub async fn analyze_and_get_retry_backoff(
+    #[must_use]
+    pub fn tracker_injection_enabled(&self) -> bool {
+        self.config.tracker_injection_enabled
+    }
+
+    /// Decides what to do about a download seen in the stopped list and
+    /// records it in `client.retries`. Returns `None` if the download did not
+    /// fail, no policy matches its error, a retry is already pending,
+    /// recovery already gave up on it or it failed more than
+    /// `state_max_age_secs` ago.
+    pub async fn plan_recovery(
         &self,
+        client: &Aria2Client,
         gid: &str,
         status: &serde_json::Value,
-    ) -> Option<u64> {
-        if !self.analyzer.should_retry(status) {
+    ) -> Option<RecoveryAction> {
+        let strategy = self.analyzer.strategy_for(status)?;
+        let now = chrono::Utc::now().timestamp();
+        if client.completions().stopped_at_or(gid, now) < self.state_cutoff(now) {
             return None;
         }
+        let error_code = status
+            .get("errorCode")
+            .and_then(|v| v.as_str())
+            .and_then(|c| c.parse().ok());
+        let error_message = status
+            .get("errorMessage")
+            .and_then(|v| v.as_str())
+            .filter(|m| !m.is_empty())
+            .map(ToString::to_string);
 
-        // Check if already pending
-        {
-            let pending = self.pending_retries.read().await;
-            if pending.contains(gid) {
-                return None;
-            }
+        let mut states = client.retries.states.lock().unwrap();
+        let state = states.entry(gid.to_string()).or_default();
+        if state.due_at.is_some() || state.gave_up {
+            return None;
         }
+        client.retries.dirty.store(true, Ordering::Relaxed);
+        state.error_code = error_code;
+        state.error_message = error_message;
 
-        let mut counts = self.retry_counts.write().await;
-        let count = counts.entry(gid.to_string()).or_insert(0);
-
-        if *count >= self.config.max_retries {
-            return None;
+        let reason = if strategy == RecoveryStrategy::GiveUp {
+            Some("an error policy gives up on this error".to_string())
+        } else if state.attempts >= self.config.max_retries {
+            Some(format!("all {} retries failed", self.config.max_retries))
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            state.gave_up = true;
+            return Some(RecoveryAction::GiveUp { reason });
         }
 
-        *count += 1;
-        let backoff = self.config.initial_backoff_secs * (2u64.pow(*count - 1));
+        state.attempts += 1;
+        let backoff_secs = self.config.initial_backoff_secs * (2u64.pow(state.attempts - 1));
+        let due_at = now.saturating_add(i64::try_from(backoff_secs).unwrap_or(i64::MAX));
+        state.strategy = Some(strategy);
+        state.due_at = Some(due_at);
+        Some(RecoveryAction::Retry {
+            strategy,
+            backoff_secs,
+            due_at,
+        })
+    }
 
-        // Mark as pending
-        {
-            let mut pending = self.pending_retries.write().await;
-            pending.insert(gid.to_string());
+    /// Forgets the retry state of downloads that completed, were removed or
+    /// are no longer known to aria2, then the retry state and lineage of
+    /// downloads that failed more than `state_max_age_secs` ago. Pending
+    /// retries are kept.
+    pub async fn prune_state(&self, client: &Aria2Client, now: i64) -> anyhow::Result<()> {
+        let cutoff = self.state_cutoff(now);
+        let completions = client.completions();
+
+        let settled: Vec<String> = client
+            .retries
+            .all()
+            .into_iter()
+            .filter(|(_, state)| state.due_at.is_none())
+            .map(|(gid, _)| gid)
+            .collect();
+        let mut calls = MultiCall::new();
+        for gid in &settled {
+            calls.push(
+                "aria2.tellStatus",
+                vec![serde_json::json!(gid), serde_json::json!(["status"])],
+            );
         }
+        let outcomes = client.multicall(&calls).await?;
 
-        Some(backoff)
+        let mut forgotten = HashSet::new();
+        for (gid, outcome) in settled.iter().zip(outcomes) {
+            let forget = match outcome {
+                Ok(status) => match status.get("status").and_then(|v| v.as_str()) {
+                    Some("complete" | "removed") => true,
+                    Some("error") => completions.stopped_at_or(gid, now) < cutoff,
+                    _ => false,
+                },
+                // aria2 no longer knows the download
+                Err(_) => true,
+            };
+            if forget {
+                forgotten.insert(gid.as_str());
+            }
+        }
+        client
+            .retries
+            .retain(|gid, state| state.due_at.is_some() || !forgotten.contains(gid));
+
+        // A link is kept while its failed download could still be planned,
+        // so the download is not retried a second time
+        client.lineage.retain(|link| {
+            link.retried_at >= cutoff
+                || completions
+                    .stopped_at(&link.from)
+                    .is_some_and(|stopped_at| stopped_at >= cutoff)
+        });
+        Ok(())
+    }
+
+    /// Failures older than this are no longer retried or remembered.
+    fn state_cutoff(&self, now: i64) -> i64 {
+        now.saturating_sub(i64::try_from(self.config.state_max_age_secs).unwrap_or(i64::MAX))
+    }
+
+    /// Backoff before the download is retried, if [`Self::plan_recovery`]
+    /// schedules a retry.
+    pub async fn analyze_and_get_retry_backoff(
+        &self,
+        client: &Aria2Client,
+        gid: &str,
+        status: &serde_json::Value,
+    ) -> Option<u64> {
+        match self.plan_recovery(client, gid, status).await? {
+            RecoveryAction::Retry { backoff_secs, .. } => Some(backoff_secs),
+            RecoveryAction::GiveUp { .. } => None,
+        }
     }
 
+    /// Re-adds a failed download with the options it had, at the front of
+    /// the waiting queue where it was while it ran. Torrents are re-added
+    /// from a magnet link built from their info hash. The new GID is recorded
+    /// in the client's [`RetryLineage`].
     pub async fn perform_retry(&self, client: &Aria2Client, gid: &str) -> anyhow::Result<String> {
+        self.perform_retry_on(client, client, gid).await
+    }
+
+    /// Like [`Self::perform_retry`], but adds the new download to `target`,
+    /// which may be another instance. The retry state moves with it.
+    pub async fn perform_retry_on(
+        &self,
+        client: &Aria2Client,
+        target: &Aria2Client,
+        gid: &str,
+    ) -> anyhow::Result<String> {
         log::info!("Attempting retry for download {gid}...");
 
-        // 1. Get URIs and options from old download
-        let status = client.tell_status(gid).await?;
-        let uris: Vec<String> = status
-            .get("files")
-            .and_then(|f| f.as_array())
-            .and_then(|files| files.first())
-            .and_then(|file| file.get("uris"))
-            .and_then(|u| u.as_array())
-            .map(|uris| {
-                uris.iter()
-                    .filter_map(|u| {
-                        u.get("uri")
-                            .and_then(|v| v.as_str().map(std::string::ToString::to_string))
-                    })
-                    .collect()
-            })
+        let strategy = client
+            .retries
+            .get(gid)
+            .and_then(|s| s.strategy)
             .unwrap_or_default();
-
-        if uris.is_empty() {
-            let res = Err(anyhow::anyhow!("No URIs found for download {gid}"));
-            let mut pending = self.pending_retries.write().await;
-            pending.remove(gid);
-            return res;
-        }
-
-        // 2. Add new download
-        let new_gid = match client.add_uri(uris, None).await {
-            Ok(ng) => ng,
+        let new_gid = match self.readd(client, target, gid, strategy).await {
+            Ok(new_gid) => new_gid,
             Err(e) => {
-                let mut pending = self.pending_retries.write().await;
-                pending.remove(gid);
+                // Keep the attempts so the next retry counts against the budget
+                client.retries.clear_pending(gid);
                 return Err(e);
             }
         };
 
         log::info!("Retry successful. New GID: {new_gid}");
 
-        // 3. Cleanup old download result (optional but recommended)
-        // If we don't remove it, it stays in the stopped list.
-        // Let's try to remove it.
-        let _ = client.remove(gid).await;
+        // Drop the failed result from the stopped list, so it is not retried
+        // again once its lineage link is pruned
+        if let Err(e) = client.remove_download_result(gid).await {
+            log::warn!("Failed to remove the result of retried download {gid}: {e}");
+        }
+
+        let state = client.retries.finish(gid);
+        let attempt = state.as_ref().map_or(0, |s| s.attempts).max(1);
+        if let Some(state) = state {
+            target.retries.insert(&new_gid, state);
+        }
+        let instance = (target.name != client.name).then(|| target.name.clone());
+        client.lineage.record(
+            gid,
+            &new_gid,
+            attempt,
+            chrono::Utc::now().timestamp(),
+            instance,
+        );
+
+        Ok(new_gid)
+    }
 
-        // 4. Transfer retry count to new GID and cleanup
-        {
-            let mut counts = self.retry_counts.write().await;
-            if let Some(count) = counts.remove(gid) {
-                counts.insert(new_gid.clone(), count);
+    async fn readd(
+        &self,
+        client: &Aria2Client,
+        target: &Aria2Client,
+        gid: &str,
+        strategy: RecoveryStrategy,
+    ) -> anyhow::Result<String> {
+        let download = client.download_status(gid).await?;
+        let mut options = match client.get_option(gid).await {
+            Ok(options) if options.is_object() => options,
+            Ok(_) => serde_json::json!({}),
+            Err(e) => {
+                log::warn!(
+                    "Could not read the options of download {gid}, retrying without them: {e}"
+                );
+                serde_json::json!({})
             }
+        };
+        if strategy == RecoveryStrategy::ReduceConnections {
+            halve_option(&mut options, "split", 5);
+            halve_option(&mut options, "max-connection-per-server", 1);
+        }
 
-            let mut pending = self.pending_retries.write().await;
-            pending.remove(gid);
+        let uris = if let Some(info_hash) = &download.info_hash {
+            vec![magnet_link(
+                info_hash,
+                download.name().as_deref(),
+                download.trackers(),
+            )]
+        } else {
+            let files_with_uris = download.files.iter().filter(|f| !f.uris.is_empty()).count();
+            if files_with_uris > 1 {
+                anyhow::bail!("Download {gid} has URIs for several files and cannot be re-added");
+            }
+            let mut entries: Vec<_> = download.files.iter().flat_map(|f| &f.uris).collect();
+            let untried = entries.iter().any(|u| u.status != Some(UriStatus::Used));
+            if strategy == RecoveryStrategy::AlternateMirror {
+                // Stable, so mirrors keep their order within each group
+                entries.sort_by_key(|u| u.status == Some(UriStatus::Used));
+            }
+            let mut seen = HashSet::new();
+            let mut uris: Vec<String> = entries
+                .into_iter()
+                .filter(|u| seen.insert(u.uri.as_str()))
+                .map(|u| u.uri.clone())
+                .collect();
+            if strategy == RecoveryStrategy::AlternateMirror && !untried {
+                if uris.len() < 2 {
+                    log::warn!("Download {gid} has no alternate mirror, retrying the same URI");
+                } else {
+                    uris.rotate_left(1);
+                }
+            }
+            uris
+        };
+        if uris.is_empty() {
+            anyhow::bail!("No URIs found for download {gid}");
         }
 
-        Ok(new_gid)
+        target.add_uri_at(uris, options, 0).await
     }
 
-    pub async fn clear_retry_count(&self, gid: &str) {
-        let mut counts = self.retry_counts.write().await;
-        counts.remove(gid);
+    pub async fn clear_retry_count(&self, client: &Aria2Client, gid: &str) {
+        client.retries.remove(gid);
     }
 
-    pub async fn perform_retry_cleanup_for_test(&self, gid: &str) {
-        let mut pending = self.pending_retries.write().await;
-        pending.remove(gid);
+    pub async fn perform_retry_cleanup_for_test(&self, client: &Aria2Client, gid: &str) {
+        client.retries.clear_pending(gid);
     }
 
+    /// Adds `trackers` to the `bt-tracker` option of a download, keeping the
+    /// trackers it already has. Returns how many trackers were new.
     pub async fn inject_trackers(
         &self,
         client: &Aria2Client,
         gid: &str,
         trackers: Vec<String>,
-    ) -> anyhow::Result<()> {
-        log::info!(
-            "Injecting {} trackers into download {}...",
-            trackers.len(),
-            gid
-        );
-        let trackers_str = trackers.join(",");
-        let options = serde_json::json!({
-            "bt-tracker": trackers_str
-        });
-        client.change_option(gid, options).await?;
+    ) -> anyhow::Result<usize> {
+        let options = client.get_option(gid).await?;
+        let existing = options
+            .get("bt-tracker")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        let (merged, added) = merge_trackers(existing, &trackers);
+        if added == 0 {
+            return Ok(0);
+        }
+
+        log::info!("Injecting {added} trackers into download {gid}...");
+        client
+            .change_option(gid, serde_json::json!({ "bt-tracker": merged.join(",") }))
+            .await?;
+        Ok(added)
+    }
+
+    /// Returns the tracker list, fetching it again once it is older than
+    /// `tracker_refresh_secs`. A stale list is kept if fetching fails.
+    pub async fn tracker_list(&self) -> anyhow::Result<Vec<String>> {
+        let now = chrono::Utc::now().timestamp();
+        let refresh_secs = i64::try_from(self.config.tracker_refresh_secs).unwrap_or(i64::MAX);
+        if let Some((fetched_at, trackers)) = self.tracker_cache.read().await.as_ref() {
+            if now.saturating_sub(*fetched_at) < refresh_secs {
+                return Ok(trackers.clone());
+            }
+        }
+
+        let scraper = if let Some(path) = &self.config.tracker_list_path {
+            TrackerScraper::from_path(path)
+        } else if let Some(url) = &self.config.tracker_list_url {
+            TrackerScraper::new(url.clone())
+        } else {
+            anyhow::bail!("Tracker injection needs tracker_list_url or tracker_list_path");
+        };
+
+        let mut cache = self.tracker_cache.write().await;
+        match scraper.fetch_trackers().await {
+            Ok(trackers) => {
+                log::info!("Fetched {} trackers for injection", trackers.len());
+                *cache = Some((now, trackers.clone()));
+                Ok(trackers)
+            }
+            Err(e) => match cache.as_ref() {
+                Some((_, trackers)) => {
+                    log::warn!("Failed to refresh tracker list, keeping the previous one: {e}");
+                    Ok(trackers.clone())
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    /// Injects trackers into active torrents of `client` that have had no
+    /// seeders and no connections for `stall_timeout_secs`. Each download is
+    /// injected into at most once.
+    pub async fn inject_stalled_trackers(&self, client: &Aria2Client) -> anyhow::Result<()> {
+        let keys = STALL_KEYS.iter().map(ToString::to_string).collect();
+        let active: Vec<DownloadStatus> =
+            serde_json::from_value(client.tell_active(Some(keys)).await?)?;
+        let now = chrono::Utc::now().timestamp();
+        let stall_timeout_secs = i64::try_from(self.config.stall_timeout_secs).unwrap_or(i64::MAX);
+
+        let due = client.trackers.update(&active, now, stall_timeout_secs);
+        if due.is_empty() {
+            return Ok(());
+        }
+
+        let trackers = self.tracker_list().await?;
+        for gid in due {
+            match self.inject_trackers(client, &gid, trackers.clone()).await {
+                Ok(added) => client.trackers.record(&gid, added, now),
+                Err(e) => log::error!("Failed to inject trackers into download {gid}: {e}"),
+            }
+        }
         Ok(())
     }
 }
 
+/// Builds a magnet link for a torrent from its info hash, name and trackers.
+#[must_use]
+pub fn magnet_link<'a>(
+    info_hash: &str,
+    name: Option<&str>,
+    trackers: impl Iterator<Item = &'a str>,
+) -> String {
+    let mut pairs = Vec::new();
+    if let Some(name) = name {
+        pairs.push(("dn", name));
+    }
+    pairs.extend(trackers.map(|t| ("tr", t)));
+
+    let mut link = format!("magnet:?xt=urn:btih:{info_hash}");
+    if let Ok(mut url) = reqwest::Url::parse("magnet:") {
+        url.query_pairs_mut().extend_pairs(pairs);
+        if let Some(query) = url.query().filter(|q| !q.is_empty()) {
+            link.push('&');
+            link.push_str(query);
+        }
+    }
+    link
+}
+
+/// Halves a numeric aria2 option, down to 1. `default` is used if the option
+/// is not set.
+fn halve_option(options: &mut serde_json::Value, key: &str, default: u64) {
+    let current = options
+        .get(key)
+        .and_then(|v| v.as_str())
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(default);
+    options[key] = serde_json::json!((current / 2).max(1).to_string());
+}
+
+/// Where a failed download stands in the recovery task.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryState {
+    /// Retries scheduled so far, carried over to the download that retried it
+    pub attempts: u32,
+    /// Strategy of the last scheduled retry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<RecoveryStrategy>,
+    /// When the scheduled retry runs, while it is pending
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    /// Set once recovery stopped retrying the download
+    #[serde(default)]
+    pub gave_up: bool,
+}
+
+/// Per-instance retry state of failed downloads, keyed by GID.
+#[derive(Default)]
+pub struct RetryStates {
+    states: Mutex<HashMap<String, RetryState>>,
+    dirty: AtomicBool,
+}
+
+impl RetryStates {
+    #[must_use]
+    pub fn get(&self, gid: &str) -> Option<RetryState> {
+        self.states.lock().unwrap().get(gid).cloned()
+    }
+
+    /// Every tracked download, sorted by GID.
+    #[must_use]
+    pub fn all(&self) -> Vec<(String, RetryState)> {
+        let mut states: Vec<_> = self
+            .states
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(gid, state)| (gid.clone(), state.clone()))
+            .collect();
+        states.sort_by(|a, b| a.0.cmp(&b.0));
+        states
+    }
+
+    pub fn insert(&self, gid: &str, state: RetryState) {
+        self.states.lock().unwrap().insert(gid.to_string(), state);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Forgets `gid`, giving it its full retry budget again. A pending retry
+    /// does not run.
+    pub fn remove(&self, gid: &str) -> Option<RetryState> {
+        let state = self.states.lock().unwrap().remove(gid);
+        if state.is_some() {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+        state
+    }
+
+    /// Marks the scheduled retry of `gid` as no longer pending.
+    pub fn clear_pending(&self, gid: &str) {
+        if let Some(state) = self.states.lock().unwrap().get_mut(gid) {
+            state.due_at = None;
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Stops recovering `gid`: a pending retry does not run and no new one is
+    /// scheduled.
+    pub fn cancel(&self, gid: &str) -> RetryState {
+        let mut states = self.states.lock().unwrap();
+        let state = states.entry(gid.to_string()).or_default();
+        state.due_at = None;
+        state.gave_up = true;
+        self.dirty.store(true, Ordering::Relaxed);
+        state.clone()
+    }
+
+    /// Schedules the retry of `gid` for `due_at`, replacing any pending one.
+    pub fn set_due(&self, gid: &str, due_at: i64) {
+        let mut states = self.states.lock().unwrap();
+        states.entry(gid.to_string()).or_default().due_at = Some(due_at);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the retry of `gid` scheduled for `due_at` is still pending, so
+    /// a timer set for it should run it.
+    #[must_use]
+    pub fn is_due(&self, gid: &str, due_at: i64) -> bool {
+        self.get(gid).is_some_and(|s| s.due_at == Some(due_at))
+    }
+
+    /// Removes `gid` once it was retried, returning its state so it can move
+    /// to the new download.
+    fn finish(&self, gid: &str) -> Option<RetryState> {
+        let mut state = self.remove(gid)?;
+        state.due_at = None;
+        state.error_code = None;
+        state.error_message = None;
+        state.gave_up = false;
+        Some(state)
+    }
+
+    #[must_use]
+    pub fn snapshot(&self) -> HashMap<String, RetryState> {
+        self.states.lock().unwrap().clone()
+    }
+
+    /// Adds persisted states without replacing ones recorded since startup.
+    pub fn restore(&self, saved: &HashMap<String, RetryState>) {
+        let mut states = self.states.lock().unwrap();
+        for (gid, state) in saved {
+            states.entry(gid.clone()).or_insert_with(|| state.clone());
+        }
+    }
+
+    /// Keeps only the downloads for which `keep` returns true.
+    pub fn retain(&self, mut keep: impl FnMut(&str, &RetryState) -> bool) {
+        let mut states = self.states.lock().unwrap();
+        let before = states.len();
+        states.retain(|gid, state| keep(gid, state));
+        if states.len() != before {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns whether the states changed since the last call.
+    pub fn take_dirty(&self) -> bool {
+        self.dirty.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// Recovery state of one instance as kept in the state file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SavedRecovery {
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub retries: HashMap<String, RetryState>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lineage: Vec<RetryLink>,
+}
+
+impl SavedRecovery {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.retries.is_empty() && self.lineage.is_empty()
+    }
+}
+
+/// A failed download and the download that retried it.
+#[derive(Debug, Clone, PartialEq, Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryLink {
+    pub from: String,
+    pub to: String,
+    /// Retry number, starting at 1
+    pub attempt: u32,
+    pub retried_at: i64,
+    /// Instance `to` was added to, if the retry switched instances
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+}
+
+/// Per-instance record of which download retried which, so a download can be
+/// followed across the GIDs it had.
+#[derive(Default)]
+pub struct RetryLineage {
+    /// Keyed by the GID that was retried
+    links: Mutex<HashMap<String, RetryLink>>,
+    dirty: AtomicBool,
+}
+
+impl RetryLineage {
+    pub fn record(
+        &self,
+        from: &str,
+        to: &str,
+        attempt: u32,
+        retried_at: i64,
+        instance: Option<String>,
+    ) {
+        self.links.lock().unwrap().insert(
+            from.to_string(),
+            RetryLink {
+                from: from.to_string(),
+                to: to.to_string(),
+                attempt,
+                retried_at,
+                instance,
+            },
+        );
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Adds persisted links without replacing ones recorded since startup.
+    pub fn restore(&self, saved: &[RetryLink]) {
+        let mut links = self.links.lock().unwrap();
+        for link in saved {
+            links
+                .entry(link.from.clone())
+                .or_insert_with(|| link.clone());
+        }
+    }
+
+    /// Keeps only the links for which `keep` returns true.
+    pub fn retain(&self, mut keep: impl FnMut(&RetryLink) -> bool) {
+        let mut links = self.links.lock().unwrap();
+        let before = links.len();
+        links.retain(|_, link| keep(link));
+        if links.len() != before {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns whether links changed since the last call.
+    pub fn take_dirty(&self) -> bool {
+        self.dirty.swap(false, Ordering::Relaxed)
+    }
+
+    /// The download that retried `gid`, if any.
+    #[must_use]
+    pub fn successor(&self, gid: &str) -> Option<String> {
+        self.links.lock().unwrap().get(gid).map(|l| l.to.clone())
+    }
+
+    /// The links leading from the original download to the latest retry,
+    /// for any GID along the way. Empty if `gid` was never retried.
+    #[must_use]
+    pub fn chain(&self, gid: &str) -> Vec<RetryLink> {
+        let links = self.links.lock().unwrap();
+        let mut origin = gid.to_string();
+        for _ in 0..links.len() {
+            match links.values().find(|l| l.to == origin) {
+                Some(link) => origin = link.from.clone(),
+                None => break,
+            }
+        }
+
+        let mut chain = Vec::new();
+        while let Some(link) = links.get(&origin) {
+            if chain.len() == links.len() {
+                break;
+            }
+            origin = link.to.clone();
+            chain.push(link.clone());
+        }
+        chain
+    }
+
+    #[must_use]
+    pub fn links(&self) -> Vec<RetryLink> {
+        let mut links: Vec<_> = self.links.lock().unwrap().values().cloned().collect();
+        links.sort_by_key(|l| l.retried_at);
+        links
+    }
+}
+
+/// Appends the trackers in `new` that are not yet in the comma separated
+/// `existing` list. Returns the merged list and how many were added.
+#[must_use]
+pub fn merge_trackers(existing: &str, new: &[String]) -> (Vec<String>, usize) {
+    let mut merged: Vec<String> = existing
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(ToString::to_string)
+        .collect();
+    let mut seen: HashSet<String> = merged.iter().cloned().collect();
+    let mut added = 0;
+    for tracker in new {
+        if seen.insert(tracker.clone()) {
+            merged.push(tracker.clone());
+            added += 1;
+        }
+    }
+    (merged, added)
+}
+
+/// Trackers injected into a stalled torrent.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackerInjection {
+    pub gid: String,
+    pub trackers_added: usize,
+    pub injected_at: i64,
+}
+
+#[derive(Default)]
+struct InjectionState {
+    /// When each torrent was first seen without seeders or connections
+    stalled_since: HashMap<String, i64>,
+    injected: HashMap<String, TrackerInjection>,
+}
+
+/// Per-instance record of stalled torrents and the trackers injected into
+/// them, reported by `check_health`.
+#[derive(Default)]
+pub struct TrackerInjections {
+    state: Mutex<InjectionState>,
+}
+
+impl TrackerInjections {
+    /// Updates which torrents are stalled from the active downloads and
+    /// returns those stalled for at least `stall_timeout_secs` that have not
+    /// been injected into yet. Downloads no longer active are forgotten.
+    pub fn update(
+        &self,
+        active: &[DownloadStatus],
+        now: i64,
+        stall_timeout_secs: i64,
+    ) -> Vec<String> {
+        let mut state = self.state.lock().unwrap();
+        let active_gids: HashSet<&str> = active.iter().map(|d| d.gid.as_str()).collect();
+        state
+            .injected
+            .retain(|gid, _| active_gids.contains(gid.as_str()));
+
+        let stalled: HashSet<&str> = active
+            .iter()
+            .filter(|d| is_stalled_torrent(d))
+            .map(|d| d.gid.as_str())
+            .collect();
+        state
+            .stalled_since
+            .retain(|gid, _| stalled.contains(gid.as_str()));
+
+        let mut due = Vec::new();
+        for gid in stalled {
+            let since = *state.stalled_since.entry(gid.to_string()).or_insert(now);
+            if now - since >= stall_timeout_secs && !state.injected.contains_key(gid) {
+                due.push(gid.to_string());
+            }
+        }
+        due.sort();
+        due
+    }
+
+    pub fn record(&self, gid: &str, trackers_added: usize, now: i64) {
+        self.state.lock().unwrap().injected.insert(
+            gid.to_string(),
+            TrackerInjection {
+                gid: gid.to_string(),
+                trackers_added,
+                injected_at: now,
+            },
+        );
+    }
+
+    /// Injections into downloads that are still active, oldest first.
+    #[must_use]
+    pub fn injections(&self) -> Vec<TrackerInjection> {
+        let mut injections: Vec<_> = self
+            .state
+            .lock()
+            .unwrap()
+            .injected
+            .values()
+            .cloned()
+            .collect();
+        injections.sort_by_key(|i| i.injected_at);
+        injections
+    }
+}
+
+/// A torrent that is still downloading but has no seeders and no connections.
+fn is_stalled_torrent(download: &DownloadStatus) -> bool {
+    download.info_hash.is_some()
+        && download.seeder != Some(true)
+        && (download.total_length == 0 || download.completed_length < download.total_length)
+        && download.connections == 0
+        && download.num_seeders.unwrap_or(0) == 0
+}
+
 pub struct TrackerScraper {
-    url: String,
+    source: TrackerSource,
+}
+
+enum TrackerSource {
+    Url(String),
+    Path(PathBuf),
 }
 
 impl TrackerScraper {
     #[must_use]
     pub fn new(url: String) -> Self {
-        Self { url }
+        Self {
+            source: TrackerSource::Url(url),
+        }
+    }
+
+    /// Reads the tracker list from a local file instead of a URL.
+    #[must_use]
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        Self {
+            source: TrackerSource::Path(path.into()),
+        }
     }
 
     pub async fn fetch_trackers(&self) -> anyhow::Result<Vec<String>> {
-        let resp = reqwest::get(&self.url).await?.text().await?;
+        let resp = match &self.source {
+            TrackerSource::Url(url) => reqwest::get(url).await?.error_for_status()?.text().await?,
+            TrackerSource::Path(path) => tokio::fs::read_to_string(path).await?,
+        };
         let trackers: Vec<String> = resp
             .lines()
             .map(|l| l.trim().to_string())
-            .filter(|l| !l.is_empty())
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
             .collect();
         Ok(trackers)
     }
@@ -0,0 +1,401 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use tokio::process::{Child, Command};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration, Instant};
+
+use super::Aria2Client;
+use crate::config::{Aria2Instance, Config, ManagedAria2Config};
+
+/// A process that stays up this long is considered healthy again, and its
+/// next exit starts a fresh count of restarts.
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+
+/// How long aria2 gets to save its session after SIGTERM before it is killed.
+const TERMINATE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessState {
+    Running,
+    Restarting,
+    Stopped,
+    /// Gave up after `max_restarts` consecutive exits
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupervisorStatus {
+    pub state: ProcessState,
+    pub pid: Option<u32>,
+    /// Restarts since the server started
+    pub restarts: u32,
+    pub last_exit: Option<String>,
+    pub since: DateTime<Utc>,
+}
+
+impl SupervisorStatus {
+    fn set(&mut self, state: ProcessState, pid: Option<u32>) {
+        self.state = state;
+        self.pid = pid;
+        self.since = Utc::now();
+    }
+}
+
+/// Starts a local `aria2c`, restarts it when it fails and stops it on
+/// [`Aria2Supervisor::stop`]. The child is also killed if the server exits
+/// without stopping it.
+pub struct Aria2Supervisor {
+    instance: Aria2Instance,
+    conf_path: PathBuf,
+    status: Arc<Mutex<SupervisorStatus>>,
+    stop_tx: watch::Sender<bool>,
+    task: tokio::sync::Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Aria2Supervisor {
+    /// Spawns `aria2c` and waits until its RPC interface answers.
+    pub async fn start(config: ManagedAria2Config) -> Result<Self> {
+        let port = match config.port {
+            Some(port) => port,
+            None => free_port()?,
+        };
+        let secret = config
+            .rpc_secret
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+        let instance = Aria2Instance {
+            name: config.name.clone(),
+            rpc_url: format!("http://127.0.0.1:{port}/jsonrpc"),
+            rpc_secret: Some(secret.clone()),
+            rpc_transport: None,
+        };
+
+        prepare_session_file(&config.session_file)?;
+        // The secret goes in a file only we can read rather than in argv,
+        // which other local users can see
+        let conf_path = PathBuf::from(format!("{}.conf", config.session_file));
+        write_conf(&conf_path, &rpc_conf(port, &secret))?;
+        let args = aria2c_args(&config, &conf_path);
+        let child = match spawn(&config.binary, &args) {
+            Ok(child) => wait_ready(child, &instance, config.startup_timeout_secs).await,
+            Err(e) => Err(e),
+        };
+        let child = child.inspect_err(|_| {
+            let _ = std::fs::remove_file(&conf_path);
+        })?;
+        log::info!(
+            "Started aria2c for instance {} on port {port} (pid {})",
+            instance.name,
+            child.id().unwrap_or_default()
+        );
+
+        let status = Arc::new(Mutex::new(SupervisorStatus {
+            state: ProcessState::Running,
+            pid: child.id(),
+            restarts: 0,
+            last_exit: None,
+            since: Utc::now(),
+        }));
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let task = tokio::spawn(supervise(config, args, child, Arc::clone(&status), stop_rx));
+
+        Ok(Self {
+            instance,
+            conf_path,
+            status,
+            stop_tx,
+            task: tokio::sync::Mutex::new(Some(task)),
+        })
+    }
+
+    /// The instance clients use to reach the supervised process.
+    #[must_use]
+    pub fn instance(&self) -> &Aria2Instance {
+        &self.instance
+    }
+
+    /// Adds the supervised instance to `config.instances`, replacing any
+    /// instance with the same name.
+    pub fn register(&self, config: &mut Config) {
+        if let Some(existing) = config
+            .instances
+            .iter_mut()
+            .find(|i| i.name == self.instance.name)
+        {
+            *existing = self.instance.clone();
+        } else {
+            config.instances.push(self.instance.clone());
+        }
+    }
+
+    #[must_use]
+    pub fn status(&self) -> SupervisorStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Stops supervising and terminates aria2, giving it a chance to save
+    /// its session.
+    pub async fn stop(&self) {
+        let _ = self.stop_tx.send(true);
+        if let Some(task) = self.task.lock().await.take() {
+            let _ = task.await;
+        }
+        let _ = std::fs::remove_file(&self.conf_path);
+    }
+}
+
+async fn supervise(
+    config: ManagedAria2Config,
+    args: Vec<String>,
+    mut child: Child,
+    status: Arc<Mutex<SupervisorStatus>>,
+    mut stop_rx: watch::Receiver<bool>,
+) {
+    let mut consecutive_restarts = 0;
+    loop {
+        let started = Instant::now();
+        let exit = tokio::select! {
+            exit = child.wait() => exit,
+            _ = stop_rx.changed() => {
+                terminate(&mut child).await;
+                status.lock().unwrap().set(ProcessState::Stopped, None);
+                return;
+            }
+        };
+        // aria2 exits with 0 when shut down on purpose, e.g. through
+        // `aria2.shutdown`, so only failures are restarted
+        if let Some(exit) = exit.as_ref().ok().filter(|s| s.success()) {
+            log::info!(
+                "aria2c for instance {} shut down ({exit}), not restarting it",
+                config.name
+            );
+            let mut status = status.lock().unwrap();
+            status.last_exit = Some(exit.to_string());
+            status.set(ProcessState::Stopped, None);
+            return;
+        }
+        let exit = exit.map_or_else(|e| e.to_string(), |s| s.to_string());
+        log::warn!("aria2c for instance {} exited ({exit})", config.name);
+        status.lock().unwrap().last_exit = Some(exit);
+        if started.elapsed() >= STABLE_UPTIME {
+            consecutive_restarts = 0;
+        }
+
+        child = loop {
+            if consecutive_restarts >= config.max_restarts {
+                log::error!(
+                    "aria2c for instance {} exited {consecutive_restarts} times in a row, giving up",
+                    config.name
+                );
+                status.lock().unwrap().set(ProcessState::Failed, None);
+                return;
+            }
+            consecutive_restarts += 1;
+            {
+                let mut status = status.lock().unwrap();
+                status.set(ProcessState::Restarting, None);
+                status.restarts += 1;
+            }
+
+            tokio::select! {
+                () = sleep(Duration::from_secs(config.restart_delay_secs)) => {}
+                _ = stop_rx.changed() => {
+                    status.lock().unwrap().set(ProcessState::Stopped, None);
+                    return;
+                }
+            }
+
+            match spawn(&config.binary, &args) {
+                Ok(child) => break child,
+                Err(e) => {
+                    log::error!("Failed to restart aria2c for instance {}: {e}", config.name);
+                    status.lock().unwrap().last_exit = Some(e.to_string());
+                }
+            }
+        };
+        log::info!(
+            "Restarted aria2c for instance {} (pid {})",
+            config.name,
+            child.id().unwrap_or_default()
+        );
+        status
+            .lock()
+            .unwrap()
+            .set(ProcessState::Running, child.id());
+    }
+}
+
+/// The RPC options of the managed process, in aria2's configuration file
+/// format.
+fn rpc_conf(port: u16, secret: &str) -> String {
+    format!("enable-rpc=true\nrpc-listen-all=false\nrpc-listen-port={port}\nrpc-secret={secret}\n")
+}
+
+/// Writes the configuration file readable only by the current user.
+fn write_conf(path: &Path, contents: &str) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // The mode only applies to new files
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    std::io::Write::write_all(&mut file, contents.as_bytes())
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn aria2c_args(config: &ManagedAria2Config, conf_path: &Path) -> Vec<String> {
+    let mut args = vec![
+        format!("--conf-path={}", conf_path.display()),
+        format!("--input-file={}", config.session_file),
+        format!("--save-session={}", config.session_file),
+        "--save-session-interval=60".to_string(),
+        // aria2 exits on its own if the server dies without stopping it
+        format!("--stop-with-process={}", std::process::id()),
+    ];
+    if let Some(dir) = &config.dir {
+        args.push(format!("--dir={dir}"));
+    }
+    args.extend(config.extra_args.iter().cloned());
+    args
+}
+
+fn spawn(binary: &str, args: &[String]) -> Result<Child> {
+    Command::new(binary)
+        .args(args)
+        .stdin(Stdio::null())
+        // stdout carries the MCP protocol when serving over stdio
+        .stdout(Stdio::null())
+        .stderr(Stdio::inherit())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start {binary}"))
+}
+
+async fn wait_ready(
+    mut child: Child,
+    instance: &Aria2Instance,
+    timeout_secs: u64,
+) -> Result<Child> {
+    let client = Aria2Client::new_with_instance(
+        Config {
+            rpc_timeout_secs: 2,
+            ..Config::default()
+        },
+        instance.clone(),
+    );
+    let deadline = Instant::now() + Duration::from_secs(timeout_secs);
+    loop {
+        if let Some(exit) = child.try_wait()? {
+            anyhow::bail!("aria2c exited during startup ({exit})");
+        }
+        if client.get_version().await.is_ok() {
+            return Ok(child);
+        }
+        if Instant::now() >= deadline {
+            terminate(&mut child).await;
+            anyhow::bail!(
+                "aria2c did not answer on {} within {timeout_secs}s",
+                instance.rpc_url
+            );
+        }
+        sleep(Duration::from_millis(200)).await;
+    }
+}
+
+/// Asks aria2 to exit with SIGTERM, which saves the session, and kills it if
+/// it does not exit in time.
+async fn terminate(child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: `pid` is our own child, which has not been reaped yet
+        unsafe {
+            libc::kill(pid as libc::pid_t, libc::SIGTERM);
+        }
+        if tokio::time::timeout(TERMINATE_TIMEOUT, child.wait())
+            .await
+            .is_ok()
+        {
+            return;
+        }
+    }
+    let _ = child.kill().await;
+}
+
+fn free_port() -> Result<u16> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    Ok(listener.local_addr()?.port())
+}
+
+/// aria2 refuses to start if `--input-file` does not exist.
+fn prepare_session_file(path: &str) -> Result<()> {
+    let path = std::path::Path::new(path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to create session file {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aria2c_args() {
+        let config = ManagedAria2Config {
+            session_file: "/data/aria2.session".to_string(),
+            dir: Some("/data/downloads".to_string()),
+            extra_args: vec!["--max-concurrent-downloads=3".to_string()],
+            ..ManagedAria2Config::default()
+        };
+        let args = aria2c_args(&config, Path::new("/data/aria2.session.conf"));
+        assert_eq!(args[0], "--conf-path=/data/aria2.session.conf");
+        assert!(args.contains(&"--input-file=/data/aria2.session".to_string()));
+        assert!(args.contains(&"--save-session=/data/aria2.session".to_string()));
+        assert!(args.contains(&"--dir=/data/downloads".to_string()));
+        assert_eq!(args.last().unwrap(), "--max-concurrent-downloads=3");
+    }
+
+    #[test]
+    fn test_rpc_conf_keeps_secret_out_of_argv() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("aria2.session.conf");
+        std::fs::write(&path, "stale").unwrap();
+
+        write_conf(&path, &rpc_conf(6801, "s3cret")).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("rpc-listen-port=6801\n"));
+        assert!(contents.contains("rpc-secret=s3cret\n"));
+        assert!(!contents.contains("stale"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let config = ManagedAria2Config::default();
+        assert!(aria2c_args(&config, &path)
+            .iter()
+            .all(|arg| !arg.contains("s3cret")));
+    }
+}
@@ -0,0 +1,394 @@
+use serde::{Deserialize, Serialize};
+
+/// State of a download as reported by `aria2.tellStatus`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadState {
+    Active,
+    Waiting,
+    Paused,
+    Error,
+    Complete,
+    Removed,
+}
+
+impl DownloadState {
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Waiting => "waiting",
+            Self::Paused => "paused",
+            Self::Error => "error",
+            Self::Complete => "complete",
+            Self::Removed => "removed",
+        }
+    }
+}
+
+/// Response of `aria2.tellStatus`, `aria2.tellActive`, `aria2.tellWaiting`
+/// and `aria2.tellStopped`. Every field is optional on the wire because the
+/// caller may restrict the returned keys.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadStatus {
+    #[serde(default)]
+    pub gid: String,
+    #[serde(default)]
+    pub status: Option<DownloadState>,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub total_length: u64,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub completed_length: u64,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub upload_length: u64,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub download_speed: u64,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub upload_speed: u64,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub connections: u64,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub piece_length: u64,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub num_pieces: u64,
+    #[serde(default, deserialize_with = "de::opt_u64")]
+    pub num_seeders: Option<u64>,
+    #[serde(default, deserialize_with = "de::opt_bool")]
+    pub seeder: Option<bool>,
+    #[serde(default)]
+    pub info_hash: Option<String>,
+    #[serde(default, deserialize_with = "de::opt_u64")]
+    pub error_code: Option<u64>,
+    #[serde(default)]
+    pub error_message: Option<String>,
+    #[serde(default)]
+    pub followed_by: Vec<String>,
+    #[serde(default)]
+    pub following: Option<String>,
+    #[serde(default)]
+    pub belongs_to: Option<String>,
+    #[serde(default)]
+    pub dir: Option<String>,
+    #[serde(default)]
+    pub files: Vec<FileEntry>,
+    #[serde(default)]
+    pub bittorrent: Option<BitTorrentInfo>,
+}
+
+impl DownloadStatus {
+    /// Best-effort display name: the torrent name, else the first file name.
+    #[must_use]
+    pub fn name(&self) -> Option<String> {
+        if let Some(name) = self
+            .bittorrent
+            .as_ref()
+            .and_then(|bt| bt.info.as_ref())
+            .map(|info| info.name.clone())
+        {
+            return Some(name);
+        }
+        self.files.first().and_then(|f| {
+            std::path::Path::new(&f.path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .or_else(|| f.uris.first().map(|u| u.uri.clone()))
+        })
+    }
+
+    /// Every URI across all files of the download.
+    pub fn uris(&self) -> impl Iterator<Item = &str> {
+        self.files
+            .iter()
+            .flat_map(|f| f.uris.iter().map(|u| u.uri.as_str()))
+    }
+
+    /// Every tracker URL in the torrent's announce list.
+    pub fn trackers(&self) -> impl Iterator<Item = &str> {
+        self.bittorrent
+            .iter()
+            .flat_map(|bt| bt.announce_list.iter().flatten().map(String::as_str))
+    }
+}
+
+/// Response item of `aria2.getFiles`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FileEntry {
+    #[serde(default, deserialize_with = "de::u64")]
+    pub index: u64,
+    #[serde(default)]
+    pub path: String,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub length: u64,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub completed_length: u64,
+    #[serde(default, deserialize_with = "de::bool")]
+    pub selected: bool,
+    #[serde(default)]
+    pub uris: Vec<UriEntry>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UriStatus {
+    Used,
+    Waiting,
+}
+
+/// Response item of `aria2.getUris`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UriEntry {
+    pub uri: String,
+    #[serde(default)]
+    pub status: Option<UriStatus>,
+}
+
+/// Response item of `aria2.getPeers`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Peer {
+    #[serde(default)]
+    pub peer_id: String,
+    #[serde(default)]
+    pub ip: String,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub port: u64,
+    #[serde(default)]
+    pub bitfield: String,
+    #[serde(default, deserialize_with = "de::bool")]
+    pub am_choking: bool,
+    #[serde(default, deserialize_with = "de::bool")]
+    pub peer_choking: bool,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub download_speed: u64,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub upload_speed: u64,
+    #[serde(default, deserialize_with = "de::bool")]
+    pub seeder: bool,
+}
+
+/// Response of `aria2.getGlobalStat`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobalStat {
+    #[serde(default, deserialize_with = "de::u64")]
+    pub download_speed: u64,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub upload_speed: u64,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub num_active: u64,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub num_waiting: u64,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub num_stopped: u64,
+    #[serde(default, deserialize_with = "de::u64")]
+    pub num_stopped_total: u64,
+}
+
+/// Response of `aria2.getVersion`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionInfo {
+    pub version: String,
+    #[serde(default)]
+    pub enabled_features: Vec<String>,
+}
+
+/// Response of `aria2.getSessionInfo`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub session_id: String,
+}
+
+/// Active, waiting and stopped downloads plus the global stat, fetched
+/// together in one `system.multicall`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueSnapshot {
+    pub active: Vec<DownloadStatus>,
+    pub waiting: Vec<DownloadStatus>,
+    pub stopped: Vec<DownloadStatus>,
+    pub global_stat: GlobalStat,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BitTorrentInfo {
+    #[serde(default)]
+    pub announce_list: Vec<Vec<String>>,
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub creation_date: Option<u64>,
+    #[serde(default)]
+    pub mode: Option<String>,
+    #[serde(default)]
+    pub info: Option<BitTorrentName>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BitTorrentName {
+    pub name: String,
+}
+
+/// aria2 encodes numbers and booleans as JSON strings; these helpers accept
+/// both the string and the native JSON form.
+mod de {
+    use serde::{Deserialize, Deserializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StrOrU64 {
+        Str(String),
+        Num(u64),
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StrOrBool {
+        Str(String),
+        Bool(bool),
+    }
+
+    pub fn u64<'de, D: Deserializer<'de>>(d: D) -> Result<u64, D::Error> {
+        Ok(opt_u64(d)?.unwrap_or(0))
+    }
+
+    pub fn opt_u64<'de, D: Deserializer<'de>>(d: D) -> Result<Option<u64>, D::Error> {
+        match Option::<StrOrU64>::deserialize(d)? {
+            None => Ok(None),
+            Some(StrOrU64::Num(n)) => Ok(Some(n)),
+            Some(StrOrU64::Str(s)) if s.is_empty() => Ok(None),
+            Some(StrOrU64::Str(s)) => s.parse().map(Some).map_err(serde::de::Error::custom),
+        }
+    }
+
+    pub fn bool<'de, D: Deserializer<'de>>(d: D) -> Result<bool, D::Error> {
+        Ok(opt_bool(d)?.unwrap_or(false))
+    }
+
+    pub fn opt_bool<'de, D: Deserializer<'de>>(d: D) -> Result<Option<bool>, D::Error> {
+        match Option::<StrOrBool>::deserialize(d)? {
+            None => Ok(None),
+            Some(StrOrBool::Bool(b)) => Ok(Some(b)),
+            Some(StrOrBool::Str(s)) => Ok(Some(s == "true")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_download_status_parses_string_numbers() {
+        let status: DownloadStatus = serde_json::from_value(json!({
+            "gid": "2089b05ecca3d829",
+            "status": "active",
+            "totalLength": "34896138",
+            "completedLength": "34896138",
+            "downloadSpeed": "0",
+            "connections": 3,
+            "errorCode": "0",
+            "seeder": "true",
+            "files": [{
+                "index": "1",
+                "path": "/downloads/file.iso",
+                "length": "34896138",
+                "completedLength": "34896138",
+                "selected": "true",
+                "uris": [{ "uri": "http://example.com/file.iso", "status": "used" }]
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(status.status, Some(DownloadState::Active));
+        assert_eq!(status.total_length, 34_896_138);
+        assert_eq!(status.connections, 3);
+        assert_eq!(status.error_code, Some(0));
+        assert_eq!(status.seeder, Some(true));
+        assert!(status.files[0].selected);
+        assert_eq!(status.files[0].uris[0].status, Some(UriStatus::Used));
+        assert_eq!(status.name().as_deref(), Some("file.iso"));
+        assert_eq!(
+            status.uris().collect::<Vec<_>>(),
+            vec!["http://example.com/file.iso"]
+        );
+    }
+
+    #[test]
+    fn test_download_status_partial_keys() {
+        let status: DownloadStatus = serde_json::from_value(json!({ "gid": "1" })).unwrap();
+        assert_eq!(status.gid, "1");
+        assert_eq!(status.status, None);
+        assert_eq!(status.total_length, 0);
+        assert!(status.files.is_empty());
+    }
+
+    #[test]
+    fn test_download_status_bittorrent_name_and_trackers() {
+        let status: DownloadStatus = serde_json::from_value(json!({
+            "gid": "1",
+            "bittorrent": {
+                "announceList": [["http://tracker1/announce"], ["udp://tracker2:80"]],
+                "info": { "name": "Linux ISO" }
+            }
+        }))
+        .unwrap();
+        assert_eq!(status.name().as_deref(), Some("Linux ISO"));
+        assert_eq!(status.trackers().count(), 2);
+    }
+
+    #[test]
+    fn test_global_stat_and_version() {
+        let stat: GlobalStat = serde_json::from_value(json!({
+            "downloadSpeed": "1024",
+            "uploadSpeed": "512",
+            "numActive": "1",
+            "numWaiting": "2",
+            "numStopped": "3",
+            "numStoppedTotal": "4"
+        }))
+        .unwrap();
+        assert_eq!(stat.download_speed, 1024);
+        assert_eq!(stat.num_stopped_total, 4);
+
+        let version: VersionInfo = serde_json::from_value(json!({
+            "version": "1.36.0",
+            "enabledFeatures": ["BitTorrent", "Metalink"]
+        }))
+        .unwrap();
+        assert_eq!(version.version, "1.36.0");
+        assert_eq!(version.enabled_features.len(), 2);
+    }
+
+    #[test]
+    fn test_peer_parsing() {
+        let peer: Peer = serde_json::from_value(json!({
+            "peerId": "id",
+            "ip": "10.0.0.1",
+            "port": "6881",
+            "bitfield": "ff",
+            "amChoking": "false",
+            "peerChoking": "true",
+            "downloadSpeed": "10",
+            "uploadSpeed": "20",
+            "seeder": "false"
+        }))
+        .unwrap();
+        assert_eq!(peer.port, 6881);
+        assert!(peer.peer_choking);
+        assert!(!peer.am_choking);
+        assert_eq!(peer.upload_speed, 20);
+    }
+
+    #[test]
+    fn test_invalid_number_is_error() {
+        let result: Result<GlobalStat, _> =
+            serde_json::from_value(json!({ "downloadSpeed": "fast" }));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,359 @@
+use anyhow::Result;
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio_tungstenite::tungstenite::Message;
+
+use super::{Aria2Client, Aria2Notification, CompletionTracker};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    /// The connection loop has not been started
+    Idle,
+    Connecting,
+    Connected,
+    /// Waiting to reconnect after the connection failed or closed
+    Disconnected,
+}
+
+/// State of an instance's WebSocket connection to aria2.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionStatus {
+    pub state: ConnectionState,
+    /// Unix time the state last changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<i64>,
+    /// Why the last attempt failed or the connection closed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// Number of times the connection was established
+    pub connects: u64,
+}
+
+impl Default for ConnectionStatus {
+    fn default() -> Self {
+        Self {
+            state: ConnectionState::Idle,
+            since: None,
+            last_error: None,
+            connects: 0,
+        }
+    }
+}
+
+/// State shared by all clones of an [`Aria2Client`] for its persistent
+/// WebSocket: the writer for outgoing requests (set while connected), the
+/// requests awaiting a response, and where aria2 notifications are forwarded.
+pub(crate) struct WsRpc {
+    outgoing: watch::Sender<Option<mpsc::UnboundedSender<String>>>,
+    pending: Mutex<HashMap<String, oneshot::Sender<Value>>>,
+    notify_tx: Mutex<Option<mpsc::Sender<Aria2Notification>>>,
+    running: AtomicBool,
+    next_id: AtomicU64,
+    status: Mutex<ConnectionStatus>,
+}
+
+impl Default for WsRpc {
+    fn default() -> Self {
+        Self {
+            outgoing: watch::channel(None).0,
+            pending: Mutex::new(HashMap::new()),
+            notify_tx: Mutex::new(None),
+            running: AtomicBool::new(false),
+            next_id: AtomicU64::new(1),
+            status: Mutex::new(ConnectionStatus::default()),
+        }
+    }
+}
+
+impl WsRpc {
+    pub(crate) fn set_notification_sender(&self, tx: mpsc::Sender<Aria2Notification>) {
+        *self.notify_tx.lock().unwrap() = Some(tx);
+    }
+
+    /// Routes an incoming message: responses go to the waiting request with
+    /// the same id, everything else is treated as a notification from
+    /// `instance` and recorded in `completions`. Never waits, so the read
+    /// loop keeps reading responses while notifications are handled.
+    fn dispatch(&self, text: &str, instance: &str, completions: &CompletionTracker) {
+        let Ok(value) = serde_json::from_str::<Value>(text) else {
+            return;
+        };
+
+        if let Some(id) = value.get("id").and_then(Value::as_str) {
+            if let Some(tx) = self.pending.lock().unwrap().remove(id) {
+                let _ = tx.send(value);
+            }
+            return;
+        }
+
+        let Ok(mut notification) = serde_json::from_value::<Aria2Notification>(value) else {
+            return;
+        };
+        notification.instance = Some(instance.to_string());
+        completions.record(&notification);
+        self.notify(notification);
+    }
+
+    /// Forwards `notification` to the notification channel, if one is set.
+    /// The event is dropped if the channel is full: its consumers call aria2
+    /// over this connection, so waiting for room would stop the responses
+    /// they wait for from being read.
+    pub(crate) fn notify(&self, notification: Aria2Notification) {
+        let tx = self.notify_tx.lock().unwrap().clone();
+        let Some(tx) = tx else {
+            return;
+        };
+        match tx.try_send(notification) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(notification)) => {
+                log::warn!(
+                    "Notification channel full, dropping {:?} event for download {}",
+                    notification.method,
+                    notification.gid()
+                );
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                log::error!("Notification channel closed");
+                *self.notify_tx.lock().unwrap() = None;
+            }
+        }
+    }
+
+    pub(crate) fn status(&self) -> ConnectionStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Moves to `state`, keeping the last error unless a new one is given.
+    fn set_state(&self, state: ConnectionState, error: Option<String>) {
+        let mut status = self.status.lock().unwrap();
+        if status.state != state {
+            status.state = state;
+            status.since = Some(chrono::Utc::now().timestamp());
+        }
+        if state == ConnectionState::Connected {
+            status.connects += 1;
+        }
+        if error.is_some() {
+            status.last_error = error;
+        }
+    }
+
+    /// Drops every in-flight request so their callers see the disconnect.
+    fn fail_pending(&self) {
+        self.pending.lock().unwrap().clear();
+    }
+}
+
+impl Aria2Client {
+    /// Starts the WebSocket connection loop for this client unless it is
+    /// already running. The loop reconnects with exponential backoff.
+    pub(crate) fn ensure_ws_running(&self) {
+        if self.ws.running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let client = self.clone();
+        tokio::spawn(async move {
+            let ws = &client.ws;
+            let mut backoff = tokio::time::Duration::from_secs(1);
+            let max_backoff = tokio::time::Duration::from_secs(60);
+
+            loop {
+                ws.set_state(ConnectionState::Connecting, None);
+                match client.connect_notifications().await {
+                    Ok(ws_stream) => {
+                        log::info!("Connected to aria2 WebSocket for instance: {}", client.name);
+                        ws.set_state(ConnectionState::Connected, None);
+                        backoff = tokio::time::Duration::from_secs(1); // Reset backoff on success
+                        let (mut sink, mut stream) = ws_stream.split();
+                        let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
+                        ws.outgoing.send_replace(Some(out_tx));
+
+                        let reason = loop {
+                            tokio::select! {
+                                msg = stream.next() => match msg {
+                                    Some(Ok(Message::Text(text))) => ws.dispatch(&text, &client.name, &client.completions),
+                                    Some(Ok(Message::Close(_))) | None => break "Connection closed".to_string(),
+                                    Some(Err(e)) => {
+                                        log::error!("WebSocket error: {e}");
+                                        break e.to_string();
+                                    }
+                                    Some(Ok(_)) => {}
+                                },
+                                Some(request) = out_rx.recv() => {
+                                    if let Err(e) = sink.send(Message::text(request)).await {
+                                        log::error!("WebSocket send error: {e}");
+                                        break e.to_string();
+                                    }
+                                }
+                            }
+                        };
+
+                        ws.outgoing.send_replace(None);
+                        ws.fail_pending();
+                        ws.set_state(ConnectionState::Disconnected, Some(reason));
+                    }
+                    Err(e) => {
+                        log::error!(
+                            "Failed to connect to aria2 WebSocket for {}: {e}. Retrying in {backoff:?}...",
+                            client.name
+                        );
+                        ws.set_state(ConnectionState::Disconnected, Some(e.to_string()));
+                    }
+                }
+                tokio::time::sleep(backoff).await;
+                backoff = std::cmp::min(backoff * 2, max_backoff);
+            }
+        });
+    }
+
+    /// Sends a JSON-RPC request over the persistent WebSocket and waits for
+    /// the response carrying the same id.
+    pub(crate) async fn ws_request(&self, mut body: Value) -> Result<Value> {
+        self.ensure_ws_running();
+
+        let timeout_secs = {
+            let config = self.config.read().await;
+            config.rpc_timeout_secs
+        };
+
+        let id = format!(
+            "aria2-mcp-{}",
+            self.ws.next_id.fetch_add(1, Ordering::Relaxed)
+        );
+        body["id"] = json!(id);
+
+        let (tx, rx) = oneshot::channel();
+        self.ws.pending.lock().unwrap().insert(id.clone(), tx);
+
+        let result = tokio::time::timeout(tokio::time::Duration::from_secs(timeout_secs), async {
+            let sender = {
+                let mut connected = self.ws.outgoing.subscribe();
+                let sender = connected.wait_for(Option::is_some).await?;
+                sender.clone()
+            };
+            sender
+                .ok_or_else(|| anyhow::anyhow!("WebSocket not connected"))?
+                .send(body.to_string())
+                .map_err(|_| anyhow::anyhow!("WebSocket connection closed"))?;
+            rx.await
+                .map_err(|_| anyhow::anyhow!("WebSocket connection closed"))
+        })
+        .await;
+
+        self.ws.pending.lock().unwrap().remove(&id);
+
+        result.map_err(|_| anyhow::anyhow!("WebSocket RPC timed out after {timeout_secs}s"))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_dispatch_routes_response_by_id() {
+        let ws = WsRpc::default();
+        let (tx, rx) = oneshot::channel();
+        ws.pending
+            .lock()
+            .unwrap()
+            .insert("aria2-mcp-7".to_string(), tx);
+
+        ws.dispatch(
+            r#"{"jsonrpc":"2.0","id":"aria2-mcp-7","result":"OK"}"#,
+            "default",
+            &CompletionTracker::default(),
+        );
+
+        let response = rx.await.unwrap();
+        assert_eq!(response["result"], "OK");
+        assert!(ws.pending.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_forwards_notifications() {
+        let ws = WsRpc::default();
+        let (tx, mut rx) = mpsc::channel(1);
+        ws.set_notification_sender(tx);
+        let completions = CompletionTracker::default();
+
+        ws.dispatch(
+            r#"{"jsonrpc":"2.0","method":"aria2.onDownloadComplete","params":[{"gid":"1"}]}"#,
+            "remote",
+            &completions,
+        );
+
+        let notification = rx.recv().await.unwrap();
+        assert_eq!(notification.params[0].gid, "1");
+        assert_eq!(notification.instance.as_deref(), Some("remote"));
+        assert!(completions.stopped_at("1").is_some());
+    }
+
+    #[test]
+    fn test_notify_drops_events_when_channel_is_full() {
+        let ws = WsRpc::default();
+        let (tx, mut rx) = mpsc::channel(1);
+        ws.set_notification_sender(tx);
+        let completions = CompletionTracker::default();
+
+        for gid in ["1", "2"] {
+            ws.dispatch(
+                &format!(
+                    r#"{{"jsonrpc":"2.0","method":"aria2.onDownloadStart","params":[{{"gid":"{gid}"}}]}}"#
+                ),
+                "default",
+                &completions,
+            );
+        }
+
+        assert_eq!(rx.try_recv().unwrap().params[0].gid, "1");
+        assert!(rx.try_recv().is_err());
+        // Still forwarding once there is room again
+        ws.dispatch(
+            r#"{"jsonrpc":"2.0","method":"aria2.onDownloadStart","params":[{"gid":"3"}]}"#,
+            "default",
+            &completions,
+        );
+        assert_eq!(rx.try_recv().unwrap().params[0].gid, "3");
+    }
+
+    #[test]
+    fn test_connection_status_transitions() {
+        let ws = WsRpc::default();
+        assert_eq!(ws.status().state, ConnectionState::Idle);
+        assert!(ws.status().since.is_none());
+
+        ws.set_state(ConnectionState::Connecting, None);
+        ws.set_state(ConnectionState::Connected, None);
+        let status = ws.status();
+        assert_eq!(status.state, ConnectionState::Connected);
+        assert_eq!(status.connects, 1);
+        assert!(status.since.is_some());
+
+        ws.set_state(
+            ConnectionState::Disconnected,
+            Some("Connection closed".to_string()),
+        );
+        ws.set_state(ConnectionState::Connecting, None);
+        let status = ws.status();
+        assert_eq!(status.state, ConnectionState::Connecting);
+        assert_eq!(status.last_error.as_deref(), Some("Connection closed"));
+    }
+
+    #[tokio::test]
+    async fn test_fail_pending_closes_requests() {
+        let ws = WsRpc::default();
+        let (tx, rx) = oneshot::channel();
+        ws.pending.lock().unwrap().insert("1".to_string(), tx);
+        ws.fail_pending();
+        assert!(rx.await.is_err());
+    }
+}
@@ -0,0 +1,555 @@
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::aria2::{Aria2Client, DownloadStatus};
+use crate::config::{BandwidthProfile, DownloadLimit};
+
+/// Keys requested when listing downloads to match against limit rules.
+const MATCH_KEYS: [&str; 6] = ["gid", "status", "infoHash", "dir", "files", "bittorrent"];
+
+/// Options applied by the scheduled profile, with the values they had before
+/// so they can be restored when the profile ends or, for downloads, when
+/// they stop matching.
+#[derive(Default)]
+pub struct BandwidthOverrides {
+    overrides: Mutex<HashMap<String, DownloadOverride>>,
+    /// Global options the scheduled profile changed, as they were before
+    globals: Mutex<Map<String, Value>>,
+    dirty: AtomicBool,
+}
+
+/// Options a download limit rule applied to one download.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownloadOverride {
+    pub original: Map<String, Value>,
+    pub applied: Map<String, Value>,
+}
+
+/// Bandwidth overrides of one instance as kept in the state file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SavedBandwidth {
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub globals: Map<String, Value>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub downloads: HashMap<String, DownloadOverride>,
+}
+
+impl SavedBandwidth {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.globals.is_empty() && self.downloads.is_empty()
+    }
+}
+
+impl BandwidthOverrides {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.overrides.lock().unwrap().is_empty()
+    }
+
+    /// Options currently applied to `gid`, if a rule matched it.
+    #[must_use]
+    pub fn applied(&self, gid: &str) -> Option<Map<String, Value>> {
+        self.overrides
+            .lock()
+            .unwrap()
+            .get(gid)
+            .map(|o| o.applied.clone())
+    }
+
+    /// GIDs with applied options, sorted.
+    #[must_use]
+    pub fn gids(&self) -> Vec<String> {
+        let mut gids: Vec<String> = self.overrides.lock().unwrap().keys().cloned().collect();
+        gids.sort();
+        gids
+    }
+
+    /// Global options to restore when the scheduled profile ends.
+    #[must_use]
+    pub fn original_globals(&self) -> Map<String, Value> {
+        self.globals.lock().unwrap().clone()
+    }
+
+    #[must_use]
+    pub fn snapshot(&self) -> SavedBandwidth {
+        SavedBandwidth {
+            globals: self.original_globals(),
+            downloads: self.overrides.lock().unwrap().clone(),
+        }
+    }
+
+    /// Adds persisted overrides without replacing ones recorded since
+    /// startup.
+    pub fn restore(&self, saved: &SavedBandwidth) {
+        let mut globals = self.globals.lock().unwrap();
+        for (key, value) in &saved.globals {
+            globals.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+        let mut overrides = self.overrides.lock().unwrap();
+        for (gid, o) in &saved.downloads {
+            overrides.entry(gid.clone()).or_insert_with(|| o.clone());
+        }
+    }
+
+    /// Returns whether the overrides changed since the last call.
+    pub fn take_dirty(&self) -> bool {
+        self.dirty.swap(false, Ordering::Relaxed)
+    }
+
+    fn insert(&self, gid: String, o: DownloadOverride) {
+        self.overrides.lock().unwrap().insert(gid, o);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    fn remove(&self, gid: &str) {
+        self.overrides.lock().unwrap().remove(gid);
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Whether `rule` matches `download`. Rules with an invalid pattern match
+/// nothing.
+#[must_use]
+pub fn rule_matches(rule: &DownloadLimit, download: &DownloadStatus) -> bool {
+    let is_torrent = download.bittorrent.is_some() || download.info_hash.is_some();
+    match rule.tag.as_deref() {
+        None => {}
+        Some("torrent") if is_torrent => {}
+        Some("uri") if !is_torrent => {}
+        Some(_) => return false,
+    }
+
+    let Some(pattern) = &rule.pattern else {
+        return true;
+    };
+    let Ok(re) = Regex::new(pattern) else {
+        return false;
+    };
+    download.name().is_some_and(|name| re.is_match(&name))
+        || download.dir.as_deref().is_some_and(|dir| re.is_match(dir))
+        || download
+            .files
+            .iter()
+            .any(|f| re.is_match(&f.path) || f.uris.iter().any(|u| re.is_match(&u.uri)))
+}
+
+/// Activates `profile` on `client`: sets its global options, then its
+/// download limits.
+pub async fn apply_profile(client: &Aria2Client, profile: &BandwidthProfile) -> Result<()> {
+    client
+        .change_global_option(profile.global_options())
+        .await?;
+    apply_download_limits(client, Some(profile)).await
+}
+
+/// Sets the global options of a scheduled `profile`, first recording the
+/// values they replace. Values recorded for an earlier profile are kept, so
+/// switching profiles still restores the options from before the first;
+/// those the new profile does not set are restored right away.
+pub async fn activate_globals(client: &Aria2Client, profile: &BandwidthProfile) -> Result<()> {
+    let options = profile.global_options();
+    let keys: Vec<&String> = options
+        .as_object()
+        .map(|o| o.keys().collect())
+        .unwrap_or_default();
+    let mut recorded = client.bandwidth.original_globals();
+    let stale: Map<String, Value> = recorded
+        .iter()
+        .filter(|(key, _)| !keys.contains(key))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    if !stale.is_empty() {
+        client
+            .change_global_option(Value::Object(stale.clone()))
+            .await?;
+        let mut globals = client.bandwidth.globals.lock().unwrap();
+        for key in stale.keys() {
+            globals.remove(key);
+            recorded.remove(key);
+        }
+        client.bandwidth.dirty.store(true, Ordering::Relaxed);
+    }
+    if keys.iter().any(|k| !recorded.contains_key(*k)) {
+        let current = client.get_global_option().await?;
+        let mut globals = client.bandwidth.globals.lock().unwrap();
+        for key in keys {
+            if let Some(value) = current.get(key) {
+                globals.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+        client.bandwidth.dirty.store(true, Ordering::Relaxed);
+    }
+    client.change_global_option(options).await
+}
+
+/// Restores the global options recorded by [`activate_globals`], once the
+/// scheduled profile ended. Does nothing if none were recorded.
+pub async fn restore_globals(client: &Aria2Client) -> Result<()> {
+    let original = client.bandwidth.original_globals();
+    if original.is_empty() {
+        return Ok(());
+    }
+    client.change_global_option(Value::Object(original)).await?;
+    client.bandwidth.globals.lock().unwrap().clear();
+    client.bandwidth.dirty.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Applies the download limits of `profile` to the active and waiting
+/// downloads of `client`, restoring the downloads that no longer match a
+/// rule. With no profile, every download is restored.
+pub async fn apply_download_limits(
+    client: &Aria2Client,
+    profile: Option<&BandwidthProfile>,
+) -> Result<()> {
+    let rules = profile.map_or(&[][..], |p| p.download_limits.as_slice());
+    if rules.is_empty() && client.bandwidth.is_empty() {
+        return Ok(());
+    }
+
+    let keys: Vec<String> = MATCH_KEYS.iter().map(ToString::to_string).collect();
+    let mut downloads = client.active_downloads(Some(keys.clone())).await?;
+    downloads.extend(client.waiting_downloads(0, 1000, Some(keys)).await?);
+
+    let mut wanted: HashMap<String, Map<String, Value>> = HashMap::new();
+    for download in &downloads {
+        if let Some(rule) = rules.iter().find(|r| rule_matches(r, download)) {
+            let options = rule.options();
+            if !options.is_empty() {
+                wanted.insert(download.gid.clone(), options);
+            }
+        }
+    }
+
+    let current = client.bandwidth.overrides.lock().unwrap().clone();
+    let listed: Vec<&str> = downloads.iter().map(|d| d.gid.as_str()).collect();
+
+    for (gid, existing) in &current {
+        if !listed.contains(&gid.as_str()) {
+            client.bandwidth.remove(gid);
+            continue;
+        }
+        let original = match wanted.get(gid) {
+            Some(options) => {
+                // Keys the new rule no longer sets go back to their original value
+                let dropped: Map<String, Value> = existing
+                    .original
+                    .iter()
+                    .filter(|(k, _)| !options.contains_key(*k))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect();
+                if dropped.is_empty() {
+                    continue;
+                }
+                dropped
+            }
+            None => existing.original.clone(),
+        };
+        client.change_option(gid, Value::Object(original)).await?;
+        if !wanted.contains_key(gid) {
+            client.bandwidth.remove(gid);
+        }
+    }
+
+    for (gid, options) in wanted {
+        let existing = current.get(&gid);
+        if existing.is_some_and(|o| o.applied == options) {
+            continue;
+        }
+        let mut original = existing.map(|o| o.original.clone()).unwrap_or_default();
+        if options.keys().any(|k| !original.contains_key(k)) {
+            let current_options = client.get_option(&gid).await?;
+            for key in options.keys() {
+                if !original.contains_key(key) {
+                    if let Some(value) = current_options.get(key) {
+                        original.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        client
+            .change_option(&gid, Value::Object(options.clone()))
+            .await?;
+        original.retain(|k, _| options.contains_key(k));
+        client.bandwidth.insert(
+            gid,
+            DownloadOverride {
+                original,
+                applied: options,
+            },
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use serde_json::json;
+    use wiremock::matchers::{body_partial_json, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn mock_method(server: &MockServer, rpc_method: &str, result: Value) {
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "method": rpc_method })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": result
+            })))
+            .mount(server)
+            .await;
+    }
+
+    fn torrent_profile() -> BandwidthProfile {
+        BandwidthProfile {
+            max_download: "0".to_string(),
+            max_upload: "0".to_string(),
+            download_limits: vec![DownloadLimit {
+                tag: Some("torrent".to_string()),
+                max_download: Some("100K".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn rpc_params(requests: &[wiremock::Request], rpc_method: &str) -> Vec<Value> {
+        requests
+            .iter()
+            .filter_map(|r| serde_json::from_slice::<Value>(&r.body).ok())
+            .filter(|b| b["method"] == rpc_method)
+            .map(|b| b["params"].clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_rule_matches() {
+        let torrent: DownloadStatus = serde_json::from_value(json!({
+            "gid": "t",
+            "infoHash": "abc",
+            "bittorrent": { "info": { "name": "ubuntu.iso" } }
+        }))
+        .unwrap();
+        let http: DownloadStatus = serde_json::from_value(json!({
+            "gid": "h",
+            "dir": "/data/work",
+            "files": [{ "path": "/data/work/report.pdf", "uris": [{ "uri": "https://example.com/report.pdf" }] }]
+        }))
+        .unwrap();
+
+        let tag = |tag: &str| DownloadLimit {
+            tag: Some(tag.to_string()),
+            ..Default::default()
+        };
+        assert!(rule_matches(&tag("torrent"), &torrent));
+        assert!(!rule_matches(&tag("torrent"), &http));
+        assert!(rule_matches(&tag("uri"), &http));
+        assert!(!rule_matches(&tag("video"), &http));
+
+        let pattern = |pattern: &str| DownloadLimit {
+            pattern: Some(pattern.to_string()),
+            ..Default::default()
+        };
+        assert!(rule_matches(&pattern("ubuntu"), &torrent));
+        assert!(rule_matches(&pattern("example\\.com"), &http));
+        assert!(rule_matches(&pattern("^/data/work"), &http));
+        assert!(!rule_matches(&pattern("ubuntu"), &http));
+        assert!(!rule_matches(&pattern("("), &http));
+    }
+
+    #[tokio::test]
+    async fn test_apply_download_limits_and_restore() {
+        let mock_server = MockServer::start().await;
+        mock_method(
+            &mock_server,
+            "aria2.tellActive",
+            json!([
+                { "gid": "t1", "status": "active", "infoHash": "abc", "bittorrent": {} },
+                { "gid": "h1", "status": "active", "files": [] }
+            ]),
+        )
+        .await;
+        mock_method(&mock_server, "aria2.tellWaiting", json!([])).await;
+        mock_method(
+            &mock_server,
+            "aria2.getOption",
+            json!({ "max-download-limit": "0" }),
+        )
+        .await;
+        mock_method(&mock_server, "aria2.changeOption", json!("OK")).await;
+
+        let client = Aria2Client::new(Config::new(mock_server.uri(), None));
+        let profile = torrent_profile();
+
+        apply_download_limits(&client, Some(&profile))
+            .await
+            .unwrap();
+        assert_eq!(client.bandwidth.gids(), vec!["t1"]);
+        assert_eq!(
+            client.bandwidth.applied("t1").unwrap()["max-download-limit"],
+            "100K"
+        );
+
+        // Already applied: nothing to change
+        apply_download_limits(&client, Some(&profile))
+            .await
+            .unwrap();
+
+        apply_download_limits(&client, None).await.unwrap();
+        assert!(client.bandwidth.is_empty());
+
+        let changes = rpc_params(
+            &mock_server.received_requests().await.unwrap(),
+            "aria2.changeOption",
+        );
+        assert_eq!(
+            changes,
+            vec![
+                json!(["t1", { "max-download-limit": "100K" }]),
+                json!(["t1", { "max-download-limit": "0" }]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_profiles_restore_original_globals() {
+        let mock_server = MockServer::start().await;
+        mock_method(
+            &mock_server,
+            "aria2.getGlobalOption",
+            json!({
+                "max-overall-download-limit": "0",
+                "max-overall-upload-limit": "0",
+                "max-concurrent-downloads": "5",
+                "seed-ratio": "1.0"
+            }),
+        )
+        .await;
+        mock_method(&mock_server, "aria2.changeGlobalOption", json!("OK")).await;
+
+        let client = Aria2Client::new(Config::new(mock_server.uri(), None));
+        let work = BandwidthProfile {
+            max_download: "1M".to_string(),
+            max_upload: "100K".to_string(),
+            max_concurrent_downloads: Some(2),
+            ..Default::default()
+        };
+        let night = BandwidthProfile {
+            max_download: "0".to_string(),
+            max_upload: "0".to_string(),
+            seed_ratio: Some(0.5),
+            ..Default::default()
+        };
+
+        activate_globals(&client, &work).await.unwrap();
+        // Switching profiles keeps the values from before the first one, and
+        // restores those the new profile leaves alone
+        activate_globals(&client, &night).await.unwrap();
+        assert!(client.bandwidth.take_dirty());
+        assert!(!client
+            .bandwidth
+            .original_globals()
+            .contains_key("max-concurrent-downloads"));
+        restore_globals(&client).await.unwrap();
+        assert!(client.bandwidth.original_globals().is_empty());
+        // Nothing left to restore
+        restore_globals(&client).await.unwrap();
+
+        let changes = rpc_params(
+            &mock_server.received_requests().await.unwrap(),
+            "aria2.changeGlobalOption",
+        );
+        assert_eq!(changes.len(), 4);
+        assert_eq!(changes[1], json!([{ "max-concurrent-downloads": "5" }]));
+        assert_eq!(
+            changes[3],
+            json!([{
+                "max-overall-download-limit": "0",
+                "max-overall-upload-limit": "0",
+                "seed-ratio": "1.0"
+            }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_overrides_restored_after_restart() {
+        let mock_server = MockServer::start().await;
+        mock_method(
+            &mock_server,
+            "aria2.tellActive",
+            json!([{ "gid": "t1", "status": "active", "infoHash": "abc", "bittorrent": {} }]),
+        )
+        .await;
+        mock_method(&mock_server, "aria2.tellWaiting", json!([])).await;
+        mock_method(
+            &mock_server,
+            "aria2.getOption",
+            json!({ "max-download-limit": "0" }),
+        )
+        .await;
+        mock_method(
+            &mock_server,
+            "aria2.getGlobalOption",
+            json!({ "max-overall-download-limit": "2M", "max-overall-upload-limit": "0" }),
+        )
+        .await;
+        mock_method(&mock_server, "aria2.changeOption", json!("OK")).await;
+        mock_method(&mock_server, "aria2.changeGlobalOption", json!("OK")).await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let state_manager = std::sync::Arc::new(crate::state::StateManager::new(
+            dir.path().join("state.json"),
+        ));
+        let profile = torrent_profile();
+
+        let mut client = Aria2Client::new(Config::new(mock_server.uri(), None));
+        client.state_manager = std::sync::Arc::clone(&state_manager);
+        activate_globals(&client, &profile).await.unwrap();
+        apply_download_limits(&client, Some(&profile))
+            .await
+            .unwrap();
+        client.save_state().await.unwrap();
+
+        let mut restarted = Aria2Client::new(Config::new(mock_server.uri(), None));
+        restarted.state_manager = state_manager;
+        restarted.restore_state().await.unwrap();
+        assert_eq!(restarted.bandwidth.gids(), vec!["t1"]);
+
+        // The profile ended while the server was down
+        restore_globals(&restarted).await.unwrap();
+        apply_download_limits(&restarted, None).await.unwrap();
+        assert!(restarted.bandwidth.is_empty());
+
+        let requests = mock_server.received_requests().await.unwrap();
+        assert_eq!(
+            rpc_params(&requests, "aria2.changeGlobalOption").last(),
+            Some(&json!([{
+                "max-overall-download-limit": "2M",
+                "max-overall-upload-limit": "0"
+            }]))
+        );
+        assert_eq!(
+            rpc_params(&requests, "aria2.changeOption").last(),
+            Some(&json!(["t1", { "max-download-limit": "0" }]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_download_limits_skips_listing_without_rules() {
+        let client = Aria2Client::new(Config::new("http://127.0.0.1:1/jsonrpc".to_string(), None));
+        let profile = BandwidthProfile::default();
+        apply_download_limits(&client, Some(&profile))
+            .await
+            .unwrap();
+        apply_download_limits(&client, None).await.unwrap();
+    }
+}
@@ -18,6 +18,8 @@ pub struct Config {
     #[serde(default = "default_rpc_timeout")]
     pub rpc_timeout_secs: u64,
     #[serde(default)]
+    pub rpc_transport: RpcTransport,
+    #[serde(default)]
     pub bandwidth_profiles: HashMap<String, BandwidthProfile>,
     #[serde(default)]
     pub bandwidth_schedules: Vec<BandwidthSchedule>,
@@ -30,22 +32,208 @@ pub struct Config {
     #[serde(default)]
     pub purge_config: PurgeConfig,
     #[serde(default)]
+    pub stats_config: StatsConfig,
+    #[serde(default)]
+    pub auto_shutdown: AutoShutdownConfig,
+    #[serde(default)]
+    pub managed_aria2: ManagedAria2Config,
+    #[serde(default)]
     pub organize_rules: Vec<crate::tools::organize_completed::Rule>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct RSSConfig {
     #[serde(default)]
     pub feeds: Vec<RSSFeed>,
+    /// Maximum number of history entries kept per feed; the oldest are dropped
+    #[serde(default = "default_rss_history_limit")]
+    pub history_limit: usize,
+    /// History entries older than this are dropped. Unset keeps them forever
+    #[serde(default)]
+    pub history_max_age_secs: Option<u64>,
+    /// How often feeds without their own `interval_secs` are polled
+    #[serde(default = "default_rss_interval_secs")]
+    pub interval_secs: u64,
+    /// Names of feeds removed (or renamed) with the RSS tools. Feeds with
+    /// these names in the config file are not monitored, until a feed with
+    /// the name is added again
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_feeds: Vec<String>,
+}
+
+impl RSSConfig {
+    /// Records that the feed `name` was removed.
+    pub fn mark_removed(&mut self, name: &str) {
+        if !self.removed_feeds.iter().any(|n| n == name) {
+            self.removed_feeds.push(name.to_string());
+        }
+    }
+
+    /// Forgets that a feed named `name` was removed, once it is added again.
+    pub fn unmark_removed(&mut self, name: &str) {
+        self.removed_feeds.retain(|n| n != name);
+    }
+}
+
+fn default_rss_history_limit() -> usize {
+    1000
+}
+
+fn default_rss_interval_secs() -> u64 {
+    600
+}
+
+impl Default for RSSConfig {
+    fn default() -> Self {
+        Self {
+            feeds: Vec::new(),
+            history_limit: default_rss_history_limit(),
+            history_max_age_secs: None,
+            interval_secs: default_rss_interval_secs(),
+            removed_feeds: Vec::new(),
+        }
+    }
+}
+
+/// Throughput sampling for the `download_stats` tool.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct StatsConfig {
+    pub enabled: bool,
+    /// Seconds between samples
+    pub interval_secs: u64,
+    /// How long samples are kept
+    pub retention_secs: u64,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 60,
+            retention_secs: 604_800, // 1 week
+        }
+    }
+}
+
+/// Settings for the action the `auto_shutdown` tool runs once the queue
+/// drains. Commands are given as the program followed by its arguments.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AutoShutdownConfig {
+    /// Seconds between checks of whether every queue is empty
+    pub check_interval_secs: u64,
+    /// Default wait after the queues drain before the action runs
+    pub grace_secs: u64,
+    pub shutdown_command: Vec<String>,
+    pub suspend_command: Vec<String>,
+    /// Command run by the `command` action; the action is unavailable if empty
+    pub command: Vec<String>,
+    /// Actions the tool may arm. Only stopping aria2 is allowed unless the
+    /// operator lists the others.
+    pub allowed_actions: Vec<crate::tools::auto_shutdown::DrainAction>,
+}
+
+impl Default for AutoShutdownConfig {
+    fn default() -> Self {
+        let (shutdown, suspend): (&[&str], &[&str]) = if cfg!(target_os = "macos") {
+            (&["shutdown", "-h", "now"], &["pmset", "sleepnow"])
+        } else if cfg!(target_os = "windows") {
+            (
+                &["shutdown", "/s", "/t", "0"],
+                &["rundll32.exe", "powrprof.dll,SetSuspendState", "0,1,0"],
+            )
+        } else {
+            (&["systemctl", "poweroff"], &["systemctl", "suspend"])
+        };
+        let to_vec = |command: &[&str]| command.iter().map(ToString::to_string).collect();
+        Self {
+            check_interval_secs: 30,
+            grace_secs: 300,
+            shutdown_command: to_vec(shutdown),
+            suspend_command: to_vec(suspend),
+            command: Vec::new(),
+            allowed_actions: vec![crate::tools::auto_shutdown::DrainAction::Aria2],
+        }
+    }
+}
+
+/// A local `aria2c` started and supervised by the server instead of one
+/// reached through `rpc_url`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ManagedAria2Config {
+    pub enabled: bool,
+    /// Name the process is registered under in `instances`. An instance with
+    /// the same name is replaced
+    pub name: String,
+    /// Path to the `aria2c` binary
+    pub binary: String,
+    /// RPC port. A free port is picked if unset
+    pub port: Option<u16>,
+    /// RPC secret. A random one is generated if unset
+    pub rpc_secret: Option<String>,
+    /// Session file loaded on start and saved by aria2 while it runs
+    pub session_file: String,
+    /// Download directory. aria2 uses its working directory if unset
+    pub dir: Option<String>,
+    /// Further arguments passed to `aria2c` as-is
+    pub extra_args: Vec<String>,
+    /// How long to wait for the RPC interface to answer after starting
+    pub startup_timeout_secs: u64,
+    /// Wait before restarting a process that failed
+    pub restart_delay_secs: u64,
+    /// Consecutive restarts before giving up. A process that stayed up for a
+    /// minute resets the count
+    pub max_restarts: u32,
+}
+
+impl Default for ManagedAria2Config {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            name: "default".to_string(),
+            binary: "aria2c".to_string(),
+            port: None,
+            rpc_secret: None,
+            session_file: "aria2.session".to_string(),
+            dir: None,
+            extra_args: Vec::new(),
+            startup_timeout_secs: 10,
+            restart_delay_secs: 2,
+            max_restarts: 5,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PurgeConfig {
     pub enabled: bool,
     pub interval_secs: u64,
+    /// How long a download must have been stopped before it is purged
     pub min_age_secs: u64,
     #[serde(default)]
     pub excluded_gids: HashSet<String>,
+    /// Download statuses that are purged
+    #[serde(default = "default_purge_statuses")]
+    pub statuses: Vec<String>,
+    /// Minimum age per status, overriding `min_age_secs`
+    #[serde(default)]
+    pub status_min_age_secs: HashMap<String, u64>,
+    /// Overrides for individual instances, keyed by instance name
+    #[serde(default)]
+    pub instances: HashMap<String, InstancePurgePolicy>,
+    /// Number of purged downloads remembered in the purge history
+    #[serde(default = "default_purge_history_limit")]
+    pub history_limit: usize,
+}
+
+fn default_purge_statuses() -> Vec<String> {
+    vec!["complete".to_string(), "error".to_string()]
+}
+
+fn default_purge_history_limit() -> usize {
+    500
 }
 
 impl Default for PurgeConfig {
@@ -55,36 +243,217 @@ impl Default for PurgeConfig {
             interval_secs: 3600, // 1 hour
             min_age_secs: 86400, // 1 day
             excluded_gids: HashSet::new(),
+            statuses: default_purge_statuses(),
+            status_min_age_secs: HashMap::new(),
+            instances: HashMap::new(),
+            history_limit: default_purge_history_limit(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+impl PurgeConfig {
+    /// The policy for `instance`, with its overrides applied.
+    #[must_use]
+    pub fn for_instance(&self, instance: &str) -> Self {
+        let mut policy = self.clone();
+        policy.instances.clear();
+        if let Some(overrides) = self.instances.get(instance) {
+            if let Some(enabled) = overrides.enabled {
+                policy.enabled = enabled;
+            }
+            if let Some(min_age_secs) = overrides.min_age_secs {
+                policy.min_age_secs = min_age_secs;
+            }
+            if let Some(statuses) = &overrides.statuses {
+                policy.statuses.clone_from(statuses);
+            }
+            policy
+                .status_min_age_secs
+                .extend(overrides.status_min_age_secs.clone());
+        }
+        policy
+    }
+
+    /// How old a download with `status` must be to be purged, or `None` if
+    /// that status is never purged.
+    #[must_use]
+    pub fn min_age_for(&self, status: &str) -> Option<u64> {
+        if !self.statuses.iter().any(|s| s == status) {
+            return None;
+        }
+        Some(
+            self.status_min_age_secs
+                .get(status)
+                .copied()
+                .unwrap_or(self.min_age_secs),
+        )
+    }
+}
+
+/// Purge settings that differ for one instance; unset fields use the
+/// top-level policy.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct InstancePurgePolicy {
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub min_age_secs: Option<u64>,
+    #[serde(default)]
+    pub statuses: Option<Vec<String>>,
+    #[serde(default)]
+    pub status_min_age_secs: HashMap<String, u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct RSSFeed {
     pub url: String,
     pub name: String,
     #[serde(default)]
     pub filters: Vec<RSSFilter>,
-    #[serde(default)]
-    pub download_history: HashSet<String>,
+    /// Items matching any of these filters are skipped
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<RSSFilter>,
+    /// Polling interval for this feed, overriding `rss_config.interval_secs`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<u64>,
+    /// aria2 options (such as `dir`) applied to every download from this feed
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub options: HashMap<String, String>,
+    /// Items already downloaded, keyed by GUID (or link) with the Unix time
+    /// they were added
+    #[serde(default, deserialize_with = "deserialize_rss_history")]
+    pub download_history: HashMap<String, i64>,
 }
 
 impl RSSFeed {
     #[must_use]
     pub fn has_downloaded(&self, id: &str) -> bool {
-        self.download_history.contains(id)
+        self.download_history.contains_key(id)
     }
 
     pub fn mark_downloaded(&mut self, id: String) {
-        self.download_history.insert(id);
+        self.download_history
+            .insert(id, chrono::Utc::now().timestamp());
+    }
+
+    /// Drops history entries older than `max_age_secs`, then the oldest
+    /// entries beyond `limit`.
+    pub fn prune_history(&mut self, limit: usize, max_age_secs: Option<u64>) {
+        if let Some(max_age) = max_age_secs {
+            let cutoff = chrono::Utc::now()
+                .timestamp()
+                .saturating_sub(i64::try_from(max_age).unwrap_or(i64::MAX));
+            self.download_history.retain(|_, added| *added >= cutoff);
+        }
+
+        if self.download_history.len() > limit {
+            let mut entries: Vec<(String, i64)> = self.download_history.drain().collect();
+            entries.sort_by_key(|entry| std::cmp::Reverse(entry.1));
+            entries.truncate(limit);
+            self.download_history = entries.into_iter().collect();
+        }
     }
 }
 
+/// Accepts the history as a map of id to time, or as the plain list of ids
+/// older versions stored, which are treated as added now.
+fn deserialize_rss_history<'de, D>(deserializer: D) -> Result<HashMap<String, i64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum History {
+        Map(HashMap<String, i64>),
+        List(Vec<String>),
+    }
+
+    match History::deserialize(deserializer)? {
+        History::Map(m) => Ok(m),
+        History::List(ids) => {
+            let now = chrono::Utc::now().timestamp();
+            Ok(ids.into_iter().map(|id| (id, now)).collect())
+        }
+    }
+}
+
+/// A feed item filter, written as a title keyword, `regex:<pattern>`,
+/// `category:<name>`, `description:<keyword>` or `size:<min>-<max>`.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-#[serde(untagged)]
+#[serde(from = "RawRSSFilter", into = "String")]
 pub enum RSSFilter {
     Keyword(String),
     Regex(String),
+    Category(String),
+    Description(String),
+    /// A size range such as `100MB-2GB`; either bound may be left out
+    Size(String),
+}
+
+impl From<String> for RSSFilter {
+    fn from(s: String) -> Self {
+        if let Some(pattern) = s.strip_prefix("regex:") {
+            Self::Regex(pattern.to_string())
+        } else if let Some(category) = s.strip_prefix("category:") {
+            Self::Category(category.to_string())
+        } else if let Some(keyword) = s.strip_prefix("description:") {
+            Self::Description(keyword.to_string())
+        } else if let Some(range) = s.strip_prefix("size:") {
+            Self::Size(range.to_string())
+        } else {
+            Self::Keyword(s)
+        }
+    }
+}
+
+/// Filters as written in config and state files: prefixed strings, or
+/// objects with one key such as `{ regex = "^CVE-" }`. Plain strings were
+/// the only form before prefixes and still read as keywords.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawRSSFilter {
+    Prefixed(String),
+    Tagged(TaggedRSSFilter),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum TaggedRSSFilter {
+    #[serde(alias = "Keyword")]
+    Keyword(String),
+    #[serde(alias = "Regex")]
+    Regex(String),
+    #[serde(alias = "Category")]
+    Category(String),
+    #[serde(alias = "Description")]
+    Description(String),
+    #[serde(alias = "Size")]
+    Size(String),
+}
+
+impl From<RawRSSFilter> for RSSFilter {
+    fn from(raw: RawRSSFilter) -> Self {
+        match raw {
+            RawRSSFilter::Prefixed(s) => Self::from(s),
+            RawRSSFilter::Tagged(TaggedRSSFilter::Keyword(k)) => Self::Keyword(k),
+            RawRSSFilter::Tagged(TaggedRSSFilter::Regex(r)) => Self::Regex(r),
+            RawRSSFilter::Tagged(TaggedRSSFilter::Category(c)) => Self::Category(c),
+            RawRSSFilter::Tagged(TaggedRSSFilter::Description(d)) => Self::Description(d),
+            RawRSSFilter::Tagged(TaggedRSSFilter::Size(r)) => Self::Size(r),
+        }
+    }
+}
+
+impl From<RSSFilter> for String {
+    fn from(filter: RSSFilter) -> Self {
+        match filter {
+            RSSFilter::Keyword(k) => k,
+            RSSFilter::Regex(r) => format!("regex:{r}"),
+            RSSFilter::Category(c) => format!("category:{c}"),
+            RSSFilter::Description(d) => format!("description:{d}"),
+            RSSFilter::Size(r) => format!("size:{r}"),
+        }
+    }
 }
 
 fn deserialize_instances<'de, D>(deserializer: D) -> Result<Vec<Aria2Instance>, D::Error>
@@ -113,12 +482,98 @@ pub struct Aria2Instance {
     pub name: String,
     pub rpc_url: String,
     pub rpc_secret: Option<String>,
+    /// Overrides the global `rpc_transport` for this instance
+    #[serde(default)]
+    pub rpc_transport: Option<RpcTransport>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct BandwidthProfile {
     pub max_download: String,
     pub max_upload: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_downloads: Option<u32>,
+    /// `seed-ratio` for torrents, e.g. 1.0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed_ratio: Option<f64>,
+    /// `seed-time` for torrents, in minutes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed_time: Option<u64>,
+    /// Limits for the downloads matching a rule; the first matching rule wins
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub download_limits: Vec<DownloadLimit>,
+    /// Names of the instances the scheduler applies the profile to. Empty
+    /// means every instance
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instances: Vec<String>,
+}
+
+impl BandwidthProfile {
+    #[must_use]
+    pub fn applies_to(&self, instance: &str) -> bool {
+        self.instances.is_empty() || self.instances.iter().any(|i| i == instance)
+    }
+
+    /// Global options set when the profile is activated.
+    #[must_use]
+    pub fn global_options(&self) -> serde_json::Value {
+        let mut options = serde_json::json!({
+            "max-overall-download-limit": self.max_download,
+            "max-overall-upload-limit": self.max_upload,
+        });
+        if let Some(max) = self.max_concurrent_downloads {
+            options["max-concurrent-downloads"] = serde_json::json!(max.to_string());
+        }
+        if let Some(ratio) = self.seed_ratio {
+            options["seed-ratio"] = serde_json::json!(ratio.to_string());
+        }
+        if let Some(time) = self.seed_time {
+            options["seed-time"] = serde_json::json!(time.to_string());
+        }
+        options
+    }
+}
+
+/// Per-download limits of a [`BandwidthProfile`]. A rule matches a download
+/// when it has `tag` (if set) and `pattern` matches its name, directory or
+/// one of its URIs (if set).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DownloadLimit {
+    /// `torrent` for BitTorrent downloads, `uri` for HTTP(S)/FTP downloads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// Regular expression
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_download: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_upload: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed_ratio: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed_time: Option<u64>,
+}
+
+impl DownloadLimit {
+    /// Options set on each matching download.
+    #[must_use]
+    pub fn options(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut options = serde_json::Map::new();
+        if let Some(limit) = &self.max_download {
+            options.insert("max-download-limit".to_string(), limit.clone().into());
+        }
+        if let Some(limit) = &self.max_upload {
+            options.insert("max-upload-limit".to_string(), limit.clone().into());
+        }
+        if let Some(ratio) = self.seed_ratio {
+            options.insert("seed-ratio".to_string(), ratio.to_string().into());
+        }
+        if let Some(time) = self.seed_time {
+            options.insert("seed-time".to_string(), time.to_string().into());
+        }
+        options
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -137,6 +592,17 @@ pub enum TransportType {
     Sse,
 }
 
+/// How RPC calls reach aria2: one HTTP request per call, or multiplexed
+/// over the persistent WebSocket that also carries notifications.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RpcTransport {
+    #[default]
+    Http,
+    #[serde(alias = "ws")]
+    WebSocket,
+}
+
 fn default_http_host() -> String {
     "0.0.0.0".to_string()
 }
@@ -160,6 +626,7 @@ impl Default for Config {
             lazy_mode: false,
             no_verify_ssl: true,
             rpc_timeout_secs: 10,
+            rpc_transport: RpcTransport::Http,
             bandwidth_profiles: HashMap::new(),
             bandwidth_schedules: Vec::new(),
             retry_config: crate::aria2::recovery::RetryConfig::default(),
@@ -167,9 +634,13 @@ impl Default for Config {
                 name: "default".to_string(),
                 rpc_url,
                 rpc_secret,
+                rpc_transport: None,
             }],
             rss_config: RSSConfig::default(),
             purge_config: PurgeConfig::default(),
+            stats_config: StatsConfig::default(),
+            auto_shutdown: AutoShutdownConfig::default(),
+            managed_aria2: ManagedAria2Config::default(),
             organize_rules: Vec::new(),
         }
     }
@@ -195,9 +666,20 @@ impl Config {
 
         let mut config: Self = s.try_deserialize()?;
         config.normalize();
+        config.validate()?;
         Ok(config)
     }
 
+    /// Rejects RSS feeds with an invalid filter, which would otherwise never
+    /// match.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for feed in &self.rss_config.feeds {
+            crate::tools::rss::FeedFilters::new(feed)
+                .map_err(|e| ConfigError::Message(format!("RSS feed '{}': {e:#}", feed.name)))?;
+        }
+        Ok(())
+    }
+
     pub fn normalize(&mut self) {
         // If instances is empty, populate from legacy fields
         if self.instances.is_empty() {
@@ -205,6 +687,7 @@ impl Config {
                 name: "default".to_string(),
                 rpc_url: self.rpc_url.clone(),
                 rpc_secret: self.rpc_secret.clone(),
+                rpc_transport: None,
             });
         }
     }
@@ -218,6 +701,7 @@ impl Config {
                 name: "default".to_string(),
                 rpc_url,
                 rpc_secret,
+                rpc_transport: None,
             }],
             ..Default::default()
         }
@@ -249,6 +733,23 @@ mod tests {
         assert!(config.is_ok());
     }
 
+    #[test]
+    fn test_validate_rejects_invalid_rss_filters() {
+        let mut config = Config::default();
+        config.rss_config.feeds.push(RSSFeed {
+            name: "isos".to_string(),
+            filters: vec![RSSFilter::Regex("ubuntu".to_string())],
+            ..RSSFeed::default()
+        });
+        assert!(config.validate().is_ok());
+
+        config.rss_config.feeds[0]
+            .exclude
+            .push(RSSFilter::Regex("(".to_string()));
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("RSS feed 'isos'"), "{err}");
+    }
+
     #[test]
     fn test_normalize_updates_default_instance() {
         let mut config = Config::default();
@@ -261,6 +762,31 @@ mod tests {
         assert_eq!(config.instances[0].rpc_url, "http://127.0.0.1:6800/jsonrpc");
     }
 
+    #[test]
+    fn test_rpc_transport_deserialize() {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "rpc_url": "http://127.0.0.1:6800/jsonrpc",
+            "rpc_secret": null,
+            "transport": "stdio",
+            "http_port": 3000,
+            "http_auth_token": null,
+            "log_level": "info",
+            "lazy_mode": false,
+            "no_verify_ssl": true,
+            "rpc_transport": "websocket",
+            "instances": [
+                { "name": "a", "rpc_url": "http://a:6800/jsonrpc", "rpc_secret": null },
+                { "name": "b", "rpc_url": "http://b:6800/jsonrpc", "rpc_secret": null, "rpc_transport": "http" }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(config.rpc_transport, RpcTransport::WebSocket);
+        assert_eq!(config.instances[0].rpc_transport, None);
+        assert_eq!(config.instances[1].rpc_transport, Some(RpcTransport::Http));
+        assert_eq!(Config::default().rpc_transport, RpcTransport::Http);
+    }
+
     #[test]
     fn test_normalize_does_not_overwrite_instance_with_default() {
         // Simulate a config file that has instances but not top-level rpc_url
@@ -271,6 +797,7 @@ mod tests {
                 name: "default".to_string(),
                 rpc_url: "http://my-aria2:6800/jsonrpc".to_string(),
                 rpc_secret: None,
+                rpc_transport: None,
             }],
             ..Default::default()
         };
@@ -287,7 +814,8 @@ mod tests {
             url: "http://test".to_string(),
             name: "test".to_string(),
             filters: vec![],
-            download_history: HashSet::new(),
+            download_history: HashMap::new(),
+            ..RSSFeed::default()
         };
 
         assert!(!feed.has_downloaded("item1"));
@@ -296,6 +824,81 @@ mod tests {
         assert!(!feed.has_downloaded("item2"));
     }
 
+    #[test]
+    fn test_rss_feed_prune_history() {
+        let now = chrono::Utc::now().timestamp();
+        let mut feed = RSSFeed {
+            url: "http://test".to_string(),
+            name: "test".to_string(),
+            filters: vec![],
+            download_history: HashMap::from([
+                ("old".to_string(), now - 10_000),
+                ("older".to_string(), now - 20_000),
+                ("new".to_string(), now),
+                ("newer".to_string(), now - 1),
+            ]),
+            ..RSSFeed::default()
+        };
+
+        feed.prune_history(10, Some(15_000));
+        assert!(!feed.has_downloaded("older"));
+        assert_eq!(feed.download_history.len(), 3);
+
+        feed.prune_history(2, None);
+        assert!(feed.has_downloaded("new"));
+        assert!(feed.has_downloaded("newer"));
+        assert!(!feed.has_downloaded("old"));
+    }
+
+    #[test]
+    fn test_rss_feed_serde() {
+        let feed: RSSFeed = serde_json::from_value(serde_json::json!({
+            "url": "http://test",
+            "name": "test",
+            "filters": ["ubuntu", "regex:^CVE-.*"],
+            "download_history": ["item1"]
+        }))
+        .unwrap();
+        assert_eq!(
+            feed.filters,
+            vec![
+                RSSFilter::Keyword("ubuntu".to_string()),
+                RSSFilter::Regex("^CVE-.*".to_string())
+            ]
+        );
+        assert!(feed.has_downloaded("item1"));
+
+        let value = serde_json::to_value(&feed).unwrap();
+        assert_eq!(value["filters"][1], "regex:^CVE-.*");
+        assert!(value["download_history"]["item1"].is_i64());
+    }
+
+    #[test]
+    fn test_rss_filter_prefixes_round_trip() {
+        let raw = vec![
+            "ubuntu",
+            "regex:^CVE-.*",
+            "category:TV",
+            "description:remux",
+            "size:100MB-2GB",
+        ];
+        let filters: Vec<RSSFilter> = serde_json::from_value(serde_json::json!(raw)).unwrap();
+        assert_eq!(
+            filters,
+            vec![
+                RSSFilter::Keyword("ubuntu".to_string()),
+                RSSFilter::Regex("^CVE-.*".to_string()),
+                RSSFilter::Category("TV".to_string()),
+                RSSFilter::Description("remux".to_string()),
+                RSSFilter::Size("100MB-2GB".to_string()),
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&filters).unwrap(),
+            serde_json::json!(raw)
+        );
+    }
+
     #[test]
     fn test_purge_config_defaults() {
         let config = Config::default();
@@ -303,5 +906,103 @@ mod tests {
         assert_eq!(config.purge_config.interval_secs, 3600);
         assert_eq!(config.purge_config.min_age_secs, 86400);
         assert!(config.purge_config.excluded_gids.is_empty());
+        assert_eq!(config.purge_config.statuses, vec!["complete", "error"]);
+        assert_eq!(config.purge_config.history_limit, 500);
+    }
+
+    #[test]
+    fn test_purge_config_for_instance() {
+        let purge_config = PurgeConfig {
+            status_min_age_secs: HashMap::from([("error".to_string(), 60)]),
+            instances: HashMap::from([(
+                "seedbox".to_string(),
+                InstancePurgePolicy {
+                    enabled: Some(true),
+                    statuses: Some(vec!["complete".to_string(), "removed".to_string()]),
+                    status_min_age_secs: HashMap::from([("removed".to_string(), 0)]),
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+
+        assert_eq!(purge_config.min_age_for("complete"), Some(86400));
+        assert_eq!(purge_config.min_age_for("error"), Some(60));
+        assert_eq!(purge_config.min_age_for("removed"), None);
+
+        let seedbox = purge_config.for_instance("seedbox");
+        assert!(seedbox.enabled);
+        assert!(seedbox.instances.is_empty());
+        assert_eq!(seedbox.min_age_for("removed"), Some(0));
+        assert_eq!(seedbox.min_age_for("error"), None);
+
+        assert!(!purge_config.for_instance("other").enabled);
+    }
+
+    #[test]
+    fn test_bandwidth_profile_options() {
+        let profile: BandwidthProfile = toml::from_str(
+            r#"
+            max_download = "1M"
+            max_upload = "100K"
+            max_concurrent_downloads = 2
+            seed_ratio = 0.5
+            seed_time = 30
+            instances = ["seedbox"]
+
+            [[download_limits]]
+            tag = "torrent"
+            max_download = "200K"
+            "#,
+        )
+        .unwrap();
+
+        let options = profile.global_options();
+        assert_eq!(options["max-overall-download-limit"], "1M");
+        assert_eq!(options["max-concurrent-downloads"], "2");
+        assert_eq!(options["seed-ratio"], "0.5");
+        assert_eq!(options["seed-time"], "30");
+        assert!(profile.applies_to("seedbox"));
+        assert!(!profile.applies_to("default"));
+        assert_eq!(
+            profile.download_limits[0].options()["max-download-limit"],
+            "200K"
+        );
+
+        let plain = BandwidthProfile {
+            max_download: "0".to_string(),
+            max_upload: "0".to_string(),
+            ..Default::default()
+        };
+        assert!(plain.applies_to("default"));
+        assert!(plain.global_options().get("seed-ratio").is_none());
+    }
+
+    #[test]
+    fn test_rss_filter_legacy_forms() {
+        let feed: RSSFeed = serde_json::from_value(serde_json::json!({
+            "url": "http://example.com/rss",
+            "name": "example",
+            "filters": ["ubuntu", { "regex": "^CVE-" }, { "Keyword": "debian" }]
+        }))
+        .unwrap();
+        assert_eq!(
+            feed.filters,
+            vec![
+                RSSFilter::Keyword("ubuntu".to_string()),
+                RSSFilter::Regex("^CVE-".to_string()),
+                RSSFilter::Keyword("debian".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rss_config_removed_feeds() {
+        let mut rss = RSSConfig::default();
+        rss.mark_removed("a");
+        rss.mark_removed("a");
+        assert_eq!(rss.removed_feeds, vec!["a".to_string()]);
+        rss.unmark_removed("a");
+        assert!(rss.removed_feeds.is_empty());
     }
 }
@@ -8,8 +8,8 @@ pub enum Error {
     #[error("Configuration error: {0}")]
     Config(String),
 
-    #[error("Aria2 RPC error: {0}")]
-    Aria2(String),
+    #[error("Aria2 RPC error {code}: {message}")]
+    Aria2 { code: i64, message: String },
 
     #[error("MCP error: {0}")]
     Mcp(String),
@@ -38,8 +38,11 @@ mod tests {
         let err = Error::Config("test".to_string());
         assert_eq!(err.to_string(), "Configuration error: test");
 
-        let err = Error::Aria2("test".to_string());
-        assert_eq!(err.to_string(), "Aria2 RPC error: test");
+        let err = Error::Aria2 {
+            code: 1,
+            message: "test".to_string(),
+        };
+        assert_eq!(err.to_string(), "Aria2 RPC error 1: test");
 
         let err = Error::Mcp("test".to_string());
         assert_eq!(err.to_string(), "MCP error: test");
@@ -0,0 +1,330 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use crate::aria2::DownloadStatus;
+use crate::error::{Error, Result};
+
+/// What aria2 knew about a finished download when its result was removed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub gid: String,
+    pub instance: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub status: String,
+    #[serde(default)]
+    pub uris: Vec<String>,
+    #[serde(default)]
+    pub files: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+    pub total_length: u64,
+    pub completed_length: u64,
+    pub upload_length: u64,
+    /// Download speed reported in the last status
+    pub download_speed: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub info_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    /// Unix time the download stopped, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<i64>,
+    /// Unix time it was archived
+    pub archived_at: i64,
+}
+
+impl HistoryEntry {
+    #[must_use]
+    pub fn new(
+        download: &DownloadStatus,
+        instance: &str,
+        completed_at: Option<i64>,
+        archived_at: i64,
+    ) -> Self {
+        let mut uris: Vec<String> = Vec::new();
+        for uri in download.uris() {
+            if !uris.iter().any(|u| u == uri) {
+                uris.push(uri.to_string());
+            }
+        }
+
+        Self {
+            gid: download.gid.clone(),
+            instance: instance.to_string(),
+            name: download.name(),
+            status: download
+                .status
+                .map(|s| s.as_str().to_string())
+                .unwrap_or_default(),
+            uris,
+            files: download
+                .files
+                .iter()
+                .map(|f| f.path.clone())
+                .filter(|p| !p.is_empty())
+                .collect(),
+            dir: download.dir.clone(),
+            total_length: download.total_length,
+            completed_length: download.completed_length,
+            upload_length: download.upload_length,
+            download_speed: download.download_speed,
+            info_hash: download.info_hash.clone(),
+            error_code: download.error_code.filter(|c| *c != 0),
+            error_message: download.error_message.clone().filter(|m| !m.is_empty()),
+            completed_at,
+            archived_at,
+        }
+    }
+
+    /// When the download finished, or when it was archived if that is unknown.
+    #[must_use]
+    pub fn finished_at(&self) -> i64 {
+        self.completed_at.unwrap_or(self.archived_at)
+    }
+}
+
+/// Filters for [`HistoryStore::query`]. Unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryQuery {
+    /// Case-insensitive substring of the name, a file path or a URI
+    pub name: Option<String>,
+    pub status: Option<String>,
+    pub instance: Option<String>,
+    pub gid: Option<String>,
+    /// Unix time the download finished at or after
+    pub since: Option<i64>,
+    /// Unix time the download finished at or before
+    pub until: Option<i64>,
+    /// Maximum number of entries returned
+    pub limit: Option<usize>,
+}
+
+impl HistoryQuery {
+    #[must_use]
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        if self.status.as_ref().is_some_and(|s| *s != entry.status)
+            || self.instance.as_ref().is_some_and(|i| *i != entry.instance)
+            || self.gid.as_ref().is_some_and(|g| *g != entry.gid)
+            || self.since.is_some_and(|since| entry.finished_at() < since)
+            || self.until.is_some_and(|until| entry.finished_at() > until)
+        {
+            return false;
+        }
+
+        let Some(name) = &self.name else {
+            return true;
+        };
+        let name = name.to_lowercase();
+        entry
+            .name
+            .iter()
+            .chain(&entry.files)
+            .chain(&entry.uris)
+            .any(|field| field.to_lowercase().contains(&name))
+    }
+}
+
+/// Append-only JSON Lines archive of finished downloads, one entry per line.
+#[derive(Debug)]
+pub struct HistoryStore {
+    path: PathBuf,
+    /// Serializes appends from concurrent tasks
+    write_lock: Mutex<()>,
+}
+
+impl HistoryStore {
+    #[must_use]
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub async fn append(&self, entries: &[HistoryEntry]) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let mut content = String::new();
+        for entry in entries {
+            content.push_str(&serde_json::to_string(entry)?);
+            content.push('\n');
+        }
+
+        let _guard = self.write_lock.lock().await;
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() && !parent.exists() {
+                fs::create_dir_all(parent).await.map_err(|e| {
+                    Error::Internal(format!(
+                        "Failed to create parent directories for history: {e}"
+                    ))
+                })?;
+            }
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to open history file: {e}")))?;
+        file.write_all(content.as_bytes())
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to write history file: {e}")))?;
+        Ok(())
+    }
+
+    /// Returns the entries matching `query`, most recently archived first.
+    /// Lines that cannot be parsed are skipped.
+    pub async fn query(&self, query: &HistoryQuery) -> Result<Vec<HistoryEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to read history file: {e}")))?;
+
+        Ok(content
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str::<HistoryEntry>(line).ok())
+            .filter(|entry| query.matches(entry))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(gid: &str, name: &str, status: &str, completed_at: Option<i64>) -> HistoryEntry {
+        HistoryEntry {
+            gid: gid.to_string(),
+            instance: "default".to_string(),
+            name: Some(name.to_string()),
+            status: status.to_string(),
+            uris: vec![format!("http://example.com/{name}")],
+            files: vec![],
+            dir: None,
+            total_length: 0,
+            completed_length: 0,
+            upload_length: 0,
+            download_speed: 0,
+            info_hash: None,
+            error_code: None,
+            error_message: None,
+            completed_at,
+            archived_at: 1_000,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_append_and_query() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let store = HistoryStore::new(dir.path().join("history.jsonl"));
+        assert!(store.query(&HistoryQuery::default()).await?.is_empty());
+
+        store
+            .append(&[
+                entry("1", "ubuntu.iso", "complete", Some(100)),
+                entry("2", "debian.iso", "error", None),
+            ])
+            .await?;
+        store
+            .append(&[entry("3", "Ubuntu-server.iso", "complete", Some(500))])
+            .await?;
+
+        let all = store.query(&HistoryQuery::default()).await?;
+        assert_eq!(
+            all.iter().map(|e| e.gid.as_str()).collect::<Vec<_>>(),
+            vec!["3", "2", "1"]
+        );
+
+        let ubuntu = store
+            .query(&HistoryQuery {
+                name: Some("UBUNTU".to_string()),
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(ubuntu.len(), 2);
+
+        let by_uri = store
+            .query(&HistoryQuery {
+                name: Some("example.com/debian".to_string()),
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(by_uri[0].gid, "2");
+
+        let dated = store
+            .query(&HistoryQuery {
+                status: Some("complete".to_string()),
+                since: Some(200),
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(dated.len(), 1);
+        assert_eq!(dated[0].gid, "3");
+
+        // Without a completion time the archive time counts
+        let until = store
+            .query(&HistoryQuery {
+                until: Some(999),
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(until.len(), 2);
+
+        let limited = store
+            .query(&HistoryQuery {
+                limit: Some(1),
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(limited.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_from_download_status() {
+        let download: DownloadStatus = serde_json::from_value(serde_json::json!({
+            "gid": "abc",
+            "status": "error",
+            "totalLength": "100",
+            "completedLength": "40",
+            "errorCode": "3",
+            "errorMessage": "Resource not found",
+            "dir": "/downloads",
+            "files": [{
+                "index": "1",
+                "path": "/downloads/file.bin",
+                "length": "100",
+                "completedLength": "40",
+                "selected": "true",
+                "uris": [
+                    { "uri": "http://a/file.bin", "status": "used" },
+                    { "uri": "http://a/file.bin", "status": "waiting" }
+                ]
+            }]
+        }))
+        .unwrap();
+
+        let entry = HistoryEntry::new(&download, "remote", Some(5), 10);
+        assert_eq!(entry.name.as_deref(), Some("file.bin"));
+        assert_eq!(entry.status, "error");
+        assert_eq!(entry.uris, vec!["http://a/file.bin"]);
+        assert_eq!(entry.files, vec!["/downloads/file.bin"]);
+        assert_eq!(entry.error_code, Some(3));
+        assert_eq!(entry.completed_length, 40);
+        assert_eq!(entry.finished_at(), 5);
+    }
+}
@@ -1,10 +1,14 @@
 pub mod aria2;
+pub mod bandwidth;
 pub mod config;
 pub mod error;
+pub mod history;
+pub mod metrics;
 pub mod prompts;
 pub mod resources;
 pub mod server;
 pub mod state;
+pub mod stats;
 pub mod tools;
 
 pub use aria2::Aria2Client;
@@ -14,7 +18,10 @@ pub use prompts::PromptRegistry;
 pub use resources::ResourceRegistry;
 pub use server::McpServer;
 pub use tools::{
-    AddRssFeedTool, BulkManageDownloadsTool, CheckHealthTool, ConfigureAria2Tool,
-    InspectDownloadTool, ListRssFeedsTool, ManageDownloadsTool, ManageTorrentTool, McpeTool,
-    MonitorQueueTool, OrganizeCompletedTool, ScheduleLimitsTool, SearchDownloadsTool, ToolRegistry,
+    AddRssFeedTool, AutoShutdownTool, BulkManageDownloadsTool, CheckHealthTool, ClearHistoryTool,
+    ConfigureAria2Tool, DownloadHistoryTool, DownloadStatsTool, InspectDownloadTool,
+    ListInstancesTool, ListRssFeedsTool, ManageAria2ProcessTool, ManageDownloadsTool,
+    ManageTorrentTool, McpeTool, MonitorQueueTool, OrganizeCompletedTool, PollRssFeedTool,
+    PreviewRssFeedTool, RemoveRssFeedTool, ScheduleLimitsTool, SearchDownloadsTool, ToolRegistry,
+    UpdateRssFeedTool,
 };
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use aria2_mcp_rs::aria2::Aria2Supervisor;
 use aria2_mcp_rs::{Aria2Client, Config, McpServer, ResourceRegistry, ToolRegistry};
 use clap::Parser;
 
@@ -27,14 +28,30 @@ struct Args {
     no_verify_ssl: bool,
     #[arg(long, env = "ARIA2_MCP_VERIFY_SSL")]
     verify_ssl: bool,
+    #[arg(long, env = "ARIA2_MCP_RPC_TRANSPORT", value_parser = parse_rpc_transport)]
+    rpc_transport: Option<aria2_mcp_rs::config::RpcTransport>,
     #[arg(short = 'i', long, value_parser = parse_instance_arg)]
     instance: Vec<aria2_mcp_rs::config::Aria2Instance>,
+    /// Start and supervise a local aria2c instead of connecting to one
+    #[arg(long, env = "ARIA2_MCP_MANAGED")]
+    managed: bool,
+}
+
+fn parse_rpc_transport(s: &str) -> Result<aria2_mcp_rs::config::RpcTransport, String> {
+    match s.to_lowercase().as_str() {
+        "http" => Ok(aria2_mcp_rs::config::RpcTransport::Http),
+        "websocket" | "ws" => Ok(aria2_mcp_rs::config::RpcTransport::WebSocket),
+        _ => Err(format!(
+            "Invalid RPC transport: {s}. Expected 'http' or 'websocket'"
+        )),
+    }
 }
 
 fn parse_instance_arg(s: &str) -> Result<aria2_mcp_rs::config::Aria2Instance, String> {
     let mut name = None;
     let mut url = None;
     let mut secret = None;
+    let mut transport = None;
 
     for part in s.split(',') {
         if let Some((key, value)) = part.split_once('=') {
@@ -42,6 +59,7 @@ fn parse_instance_arg(s: &str) -> Result<aria2_mcp_rs::config::Aria2Instance, St
                 "name" => name = Some(value.to_string()),
                 "url" => url = Some(value.to_string()),
                 "secret" => secret = Some(value.to_string()),
+                "transport" => transport = Some(parse_rpc_transport(value)?),
                 _ => return Err(format!("Unknown instance key: {key}")),
             }
         } else {
@@ -60,6 +78,7 @@ fn parse_instance_arg(s: &str) -> Result<aria2_mcp_rs::config::Aria2Instance, St
         name,
         rpc_url,
         rpc_secret: secret,
+        rpc_transport: transport,
     })
 }
 
@@ -105,11 +124,17 @@ fn main() -> Result<()> {
     if args.lazy {
         config.lazy_mode = true;
     }
+    if let Some(rpc_transport) = args.rpc_transport {
+        config.rpc_transport = rpc_transport;
+    }
     if args.verify_ssl {
         config.no_verify_ssl = false;
     } else if args.no_verify_ssl {
         config.no_verify_ssl = true;
     }
+    if args.managed {
+        config.managed_aria2.enabled = true;
+    }
 
     // Add instances from CLI if provided
     for inst in args.instance {
@@ -128,8 +153,8 @@ fn main() -> Result<()> {
     let rt = tokio::runtime::Runtime::new()?;
     rt.block_on(async {
         // Load persistent state and merge into config
-        let state_manager = aria2_mcp_rs::state::StateManager::new(std::path::PathBuf::from(
-            "aria2_mcp_state.json",
+        let state_manager = std::sync::Arc::new(aria2_mcp_rs::state::StateManager::new(
+            std::path::PathBuf::from("aria2_mcp_state.json"),
         ));
         if let Ok(state) = state_manager.load().await {
             for (k, v) in state.bandwidth_profiles {
@@ -149,7 +174,7 @@ fn main() -> Result<()> {
             }
         }
 
-        run_app(config).await
+        run_app(config, state_manager).await
     })
 }
 
@@ -175,7 +200,18 @@ fn parse_log_level(level: &str) -> &str {
     }
 }
 
-async fn run_app(config: Config) -> Result<()> {
+async fn run_app(
+    mut config: Config,
+    state_manager: std::sync::Arc<aria2_mcp_rs::state::StateManager>,
+) -> Result<()> {
+    let supervisor = if config.managed_aria2.enabled {
+        let supervisor = Aria2Supervisor::start(config.managed_aria2.clone()).await?;
+        supervisor.register(&mut config);
+        Some(supervisor)
+    } else {
+        None
+    };
+
     log::info!(
         "Starting aria2-mcp-rs with {} instances...",
         config.instances.len()
@@ -184,7 +220,12 @@ async fn run_app(config: Config) -> Result<()> {
     let clients: Vec<Aria2Client> = config
         .instances
         .iter()
-        .map(|instance| Aria2Client::new_with_instance(config.clone(), instance.clone()))
+        .map(|instance| {
+            let mut client = Aria2Client::new_with_instance(config.clone(), instance.clone());
+            // One manager for every instance, so their saves are serialized
+            client.state_manager = std::sync::Arc::clone(&state_manager);
+            client
+        })
         .collect();
 
     let registry = ToolRegistry::new(&config);
@@ -201,9 +242,12 @@ async fn run_app(config: Config) -> Result<()> {
         prompt_registry,
         clients,
     );
-    server.run().await?;
+    let result = server.run().await;
 
-    Ok(())
+    if let Some(supervisor) = supervisor {
+        supervisor.stop().await;
+    }
+    result
 }
 
 #[cfg(test)]
@@ -411,6 +455,7 @@ mod tests {
                 name: "local".to_string(),
                 rpc_url: "http://config-url:6800/jsonrpc".to_string(),
                 rpc_secret: None,
+                rpc_transport: None,
             }],
             ..Config::default()
         };
@@ -476,6 +521,7 @@ mod tests {
                 name: "default".to_string(),
                 rpc_url,
                 rpc_secret: None,
+                rpc_transport: None,
             }],
             ..Config::default()
         };
@@ -634,6 +680,30 @@ mod tests {
         assert!(result.unwrap_err().contains("Unknown instance key"));
     }
 
+    #[test]
+    fn test_parse_instance_arg_transport() {
+        let inst =
+            parse_instance_arg("name=ws,url=http://localhost:6800/jsonrpc,transport=websocket")
+                .unwrap();
+        assert_eq!(
+            inst.rpc_transport,
+            Some(aria2_mcp_rs::config::RpcTransport::WebSocket)
+        );
+
+        let result = parse_instance_arg("name=ws,url=http://localhost:6800/jsonrpc,transport=ftp");
+        assert!(result.unwrap_err().contains("Invalid RPC transport"));
+    }
+
+    #[test]
+    fn test_args_parse_rpc_transport() {
+        let args = Args::try_parse_from(["aria2-mcp-rs", "--rpc-transport", "ws"]).unwrap();
+        assert_eq!(
+            args.rpc_transport,
+            Some(aria2_mcp_rs::config::RpcTransport::WebSocket)
+        );
+        assert!(Args::try_parse_from(["aria2-mcp-rs", "--rpc-transport", "ftp"]).is_err());
+    }
+
     #[test]
     fn test_init_logger() {
         // We call it to cover the branches
@@ -0,0 +1,417 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::aria2::{Aria2Client, GlobalStat};
+use crate::tools::check_health::get_disk_info;
+
+/// Upper bounds, in seconds, of the tool latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 10] = [0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Events on one aria2 instance, exported by `/metrics`.
+#[derive(Debug, Default)]
+pub struct InstanceCounters {
+    /// RPC requests that failed or returned an aria2 error
+    pub rpc_failures: AtomicU64,
+    /// RSS items added as downloads
+    pub rss_matches: AtomicU64,
+    /// Failed downloads retried by the recovery task
+    pub recoveries: AtomicU64,
+    /// Stopped downloads removed by the purge task
+    pub purges: AtomicU64,
+}
+
+impl InstanceCounters {
+    pub fn add(counter: &AtomicU64, count: u64) {
+        counter.fetch_add(count, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct ToolStats {
+    calls: u64,
+    errors: u64,
+    duration_sum: f64,
+    /// Calls per latency bucket, not cumulative
+    buckets: [u64; LATENCY_BUCKETS.len()],
+}
+
+/// Calls, errors and latency of each tool.
+#[derive(Debug, Default)]
+pub struct ToolMetrics {
+    tools: Mutex<BTreeMap<String, ToolStats>>,
+}
+
+impl ToolMetrics {
+    pub fn observe(&self, tool: &str, elapsed: Duration, ok: bool) {
+        let mut tools = self.tools.lock().unwrap();
+        let stats = tools.entry(tool.to_string()).or_default();
+        let secs = elapsed.as_secs_f64();
+        stats.calls += 1;
+        if !ok {
+            stats.errors += 1;
+        }
+        stats.duration_sum += secs;
+        if let Some(bucket) = LATENCY_BUCKETS.iter().position(|le| secs <= *le) {
+            stats.buckets[bucket] += 1;
+        }
+    }
+
+    fn render(&self, out: &mut String) {
+        let tools = self.tools.lock().unwrap().clone();
+
+        header(
+            out,
+            "aria2_mcp_tool_calls_total",
+            "counter",
+            "Tool calls by tool",
+        );
+        for (tool, stats) in &tools {
+            sample(
+                out,
+                "aria2_mcp_tool_calls_total",
+                &[("tool", tool)],
+                stats.calls,
+            );
+        }
+
+        header(
+            out,
+            "aria2_mcp_tool_errors_total",
+            "counter",
+            "Tool calls that returned an error, by tool",
+        );
+        for (tool, stats) in &tools {
+            sample(
+                out,
+                "aria2_mcp_tool_errors_total",
+                &[("tool", tool)],
+                stats.errors,
+            );
+        }
+
+        header(
+            out,
+            "aria2_mcp_tool_duration_seconds",
+            "histogram",
+            "Tool call latency by tool",
+        );
+        for (tool, stats) in &tools {
+            let mut cumulative = 0;
+            for (le, count) in LATENCY_BUCKETS.iter().zip(stats.buckets) {
+                cumulative += count;
+                sample(
+                    out,
+                    "aria2_mcp_tool_duration_seconds_bucket",
+                    &[("tool", tool), ("le", &le.to_string())],
+                    cumulative,
+                );
+            }
+            sample(
+                out,
+                "aria2_mcp_tool_duration_seconds_bucket",
+                &[("tool", tool), ("le", "+Inf")],
+                stats.calls,
+            );
+            sample(
+                out,
+                "aria2_mcp_tool_duration_seconds_sum",
+                &[("tool", tool)],
+                stats.duration_sum,
+            );
+            sample(
+                out,
+                "aria2_mcp_tool_duration_seconds_count",
+                &[("tool", tool)],
+                stats.calls,
+            );
+        }
+    }
+}
+
+/// Current state of an instance, read when `/metrics` is scraped.
+#[derive(Debug, Clone, Default)]
+struct InstanceGauges {
+    global_stat: GlobalStat,
+    /// Free space in the download directory, if it could be read
+    disk_free: Option<u64>,
+}
+
+async fn collect_gauges(client: &Aria2Client) -> anyhow::Result<InstanceGauges> {
+    let global_stat: GlobalStat = serde_json::from_value(client.get_global_stat().await?)?;
+
+    // Same download directory and disk check as `check_health`
+    let global_options = client.get_global_option().await?;
+    let download_dir = global_options
+        .get("dir")
+        .and_then(|v| v.as_str())
+        .unwrap_or(".");
+    let disk_free = get_disk_info(download_dir).ok().map(|d| d.available);
+
+    Ok(InstanceGauges {
+        global_stat,
+        disk_free,
+    })
+}
+
+/// Renders the metrics of the server and of every instance in the
+/// Prometheus text exposition format.
+pub async fn render(tools: &ToolMetrics, clients: &[Arc<Aria2Client>]) -> String {
+    let mut instances = Vec::new();
+    for client in clients {
+        let gauges = match collect_gauges(client).await {
+            Ok(gauges) => Some(gauges),
+            Err(e) => {
+                log::debug!("Failed to collect metrics for {}: {e}", client.name);
+                None
+            }
+        };
+        instances.push((client, gauges));
+    }
+
+    let mut out = String::new();
+
+    header(
+        &mut out,
+        "aria2_mcp_instance_up",
+        "gauge",
+        "Whether the aria2 instance answered the last scrape",
+    );
+    for (client, gauges) in &instances {
+        sample(
+            &mut out,
+            "aria2_mcp_instance_up",
+            &[("instance", &client.name)],
+            u64::from(gauges.is_some()),
+        );
+    }
+
+    let reachable: Vec<(&str, &InstanceGauges)> = instances
+        .iter()
+        .filter_map(|(client, gauges)| gauges.as_ref().map(|g| (client.name.as_str(), g)))
+        .collect();
+
+    header(
+        &mut out,
+        "aria2_mcp_downloads",
+        "gauge",
+        "Downloads in the queue by state",
+    );
+    for (instance, gauges) in &reachable {
+        let stat = &gauges.global_stat;
+        for (state, count) in [
+            ("active", stat.num_active),
+            ("waiting", stat.num_waiting),
+            ("stopped", stat.num_stopped),
+        ] {
+            sample(
+                &mut out,
+                "aria2_mcp_downloads",
+                &[("instance", instance), ("state", state)],
+                count,
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "aria2_mcp_download_speed_bytes",
+        "gauge",
+        "Overall download speed in bytes per second",
+    );
+    for (instance, gauges) in &reachable {
+        sample(
+            &mut out,
+            "aria2_mcp_download_speed_bytes",
+            &[("instance", instance)],
+            gauges.global_stat.download_speed,
+        );
+    }
+
+    header(
+        &mut out,
+        "aria2_mcp_upload_speed_bytes",
+        "gauge",
+        "Overall upload speed in bytes per second",
+    );
+    for (instance, gauges) in &reachable {
+        sample(
+            &mut out,
+            "aria2_mcp_upload_speed_bytes",
+            &[("instance", instance)],
+            gauges.global_stat.upload_speed,
+        );
+    }
+
+    header(
+        &mut out,
+        "aria2_mcp_disk_free_bytes",
+        "gauge",
+        "Free space in the download directory",
+    );
+    for (instance, gauges) in &reachable {
+        if let Some(disk_free) = gauges.disk_free {
+            sample(
+                &mut out,
+                "aria2_mcp_disk_free_bytes",
+                &[("instance", instance)],
+                disk_free,
+            );
+        }
+    }
+
+    for (name, help, counter) in [
+        (
+            "aria2_mcp_rpc_failures_total",
+            "RPC requests to aria2 that failed",
+            (|c: &InstanceCounters| &c.rpc_failures) as fn(&InstanceCounters) -> &AtomicU64,
+        ),
+        (
+            "aria2_mcp_rss_matches_total",
+            "RSS items added as downloads",
+            |c| &c.rss_matches,
+        ),
+        (
+            "aria2_mcp_recoveries_total",
+            "Failed downloads retried automatically",
+            |c| &c.recoveries,
+        ),
+        (
+            "aria2_mcp_purges_total",
+            "Stopped downloads purged automatically",
+            |c| &c.purges,
+        ),
+    ] {
+        header(&mut out, name, "counter", help);
+        for (client, _) in &instances {
+            sample(
+                &mut out,
+                name,
+                &[("instance", &client.name)],
+                counter(&client.counters).load(Ordering::Relaxed),
+            );
+        }
+    }
+
+    tools.render(&mut out);
+    out
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: impl std::fmt::Display) {
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|(key, value)| format!("{key}=\"{}\"", escape_label(value)))
+        .collect();
+    let _ = writeln!(out, "{name}{{{}}} {value}", labels.join(","));
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_tool_metrics_render() {
+        let metrics = ToolMetrics::default();
+        metrics.observe("monitor_queue", Duration::from_millis(20), true);
+        metrics.observe("monitor_queue", Duration::from_secs(3), false);
+        metrics.observe("monitor_queue", Duration::from_secs(60), true);
+
+        let mut out = String::new();
+        metrics.render(&mut out);
+        assert!(out.contains("# TYPE aria2_mcp_tool_calls_total counter\n"));
+        assert!(out.contains("aria2_mcp_tool_calls_total{tool=\"monitor_queue\"} 3\n"));
+        assert!(out.contains("aria2_mcp_tool_errors_total{tool=\"monitor_queue\"} 1\n"));
+        assert!(out.contains(
+            "aria2_mcp_tool_duration_seconds_bucket{tool=\"monitor_queue\",le=\"0.05\"} 1\n"
+        ));
+        assert!(out.contains(
+            "aria2_mcp_tool_duration_seconds_bucket{tool=\"monitor_queue\",le=\"5\"} 2\n"
+        ));
+        assert!(out.contains(
+            "aria2_mcp_tool_duration_seconds_bucket{tool=\"monitor_queue\",le=\"+Inf\"} 3\n"
+        ));
+        assert!(out.contains("aria2_mcp_tool_duration_seconds_count{tool=\"monitor_queue\"} 3\n"));
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[tokio::test]
+    async fn test_render_instances() {
+        use wiremock::matchers::{body_string_contains, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_string_contains("aria2.getGlobalStat"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": {
+                    "downloadSpeed": "2048",
+                    "uploadSpeed": "16",
+                    "numActive": "2",
+                    "numWaiting": "1",
+                    "numStopped": "4"
+                }
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_string_contains("aria2.getGlobalOption"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": { "dir": "/" }
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = Aria2Client::new(Config {
+            rpc_url: format!("{}/jsonrpc", mock_server.uri()),
+            ..Config::default()
+        });
+        InstanceCounters::add(&client.counters.purges, 3);
+
+        let mut unreachable = Aria2Client::new(Config {
+            rpc_url: "http://127.0.0.1:1/jsonrpc".to_string(),
+            ..Config::default()
+        });
+        unreachable.name = "offline".to_string();
+
+        let out = render(
+            &ToolMetrics::default(),
+            &[Arc::new(client), Arc::new(unreachable)],
+        )
+        .await;
+        assert!(out.contains("aria2_mcp_instance_up{instance=\"default\"} 1\n"));
+        assert!(out.contains("aria2_mcp_instance_up{instance=\"offline\"} 0\n"));
+        assert!(out.contains("aria2_mcp_downloads{instance=\"default\",state=\"active\"} 2\n"));
+        assert!(out.contains("aria2_mcp_downloads{instance=\"default\",state=\"stopped\"} 4\n"));
+        assert!(out.contains("aria2_mcp_download_speed_bytes{instance=\"default\"} 2048\n"));
+        assert!(out.contains("aria2_mcp_disk_free_bytes{instance=\"default\"}"));
+        assert!(!out.contains("aria2_mcp_downloads{instance=\"offline\""));
+        assert!(out.contains("aria2_mcp_purges_total{instance=\"default\"} 3\n"));
+        // The offline instance's failed requests are counted
+        assert!(out.contains("aria2_mcp_rpc_failures_total{instance=\"offline\"} 1\n"));
+    }
+}
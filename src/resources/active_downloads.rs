@@ -2,7 +2,7 @@ use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value;
 
-use crate::aria2::Aria2Client;
+use crate::aria2::{Aria2Client, Aria2Event};
 use crate::resources::McpResource;
 
 pub struct ActiveDownloadsResource;
@@ -25,6 +25,10 @@ impl McpResource for ActiveDownloadsResource {
         Some("application/json".to_string())
     }
 
+    fn updated_by(&self, _event: Aria2Event) -> bool {
+        true
+    }
+
     async fn read(&self, client: &Aria2Client) -> Result<Value> {
         client.tell_active(None).await
     }
@@ -0,0 +1,149 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::aria2::Aria2Client;
+use crate::resources::{find_instance, McpResourceTemplate};
+
+/// Which part of a download's state the template exposes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadView {
+    Status,
+    Files,
+    Peers,
+}
+
+/// Live state of a single download on a named instance, e.g.
+/// `aria2://instances/default/downloads/2089b05ecca3d829/files`.
+pub struct DownloadResourceTemplate {
+    view: DownloadView,
+}
+
+impl DownloadResourceTemplate {
+    #[must_use]
+    pub fn new(view: DownloadView) -> Self {
+        Self { view }
+    }
+}
+
+#[async_trait]
+impl McpResourceTemplate for DownloadResourceTemplate {
+    fn uri_template(&self) -> String {
+        let base = "aria2://instances/{instance}/downloads/{gid}";
+        match self.view {
+            DownloadView::Status => base.to_string(),
+            DownloadView::Files => format!("{base}/files"),
+            DownloadView::Peers => format!("{base}/peers"),
+        }
+    }
+
+    fn name(&self) -> String {
+        match self.view {
+            DownloadView::Status => "Download Status",
+            DownloadView::Files => "Download Files",
+            DownloadView::Peers => "Download Peers",
+        }
+        .to_string()
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(
+            match self.view {
+                DownloadView::Status => "Full status of a single download.",
+                DownloadView::Files => "Files of a single download with their progress.",
+                DownloadView::Peers => "Peers connected for a single BitTorrent download.",
+            }
+            .to_string(),
+        )
+    }
+
+    fn mime_type(&self) -> Option<String> {
+        Some("application/json".to_string())
+    }
+
+    async fn read(
+        &self,
+        clients: &[Arc<Aria2Client>],
+        vars: &HashMap<String, String>,
+    ) -> Result<Value> {
+        let instance = vars.get("instance").map_or("", String::as_str);
+        let gid = vars.get("gid").map_or("", String::as_str);
+        let client = find_instance(clients, instance)?;
+        match self.view {
+            DownloadView::Status => client.tell_status(gid).await,
+            DownloadView::Files => client.get_files(gid).await,
+            DownloadView::Peers => client.get_peers(gid).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use wiremock::matchers::{body_partial_json, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn vars(instance: &str, gid: &str) -> HashMap<String, String> {
+        HashMap::from([
+            ("instance".to_string(), instance.to_string()),
+            ("gid".to_string(), gid.to_string()),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_download_template_reads_named_instance() -> Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({
+                "method": "aria2.getFiles",
+                "params": ["abc"]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": [{ "index": "1", "path": "/downloads/file.iso" }]
+            })))
+            .mount(&server)
+            .await;
+
+        let unreachable = Arc::new(Aria2Client::new(Config::default()));
+        let mut nas = Aria2Client::new(Config {
+            rpc_url: format!("http://{}", server.address()),
+            ..Config::default()
+        });
+        nas.name = "nas".to_string();
+        let clients = vec![unreachable, Arc::new(nas)];
+
+        let template = DownloadResourceTemplate::new(DownloadView::Files);
+        let result = template.read(&clients, &vars("nas", "abc")).await?;
+        assert_eq!(result[0]["path"], "/downloads/file.iso");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_template_unknown_instance() {
+        let clients = vec![Arc::new(Aria2Client::new(Config::default()))];
+        let template = DownloadResourceTemplate::new(DownloadView::Status);
+        let err = template
+            .read(&clients, &vars("missing", "abc"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Unknown instance: missing"));
+    }
+
+    #[test]
+    fn test_download_template_uris() {
+        assert_eq!(
+            DownloadResourceTemplate::new(DownloadView::Status).uri_template(),
+            "aria2://instances/{instance}/downloads/{gid}"
+        );
+        assert_eq!(
+            DownloadResourceTemplate::new(DownloadView::Peers).uri_template(),
+            "aria2://instances/{instance}/downloads/{gid}/peers"
+        );
+    }
+}
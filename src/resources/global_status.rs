@@ -3,7 +3,7 @@ use async_trait::async_trait;
 use serde_json::Value;
 use std::sync::Arc;
 
-use crate::aria2::Aria2Client;
+use crate::aria2::{Aria2Client, Aria2Event};
 use crate::resources::McpResource;
 
 pub struct GlobalStatusResource;
@@ -26,6 +26,10 @@ impl McpResource for GlobalStatusResource {
         Some("application/json".to_string())
     }
 
+    fn updated_by(&self, _event: Aria2Event) -> bool {
+        true
+    }
+
     async fn read(&self, client: &Aria2Client) -> Result<Value> {
         client.get_global_stat().await
     }
@@ -0,0 +1,138 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::aria2::{Aria2Client, Aria2Event, GlobalStat};
+use crate::resources::McpResource;
+
+/// Global statistics summed across every instance, with the per-instance
+/// figures they were merged from.
+pub struct GlobalSummaryResource;
+
+fn merge(total: &mut GlobalStat, stat: &GlobalStat) {
+    total.download_speed += stat.download_speed;
+    total.upload_speed += stat.upload_speed;
+    total.num_active += stat.num_active;
+    total.num_waiting += stat.num_waiting;
+    total.num_stopped += stat.num_stopped;
+    total.num_stopped_total += stat.num_stopped_total;
+}
+
+#[async_trait]
+impl McpResource for GlobalSummaryResource {
+    fn uri(&self) -> String {
+        "aria2://status/summary".to_string()
+    }
+
+    fn name(&self) -> String {
+        "Global Status Summary".to_string()
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(
+            "Global statistics summed across all instances, with a per-instance breakdown."
+                .to_string(),
+        )
+    }
+
+    fn mime_type(&self) -> Option<String> {
+        Some("application/json".to_string())
+    }
+
+    fn updated_by(&self, _event: Aria2Event) -> bool {
+        true
+    }
+
+    async fn read(&self, client: &Aria2Client) -> Result<Value> {
+        Ok(serde_json::to_value(client.global_stat().await?)?)
+    }
+
+    async fn read_multi(&self, clients: &[Arc<Aria2Client>]) -> Result<Value> {
+        let mut total = GlobalStat::default();
+        let mut reachable = 0;
+        let mut instances = Vec::new();
+        for client in clients {
+            match client.global_stat().await {
+                Ok(stat) => {
+                    merge(&mut total, &stat);
+                    reachable += 1;
+                    instances.push(serde_json::json!({
+                        "instance": client.name,
+                        "status": "ok",
+                        "stats": stat
+                    }));
+                }
+                Err(e) => {
+                    instances.push(serde_json::json!({
+                        "instance": client.name,
+                        "status": "error",
+                        "error": e.to_string()
+                    }));
+                }
+            }
+        }
+        Ok(serde_json::json!({
+            "total": total,
+            "reachableInstances": reachable,
+            "totalInstances": clients.len(),
+            "instances": instances
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_global_summary_merges_instances() -> Result<()> {
+        let server1 = MockServer::start().await;
+        let server2 = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": "aria2-mcp",
+                "result": {"downloadSpeed": "1000", "numActive": "1", "numWaiting": "2"}
+            })))
+            .mount(&server1)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0", "id": "aria2-mcp",
+                "result": {"downloadSpeed": "500", "numActive": "3", "numWaiting": "0"}
+            })))
+            .mount(&server2)
+            .await;
+
+        let client1 = Arc::new(Aria2Client::new(Config {
+            rpc_url: format!("http://{}", server1.address()),
+            ..Config::default()
+        }));
+        let mut client2 = Aria2Client::new(Config {
+            rpc_url: format!("http://{}", server2.address()),
+            ..Config::default()
+        });
+        client2.name = "box2".to_string();
+        let mut offline = Aria2Client::new(Config::default());
+        offline.name = "offline".to_string();
+
+        let result = GlobalSummaryResource
+            .read_multi(&[client1, Arc::new(client2), Arc::new(offline)])
+            .await?;
+
+        assert_eq!(result["total"]["downloadSpeed"], 1500);
+        assert_eq!(result["total"]["numActive"], 4);
+        assert_eq!(result["total"]["numWaiting"], 2);
+        assert_eq!(result["reachableInstances"], 2);
+        assert_eq!(result["totalInstances"], 3);
+        assert_eq!(result["instances"][1]["instance"], "box2");
+        assert_eq!(result["instances"][1]["stats"]["numActive"], 3);
+        assert_eq!(result["instances"][2]["status"], "error");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::aria2::Aria2Client;
+use crate::resources::{find_instance, McpResource, McpResourceTemplate};
+
+/// Exposes an aggregated resource for a single instance, e.g.
+/// `aria2://downloads/active` as `aria2://instances/{instance}/downloads/active`.
+pub struct InstanceResourceTemplate {
+    resource: Arc<dyn McpResource>,
+}
+
+impl InstanceResourceTemplate {
+    #[must_use]
+    pub fn new(resource: Arc<dyn McpResource>) -> Self {
+        Self { resource }
+    }
+}
+
+#[async_trait]
+impl McpResourceTemplate for InstanceResourceTemplate {
+    fn uri_template(&self) -> String {
+        let uri = self.resource.uri();
+        let path = uri.strip_prefix("aria2://").unwrap_or(&uri);
+        format!("aria2://instances/{{instance}}/{path}")
+    }
+
+    fn name(&self) -> String {
+        format!("{} (Instance)", self.resource.name())
+    }
+
+    fn description(&self) -> Option<String> {
+        self.resource
+            .description()
+            .map(|desc| format!("{desc} For a single instance."))
+    }
+
+    fn mime_type(&self) -> Option<String> {
+        self.resource.mime_type()
+    }
+
+    async fn read(
+        &self,
+        clients: &[Arc<Aria2Client>],
+        vars: &HashMap<String, String>,
+    ) -> Result<Value> {
+        let instance = vars.get("instance").map_or("", String::as_str);
+        let client = find_instance(clients, instance)?;
+        self.resource.read(client).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::resources::ActiveDownloadsResource;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_instance_template_reads_one_instance() -> Result<()> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": [{ "gid": "nas-1" }]
+            })))
+            .mount(&server)
+            .await;
+
+        let unreachable = Arc::new(Aria2Client::new(Config::default()));
+        let mut nas = Aria2Client::new(Config {
+            rpc_url: format!("http://{}", server.address()),
+            ..Config::default()
+        });
+        nas.name = "nas".to_string();
+        let clients = vec![unreachable, Arc::new(nas)];
+
+        let template = InstanceResourceTemplate::new(Arc::new(ActiveDownloadsResource));
+        assert_eq!(
+            template.uri_template(),
+            "aria2://instances/{instance}/downloads/active"
+        );
+
+        let vars = HashMap::from([("instance".to_string(), "nas".to_string())]);
+        let result = template.read(&clients, &vars).await?;
+        assert_eq!(result[0]["gid"], "nas-1");
+
+        Ok(())
+    }
+}
@@ -1,19 +1,29 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::aria2::Aria2Client;
+use crate::aria2::{Aria2Client, Aria2Event};
 
 pub mod active_downloads;
+pub mod download;
 pub mod global_status;
+pub mod global_summary;
+pub mod instance;
 pub mod recent_logs;
 pub mod registry;
+pub mod stopped_downloads;
+pub mod uri_template;
 
 pub use active_downloads::ActiveDownloadsResource;
+pub use download::{DownloadResourceTemplate, DownloadView};
 pub use global_status::GlobalStatusResource;
+pub use global_summary::GlobalSummaryResource;
+pub use instance::InstanceResourceTemplate;
 pub use recent_logs::RecentLogsResource;
 pub use registry::ResourceRegistry;
+pub use stopped_downloads::StoppedDownloadsResource;
 
 #[async_trait]
 pub trait McpResource: Send + Sync {
@@ -21,6 +31,11 @@ pub trait McpResource: Send + Sync {
     fn name(&self) -> String;
     fn description(&self) -> Option<String>;
     fn mime_type(&self) -> Option<String>;
+    /// Whether an aria2 event changes the content of this resource, in which
+    /// case subscribers get `notifications/resources/updated`.
+    fn updated_by(&self, _event: Aria2Event) -> bool {
+        false
+    }
     async fn read(&self, client: &Aria2Client) -> Result<Value>;
     async fn read_multi(&self, clients: &[Arc<Aria2Client>]) -> Result<Value> {
         let mut results = Vec::new();
@@ -46,6 +61,33 @@ pub trait McpResource: Send + Sync {
     }
 }
 
+/// A family of resources addressed by a URI template, whose variables are
+/// passed to `read`.
+#[async_trait]
+pub trait McpResourceTemplate: Send + Sync {
+    fn uri_template(&self) -> String;
+    fn name(&self) -> String;
+    fn description(&self) -> Option<String>;
+    fn mime_type(&self) -> Option<String>;
+    async fn read(
+        &self,
+        clients: &[Arc<Aria2Client>],
+        vars: &HashMap<String, String>,
+    ) -> Result<Value>;
+}
+
+/// Finds the client for an `{instance}` URI variable, by name or by index.
+pub fn find_instance<'a>(
+    clients: &'a [Arc<Aria2Client>],
+    instance: &str,
+) -> Result<&'a Arc<Aria2Client>> {
+    clients
+        .iter()
+        .find(|c| c.name == instance)
+        .or_else(|| instance.parse::<usize>().ok().and_then(|i| clients.get(i)))
+        .ok_or_else(|| anyhow::anyhow!("Unknown instance: {instance}"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -88,13 +130,28 @@ mod tests {
         registry.register(r2);
 
         let list = registry.list_resources();
-        assert_eq!(list.len(), 2 + 3); // 2 mocks + 3 default resources (GlobalStatus, ActiveDownloads, RecentLogs)
+        assert_eq!(list.len(), 2 + 5); // 2 mocks + 5 default resources (GlobalStatus, GlobalSummary, ActiveDownloads, StoppedDownloads, RecentLogs)
                                        // GlobalStatus is aria2://status/global
+                                       // GlobalSummary is aria2://status/summary
                                        // ActiveDownloads is aria2://downloads/active
+                                       // StoppedDownloads is aria2://downloads/stopped
                                        // RecentLogs is aria2://logs/recent
                                        // Mocks are test://1 and test://2
 
         assert!(registry.get_resource("test://1").is_some());
         assert!(registry.get_resource("test://3").is_none());
     }
+
+    #[test]
+    fn test_find_instance() {
+        let first = Arc::new(Aria2Client::new(crate::Config::default()));
+        let mut second = Aria2Client::new(crate::Config::default());
+        second.name = "nas".to_string();
+        let clients = vec![first, Arc::new(second)];
+
+        assert_eq!(find_instance(&clients, "nas").unwrap().name, "nas");
+        assert_eq!(find_instance(&clients, "1").unwrap().name, "nas");
+        assert!(find_instance(&clients, "2").is_err());
+        assert!(find_instance(&clients, "other").is_err());
+    }
 }
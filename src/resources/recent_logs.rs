@@ -1,6 +1,7 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value;
+use std::sync::Arc;
 
 use crate::aria2::Aria2Client;
 use crate::resources::McpResource;
@@ -40,6 +41,14 @@ impl McpResource for RecentLogsResource {
         Some("text/plain".to_string())
     }
 
+    /// The log is shared by all instances, so it is read once.
+    async fn read_multi(&self, clients: &[Arc<Aria2Client>]) -> Result<Value> {
+        let client = clients
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("No clients provided"))?;
+        self.read(client).await
+    }
+
     async fn read(&self, _client: &Aria2Client) -> Result<Value> {
         // This resource reads from the filesystem, not directly from an Aria2Client.
         // The client parameter is kept for trait consistency.
@@ -2,14 +2,24 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use super::McpResource;
+use super::uri_template::match_uri_template;
+use super::{McpResource, McpResourceTemplate};
+use crate::aria2::Aria2Event;
 
 use super::active_downloads::ActiveDownloadsResource;
+use super::download::{DownloadResourceTemplate, DownloadView};
 use super::global_status::GlobalStatusResource;
+use super::global_summary::GlobalSummaryResource;
+use super::instance::InstanceResourceTemplate;
 use super::recent_logs::RecentLogsResource; // Import RecentLogsResource
+use super::stopped_downloads::StoppedDownloadsResource;
+
+/// A resource template together with the variables matched from a URI.
+pub type TemplateMatch = (Arc<dyn McpResourceTemplate>, HashMap<String, String>);
 
 pub struct ResourceRegistry {
     resources: HashMap<String, Arc<dyn McpResource>>,
+    templates: Vec<Arc<dyn McpResourceTemplate>>,
 }
 
 impl Default for ResourceRegistry {
@@ -23,16 +33,37 @@ impl ResourceRegistry {
     pub fn new() -> Self {
         let mut registry = Self {
             resources: HashMap::new(),
+            templates: Vec::new(),
         };
 
-        registry.register(Arc::new(GlobalStatusResource));
-        registry.register(Arc::new(ActiveDownloadsResource));
+        registry.register(Arc::new(GlobalSummaryResource));
+
+        // Aggregated across all instances, and per instance via a template
+        let per_instance: [Arc<dyn McpResource>; 3] = [
+            Arc::new(GlobalStatusResource),
+            Arc::new(ActiveDownloadsResource),
+            Arc::new(StoppedDownloadsResource),
+        ];
+        for resource in per_instance {
+            registry.register_template(Arc::new(InstanceResourceTemplate::new(Arc::clone(
+                &resource,
+            ))));
+            registry.register(resource);
+        }
         // Register RecentLogsResource with default values
         registry.register(Arc::new(RecentLogsResource::new(
             "/var/log/aria2-mcp-rs.log".to_string(),
             100,
         )));
 
+        for view in [
+            DownloadView::Status,
+            DownloadView::Files,
+            DownloadView::Peers,
+        ] {
+            registry.register_template(Arc::new(DownloadResourceTemplate::new(view)));
+        }
+
         registry
     }
 
@@ -40,11 +71,57 @@ impl ResourceRegistry {
         self.resources.insert(resource.uri(), resource);
     }
 
+    pub fn register_template(&mut self, template: Arc<dyn McpResourceTemplate>) {
+        self.templates.push(template);
+    }
+
+    /// Finds the template matching `uri`, for URIs that are not a fixed
+    /// resource.
+    #[must_use]
+    pub fn match_template(&self, uri: &str) -> Option<TemplateMatch> {
+        self.templates.iter().find_map(|template| {
+            match_uri_template(&template.uri_template(), uri)
+                .map(|vars| (Arc::clone(template), vars))
+        })
+    }
+
+    #[must_use]
+    pub fn list_templates(&self) -> Vec<Value> {
+        let mut results = Vec::new();
+        for template in &self.templates {
+            let mut val = serde_json::json!({
+                "uriTemplate": template.uri_template(),
+                "name": template.name(),
+            });
+            if let Some(desc) = template.description() {
+                val["description"] = serde_json::json!(desc);
+            }
+            if let Some(mime) = template.mime_type() {
+                val["mimeType"] = serde_json::json!(mime);
+            }
+            results.push(val);
+        }
+        results
+    }
+
     #[must_use]
     pub fn get_resource(&self, uri: &str) -> Option<Arc<dyn McpResource>> {
         self.resources.get(uri).cloned()
     }
 
+    /// URIs of the resources whose content changes with `event`.
+    #[must_use]
+    pub fn updated_by(&self, event: Aria2Event) -> Vec<String> {
+        let mut uris: Vec<String> = self
+            .resources
+            .values()
+            .filter(|r| r.updated_by(event))
+            .map(|r| r.uri())
+            .collect();
+        uris.sort();
+        uris
+    }
+
     #[must_use]
     pub fn list_resources(&self) -> Vec<Value> {
         let mut results = Vec::new();
@@ -110,19 +187,69 @@ mod tests {
         registry.register(r2);
 
         let list = registry.list_resources();
-        // 3 default resources + 2 mock resources
-        assert_eq!(list.len(), 5);
+        // 5 default resources + 2 mock resources
+        assert_eq!(list.len(), 7);
 
         // Resources are sorted by URI:
         // 1. aria2://downloads/active
-        // 2. aria2://logs/recent
-        // 3. aria2://status/global
-        // 4. test://1
-        // 5. test://2
-        assert_eq!(list[3]["uri"], "test://1");
-        assert_eq!(list[4]["uri"], "test://2");
+        // 2. aria2://downloads/stopped
+        // 3. aria2://logs/recent
+        // 4. aria2://status/global
+        // 5. aria2://status/summary
+        // 6. test://1
+        // 7. test://2
+        assert_eq!(list[5]["uri"], "test://1");
+        assert_eq!(list[6]["uri"], "test://2");
 
         assert!(registry.get_resource("test://1").is_some());
         assert!(registry.get_resource("test://3").is_none());
     }
+
+    #[test]
+    fn test_registry_updated_by() {
+        let mut registry = ResourceRegistry::new();
+        registry.register(Arc::new(MockResource {
+            uri: "test://1".to_string(),
+        }));
+
+        assert_eq!(
+            registry.updated_by(Aria2Event::DownloadComplete),
+            vec![
+                "aria2://downloads/active",
+                "aria2://downloads/stopped",
+                "aria2://status/global",
+                "aria2://status/summary"
+            ]
+        );
+        assert_eq!(
+            registry.updated_by(Aria2Event::DownloadStart),
+            vec![
+                "aria2://downloads/active",
+                "aria2://status/global",
+                "aria2://status/summary"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_registry_templates() {
+        let registry = ResourceRegistry::new();
+
+        let templates = registry.list_templates();
+        assert_eq!(templates.len(), 6);
+        assert_eq!(
+            templates[0]["uriTemplate"],
+            "aria2://instances/{instance}/status/global"
+        );
+
+        let (template, vars) = registry
+            .match_template("aria2://instances/default/downloads/abc/peers")
+            .unwrap();
+        assert_eq!(template.name(), "Download Peers");
+        assert_eq!(vars["gid"], "abc");
+
+        assert!(registry
+            .match_template("aria2://downloads/active")
+            .is_none());
+    }
 }
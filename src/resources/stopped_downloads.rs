@@ -0,0 +1,90 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::aria2::{Aria2Client, Aria2Event};
+use crate::resources::McpResource;
+
+/// Number of finished downloads returned by the resource.
+const STOPPED_LIMIT: i32 = 100;
+
+pub struct StoppedDownloadsResource;
+
+#[async_trait]
+impl McpResource for StoppedDownloadsResource {
+    fn uri(&self) -> String {
+        "aria2://downloads/stopped".to_string()
+    }
+
+    fn name(&self) -> String {
+        "Stopped Downloads".to_string()
+    }
+
+    fn description(&self) -> Option<String> {
+        Some(format!(
+            "Up to {STOPPED_LIMIT} completed, failed or removed downloads."
+        ))
+    }
+
+    fn mime_type(&self) -> Option<String> {
+        Some("application/json".to_string())
+    }
+
+    fn updated_by(&self, event: Aria2Event) -> bool {
+        matches!(
+            event,
+            Aria2Event::DownloadStop
+                | Aria2Event::DownloadComplete
+                | Aria2Event::DownloadError
+                | Aria2Event::BtDownloadComplete
+        )
+    }
+
+    async fn read(&self, client: &Aria2Client) -> Result<Value> {
+        client.tell_stopped(0, STOPPED_LIMIT, None).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use wiremock::matchers::{body_partial_json, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_stopped_downloads_resource_read() -> Result<()> {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(body_partial_json(serde_json::json!({
+                "method": "aria2.tellStopped",
+                "params": [0, 100]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": [{ "gid": "456", "status": "complete" }]
+            })))
+            .mount(&server)
+            .await;
+
+        let config = Config {
+            rpc_url: format!("http://{}", server.address()),
+            ..Config::default()
+        };
+        let client = Aria2Client::new(config);
+
+        let result = StoppedDownloadsResource.read(&client).await?;
+        assert_eq!(result[0]["gid"], "456");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stopped_downloads_updated_by() {
+        assert!(StoppedDownloadsResource.updated_by(Aria2Event::DownloadComplete));
+        assert!(!StoppedDownloadsResource.updated_by(Aria2Event::DownloadStart));
+        assert!(!StoppedDownloadsResource.updated_by(Aria2Event::DownloadPause));
+    }
+}
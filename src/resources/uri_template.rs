@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+/// Matches `uri` against a level 1 URI template such as
+/// `aria2://instances/{instance}/downloads/{gid}`, returning the value of
+/// each variable. A variable matches one non-empty path segment.
+#[must_use]
+pub fn match_uri_template(template: &str, uri: &str) -> Option<HashMap<String, String>> {
+    let mut vars = HashMap::new();
+    let mut rest = uri;
+    let mut parts = template.split('{');
+
+    let prefix = parts.next().unwrap_or_default();
+    rest = rest.strip_prefix(prefix)?;
+
+    for part in parts {
+        let (name, literal) = part.split_once('}')?;
+        let end = rest.find('/').unwrap_or(rest.len());
+        let value = &rest[..end];
+        if value.is_empty() {
+            return None;
+        }
+        vars.insert(name.to_string(), value.to_string());
+        rest = rest[end..].strip_prefix(literal)?;
+    }
+
+    rest.is_empty().then_some(vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = "aria2://instances/{instance}/downloads/{gid}";
+
+    #[test]
+    fn test_match_extracts_variables() {
+        let vars =
+            match_uri_template(TEMPLATE, "aria2://instances/default/downloads/2089b05e").unwrap();
+        assert_eq!(vars["instance"], "default");
+        assert_eq!(vars["gid"], "2089b05e");
+    }
+
+    #[test]
+    fn test_match_with_suffix() {
+        let template = "aria2://instances/{instance}/downloads/{gid}/files";
+        let vars = match_uri_template(template, "aria2://instances/nas/downloads/1/files").unwrap();
+        assert_eq!(vars["instance"], "nas");
+        assert_eq!(vars["gid"], "1");
+
+        assert!(match_uri_template(template, "aria2://instances/nas/downloads/1").is_none());
+        assert!(match_uri_template(template, "aria2://instances/nas/downloads/1/peers").is_none());
+    }
+
+    #[test]
+    fn test_match_rejects_other_uris() {
+        assert!(match_uri_template(TEMPLATE, "aria2://downloads/active").is_none());
+        assert!(match_uri_template(TEMPLATE, "aria2://instances//downloads/1").is_none());
+        assert!(match_uri_template(TEMPLATE, "aria2://instances/a/downloads/1/files").is_none());
+    }
+
+    #[test]
+    fn test_match_without_variables() {
+        let vars = match_uri_template("aria2://downloads/stopped", "aria2://downloads/stopped");
+        assert!(vars.unwrap().is_empty());
+    }
+}
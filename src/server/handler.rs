@@ -118,6 +118,20 @@ impl McpHandler {
     }
 }
 
+async fn read_with_timeout(
+    read: impl std::future::Future<Output = anyhow::Result<serde_json::Value>>,
+) -> Result<serde_json::Value, Error> {
+    tokio::time::timeout(std::time::Duration::from_secs(30), read)
+        .await
+        .map_err(|_| {
+            Error::protocol(
+                ErrorCode::InternalError,
+                "Resource read timed out".to_string(),
+            )
+        })?
+        .map_err(|e| Error::protocol(ErrorCode::InternalError, e.to_string()))
+}
+
 #[async_trait]
 impl ServerHandler for McpHandler {
     async fn initialize(
@@ -193,6 +207,11 @@ impl ServerHandler for McpHandler {
                 let resources = registry.list_resources();
                 Ok(serde_json::json!({ "resources": resources }))
             }
+            "resources/templates/list" => {
+                let registry = self.resource_registry.read().await;
+                let templates = registry.list_templates();
+                Ok(serde_json::json!({ "resourceTemplates": templates }))
+            }
             "resources/read" => {
                 let params = params.ok_or_else(|| {
                     Error::protocol(
@@ -206,33 +225,32 @@ impl ServerHandler for McpHandler {
                 })?;
 
                 let registry = self.resource_registry.read().await;
-                let resource = registry.get_resource(uri).ok_or_else(|| {
-                    Error::protocol(
-                        ErrorCode::MethodNotFound,
-                        format!("Resource not found: {uri}"),
-                    )
-                })?;
+                let resource = registry.get_resource(uri);
+                let template = match resource {
+                    Some(_) => None,
+                    None => registry.match_template(uri),
+                };
                 drop(registry);
 
-                let result = tokio::time::timeout(
-                    std::time::Duration::from_secs(30),
-                    resource.read_multi(&self.clients),
-                )
-                .await
-                .map_err(|_| {
-                    Error::protocol(
-                        ErrorCode::InternalError,
-                        "Resource read timed out".to_string(),
-                    )
-                })?
-                .map_err(|e| Error::protocol(ErrorCode::InternalError, e.to_string()))?;
+                let (result, mime_type) = if let Some(resource) = resource {
+                    let result = read_with_timeout(resource.read_multi(&self.clients)).await?;
+                    (result, resource.mime_type())
+                } else if let Some((template, vars)) = template {
+                    let result = read_with_timeout(template.read(&self.clients, &vars)).await?;
+                    (result, template.mime_type())
+                } else {
+                    return Err(Error::protocol(
+                        ErrorCode::MethodNotFound,
+                        format!("Resource not found: {uri}"),
+                    ));
+                };
 
                 // MCP spec for resources/read returns "contents": [{ "uri": "...", "mimeType": "...", "text": "..." }]
                 let mut content_item = serde_json::json!({
                     "uri": uri,
                 });
 
-                if let Some(mime) = resource.mime_type() {
+                if let Some(mime) = mime_type {
                     content_item["mimeType"] = serde_json::json!(mime);
                 }
 
@@ -263,26 +281,7 @@ impl ServerHandler for McpHandler {
                 }
 
                 if registry.is_lazy_mode() {
-                    tool_infos.push(serde_json::json!({
-                        "name": "manage_tools",
-                        "description": "Manage available tools (enable/disable) to save tokens.",
-                        "inputSchema": {
-                            "type": "object",
-                            "properties": {
-                                "action": {
-                                    "type": "string",
-                                    "enum": ["list", "enable", "disable"],
-                                    "description": "The action to perform."
-                                },
-                                "tools": {
-                                    "type": "array",
-                                    "items": { "type": "string" },
-                                    "description": "List of tool names to enable or disable."
-                                }
-                            },
-                            "required": ["action"]
-                        }
-                    }));
+                    tool_infos.push(ToolRegistry::manage_tools_info());
                 }
 
                 Ok(serde_json::json!({ "tools": tool_infos }))
@@ -526,6 +525,32 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_handler_resource_templates() {
+        let registry = Arc::new(RwLock::new(ToolRegistry::new(&Config::default())));
+        let resource_registry = Arc::new(RwLock::new(ResourceRegistry::default()));
+        let prompt_registry = Arc::new(RwLock::new(PromptRegistry::default()));
+        let client = Arc::new(Aria2Client::new(Config::default()));
+        let handler = McpHandler::new(registry, resource_registry, prompt_registry, vec![client]);
+
+        let result = handler
+            .handle_method("resources/templates/list", None)
+            .await
+            .unwrap();
+        let templates = result["resourceTemplates"].as_array().unwrap();
+        assert!(templates
+            .iter()
+            .any(|t| t["uriTemplate"] == "aria2://instances/{instance}/downloads/{gid}/files"));
+
+        // A templated URI for an unknown instance reaches the template, not "not found"
+        let params = serde_json::json!({ "uri": "aria2://instances/nas/downloads/abc" });
+        let err = handler
+            .handle_method("resources/read", Some(params))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Unknown instance: nas"));
+    }
+
     #[tokio::test]
     async fn test_handler_manage_tools_errors() {
         let config = Config {
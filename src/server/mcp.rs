@@ -1,4 +1,4 @@
-use crate::aria2::notifications::Aria2Notification;
+use crate::aria2::notifications::{Aria2Event, Aria2Notification, MCP_EVENT_METHOD};
 use crate::aria2::Aria2Client;
 use crate::prompts::PromptRegistry;
 use crate::resources::ResourceRegistry;
@@ -6,9 +6,10 @@ use crate::tools::ToolRegistry;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::mpsc::Receiver;
 use tokio::sync::RwLock;
 use tokio::time::{sleep, Duration};
@@ -80,11 +81,87 @@ impl JsonRpcError {
     }
 }
 
+/// Which aria2 events a client wants to receive. `None` means no
+/// restriction on that dimension.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventFilter {
+    pub events: Option<HashSet<Aria2Event>>,
+    pub instances: Option<HashSet<String>>,
+}
+
+impl EventFilter {
+    /// Parses the params of an `aria2/subscribe` request, e.g.
+    /// `{"events": ["download_complete", "download_error"]}`.
+    pub fn from_params(params: &Value) -> std::result::Result<Self, String> {
+        let events = match params.get("events").filter(|v| !v.is_null()) {
+            Some(value) => {
+                let names = value
+                    .as_array()
+                    .ok_or_else(|| "events must be an array of event names".to_string())?;
+                let mut events = HashSet::new();
+                for name in names {
+                    let name = name.as_str().unwrap_or_default();
+                    let event = Aria2Event::from_name(name)
+                        .ok_or_else(|| format!("Unknown event: {name}"))?;
+                    events.insert(event);
+                }
+                Some(events)
+            }
+            None => None,
+        };
+
+        let instances = match params.get("instances").filter(|v| !v.is_null()) {
+            Some(value) => Some(
+                serde_json::from_value::<HashSet<String>>(value.clone())
+                    .map_err(|_| "instances must be an array of instance names".to_string())?,
+            ),
+            None => None,
+        };
+
+        Ok(Self { events, instances })
+    }
+
+    #[must_use]
+    pub fn allows(&self, notification: &Aria2Notification) -> bool {
+        let event_ok = self
+            .events
+            .as_ref()
+            .is_none_or(|events| events.contains(&notification.method));
+        let instance_ok = self.instances.as_ref().is_none_or(|instances| {
+            notification
+                .instance
+                .as_ref()
+                .is_some_and(|name| instances.contains(name))
+        });
+        event_ok && instance_ok
+    }
+
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        let events = self.events.as_ref().map(|events| {
+            Aria2Event::ALL
+                .into_iter()
+                .filter(|e| events.contains(e))
+                .map(|e| e.name())
+                .collect::<Vec<_>>()
+        });
+        let instances = self.instances.as_ref().map(|instances| {
+            let mut names: Vec<_> = instances.iter().cloned().collect();
+            names.sort();
+            names
+        });
+        json!({ "events": events, "instances": instances })
+    }
+}
+
 pub struct McpState {
     pub running: bool,
     pub initialized: bool,
     pub lazy_mode: bool,
     pub notifications: VecDeque<JsonRpcRequest>,
+    pub event_filter: EventFilter,
+    /// URIs the client subscribed to with `resources/subscribe`
+    pub resource_subscriptions: HashSet<String>,
 }
 
 impl McpState {
@@ -95,6 +172,8 @@ impl McpState {
             initialized: false,
             lazy_mode,
             notifications: VecDeque::new(),
+            event_filter: EventFilter::default(),
+            resource_subscriptions: HashSet::new(),
         }
     }
 
@@ -110,6 +189,72 @@ impl McpState {
     pub fn pop_notification(&mut self) -> Option<JsonRpcRequest> {
         self.notifications.pop_front()
     }
+
+    /// Queues `notifications/resources/updated` for `uri` if the client is
+    /// subscribed to it and no update for it is already pending.
+    pub fn queue_resource_updated(&mut self, uri: &str) {
+        if !self.resource_subscriptions.contains(uri) {
+            return;
+        }
+        let params = json!({ "uri": uri });
+        let pending = self
+            .notifications
+            .iter()
+            .any(|n| n.method == RESOURCE_UPDATED_METHOD && n.params.as_ref() == Some(&params));
+        if !pending {
+            self.queue_notification(RESOURCE_UPDATED_METHOD, params);
+        }
+    }
+}
+
+pub const RESOURCE_UPDATED_METHOD: &str = "notifications/resources/updated";
+pub const TOOLS_LIST_CHANGED_METHOD: &str = "notifications/tools/list_changed";
+
+/// Builds the params of a `notifications/aria2/event` notification, looking
+/// up the download on the instance that emitted the event.
+pub async fn event_params(notification: &Aria2Notification, clients: &[Arc<Aria2Client>]) -> Value {
+    let client = notification
+        .instance
+        .as_ref()
+        .and_then(|name| clients.iter().find(|c| &c.name == name))
+        .or_else(|| clients.first());
+    let snapshot = match client {
+        Some(client) => notification.snapshot(client).await,
+        None => None,
+    };
+    notification.event_params(snapshot.as_ref())
+}
+
+/// Queues every aria2 event allowed by the client's filter, in the order the
+/// events arrive, along with updates for the subscribed resources the event
+/// changes. Runs until the channel closes.
+pub async fn forward_events(
+    state: Arc<RwLock<McpState>>,
+    resource_registry: Arc<RwLock<ResourceRegistry>>,
+    clients: Vec<Arc<Aria2Client>>,
+    mut notification_rx: Receiver<Aria2Notification>,
+) {
+    while let Some(notification) = notification_rx.recv().await {
+        let updated = resource_registry
+            .read()
+            .await
+            .updated_by(notification.method);
+        {
+            let mut state_guard = state.write().await;
+            for uri in &updated {
+                state_guard.queue_resource_updated(uri);
+            }
+        }
+
+        if !state.read().await.event_filter.allows(&notification) {
+            continue;
+        }
+        let params = event_params(&notification, &clients).await;
+        state
+            .write()
+            .await
+            .queue_notification(MCP_EVENT_METHOD, params);
+    }
 }
 
 pub async fn run_stdio(
@@ -118,11 +263,18 @@ pub async fn run_stdio(
     resource_registry: Arc<RwLock<ResourceRegistry>>,
     prompt_registry: Arc<RwLock<PromptRegistry>>,
     clients: Vec<Arc<Aria2Client>>,
-    mut notification_rx: Receiver<Aria2Notification>,
+    notification_rx: Receiver<Aria2Notification>,
 ) -> Result<()> {
     let mut reader = BufReader::new(tokio::io::stdin()).lines();
     let mut stdout = tokio::io::stdout();
     let (response_tx, mut response_rx) = tokio::sync::mpsc::channel::<JsonRpcResponse>(100);
+    let forwarder = tokio::spawn(forward_events(
+        Arc::clone(&state),
+        Arc::clone(&resource_registry),
+        clients.clone(),
+        notification_rx,
+    ));
+    let mut tool_changes = registry.read().await.subscribe_changes();
 
     loop {
         {
@@ -191,12 +343,11 @@ pub async fn run_stdio(
                 stdout.write_all(b"\n").await?;
                 stdout.flush().await?;
             }
-            Some(notif) = notification_rx.recv() => {
-                let mut state_guard = state.write().await;
-                state_guard.queue_notification("notifications/message", json!({
-                    "level": "info",
-                    "data": format!("Aria2 Event: {:?}", notif)
-                }));
+            Ok(()) | Err(RecvError::Lagged(_)) = tool_changes.recv() => {
+                state
+                    .write()
+                    .await
+                    .queue_notification(TOOLS_LIST_CHANGED_METHOD, json!({}));
             }
             () = sleep(Duration::from_millis(100)) => {
                 flush_notifications_async(Arc::clone(&state), &mut stdout).await?;
@@ -204,6 +355,7 @@ pub async fn run_stdio(
         }
     }
 
+    forwarder.abort();
     Ok(())
 }
 
@@ -221,6 +373,61 @@ pub async fn flush_notifications_async<W: AsyncWriteExt + Unpin>(
     Ok(())
 }
 
+/// Wraps a resource's content in a `resources/read` result.
+fn resource_contents(uri: &str, mime_type: Option<String>, res: &Value) -> Value {
+    let mut content_item = json!({
+        "uri": uri,
+    });
+    if let Some(mime) = mime_type {
+        content_item["mimeType"] = json!(mime);
+    }
+    if let Some(s) = res.as_str() {
+        content_item["text"] = json!(s);
+    } else {
+        content_item["text"] = json!(res.to_string());
+    }
+    json!({ "contents": [content_item] })
+}
+
+/// Runs the lazy-mode `manage_tools` meta-tool, returning its text output.
+async fn manage_tools(
+    registry: &RwLock<ToolRegistry>,
+    args: &Value,
+) -> std::result::Result<String, JsonRpcError> {
+    let action = args["action"].as_str().unwrap_or_default();
+    if action == "list" {
+        let tools = registry.read().await.list_available_tools();
+        return Ok(serde_json::to_string_pretty(&tools).unwrap_or_default());
+    }
+    if action != "enable" && action != "disable" {
+        return Err(JsonRpcError::new(
+            -32602,
+            &format!("Invalid action: {action}"),
+        ));
+    }
+
+    let names = args["tools"].as_array().ok_or_else(|| {
+        JsonRpcError::new(-32602, &format!("Missing 'tools' in manage_tools {action}"))
+    })?;
+    let mut reg = registry.write().await;
+    let mut count = 0;
+    for name in names.iter().filter_map(Value::as_str) {
+        let changed = if action == "enable" {
+            reg.enable_tool(name)
+        } else {
+            reg.disable_tool(name)
+        };
+        if changed {
+            count += 1;
+        }
+    }
+    Ok(if action == "enable" {
+        format!("Enabled {count} tools.")
+    } else {
+        format!("Disabled {count} tools.")
+    })
+}
+
 pub async fn handle_request(
     req: JsonRpcRequest,
     state: Arc<RwLock<McpState>>,
@@ -240,9 +447,14 @@ pub async fn handle_request(
             Ok(json!({
                 "protocolVersion": "2024-11-05",
                 "capabilities": {
-                    "tools": {},
-                    "resources": {},
-                    "prompts": {}
+                    "tools": { "listChanged": true },
+                    "resources": { "subscribe": true },
+                    "prompts": {},
+                    "experimental": {
+                        "aria2/events": {
+                            "events": Aria2Event::ALL.map(|e| e.name())
+                        }
+                    }
                 },
                 "serverInfo": {
                     "name": "aria2-mcp-rs",
@@ -261,12 +473,25 @@ pub async fn handle_request(
                     "inputSchema": tool.schema().unwrap_or(json!({"type": "object"}))
                 }));
             }
+            if reg.is_lazy_mode() {
+                tool_info.push(ToolRegistry::manage_tools_info());
+            }
             Ok(json!({ "tools": tool_info }))
         }
         "tools/call" => {
             let params = req.params.unwrap_or(json!({}));
             let name = params["name"].as_str().unwrap_or_default();
             let args = params["arguments"].clone();
+            let lazy_mode = registry.read().await.is_lazy_mode();
+            if name == "manage_tools" && lazy_mode {
+                return Ok(Some(match manage_tools(&registry, &args).await {
+                    Ok(text) => JsonRpcResponse::success(
+                        id,
+                        json!({ "content": [{ "type": "text", "text": text }] }),
+                    ),
+                    Err(e) => JsonRpcResponse::error(id, e),
+                }));
+            }
             let reg = registry.read().await;
             if let Some(tool) = reg.get_tool(name) {
                 match tool.run_multi(&clients, args).await {
@@ -287,39 +512,55 @@ pub async fn handle_request(
             let resources = reg.list_resources();
             Ok(json!({ "resources": resources }))
         }
+        "resources/templates/list" => {
+            let reg = resource_registry.read().await;
+            Ok(json!({ "resourceTemplates": reg.list_templates() }))
+        }
         "resources/read" => {
             let params = req.params.unwrap_or(json!({}));
             let uri = params["uri"].as_str().unwrap_or_default();
             let reg = resource_registry.read().await;
             if let Some(resource) = reg.get_resource(uri) {
-                if let Some(client) = clients.first() {
-                    match resource.read(client).await {
-                        Ok(res) => {
-                            let mut content_item = json!({
-                                "uri": uri,
-                            });
-                            if let Some(mime) = resource.mime_type() {
-                                content_item["mimeType"] = json!(mime);
-                            }
-                            if let Some(s) = res.as_str() {
-                                content_item["text"] = json!(s);
-                            } else {
-                                content_item["text"] = json!(res.to_string());
-                            }
-                            Ok(json!({ "contents": [content_item] }))
-                        }
-                        Err(e) => Err(JsonRpcError::new(-32603, &format!("Resource error: {e}"))),
-                    }
-                } else {
+                drop(reg);
+                if clients.is_empty() {
                     Err(JsonRpcError::new(
                         -32603,
                         "No clients available for resource read",
                     ))
+                } else {
+                    match resource.read_multi(&clients).await {
+                        Ok(res) => Ok(resource_contents(uri, resource.mime_type(), &res)),
+                        Err(e) => Err(JsonRpcError::new(-32603, &format!("Resource error: {e}"))),
+                    }
+                }
+            } else if let Some((template, vars)) = reg.match_template(uri) {
+                drop(reg);
+                match template.read(&clients, &vars).await {
+                    Ok(res) => Ok(resource_contents(uri, template.mime_type(), &res)),
+                    Err(e) => Err(JsonRpcError::new(-32603, &format!("Resource error: {e}"))),
                 }
             } else {
                 Err(JsonRpcError::new(-32601, "Resource not found"))
             }
         }
+        "resources/subscribe" | "resources/unsubscribe" => {
+            let params = req.params.unwrap_or(json!({}));
+            let uri = params["uri"].as_str().unwrap_or_default();
+            if resource_registry.read().await.get_resource(uri).is_none() {
+                Err(JsonRpcError::new(
+                    -32602,
+                    &format!("Resource not found: {uri}"),
+                ))
+            } else {
+                let mut state_guard = state.write().await;
+                if req.method == "resources/subscribe" {
+                    state_guard.resource_subscriptions.insert(uri.to_string());
+                } else {
+                    state_guard.resource_subscriptions.remove(uri);
+                }
+                Ok(json!({}))
+            }
+        }
         "prompts/list" => {
             let reg = prompt_registry.read().await;
             let prompts = reg.list_prompts();
@@ -339,6 +580,17 @@ pub async fn handle_request(
                 Err(JsonRpcError::new(-32601, "Prompt not found"))
             }
         }
+        "aria2/subscribe" => {
+            let params = req.params.unwrap_or(json!({}));
+            match EventFilter::from_params(&params) {
+                Ok(filter) => {
+                    let result = json!({ "filter": filter.to_json() });
+                    state.write().await.event_filter = filter;
+                    Ok(result)
+                }
+                Err(e) => Err(JsonRpcError::new(-32602, &e)),
+            }
+        }
         _ => Err(JsonRpcError::new(-32601, "Method not found")),
     };
 
@@ -1,19 +1,24 @@
 pub mod handler;
 pub mod mcp;
 pub mod sse;
+pub mod streamable_http;
 
 use anyhow::Result;
 use chrono::{Datelike, Local, Timelike};
+use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::time::{self, Duration};
 
-use crate::aria2::notifications::Aria2Notification;
-use crate::aria2::recovery::RecoveryManager;
-use crate::aria2::Aria2Client;
-use crate::config::{Config, TransportType};
+use crate::aria2::notifications::{Aria2Event, Aria2Notification};
+use crate::aria2::recovery::{RecoveryAction, RecoveryManager, RecoveryStrategy};
+use crate::aria2::{Aria2Client, DownloadStatus};
+use crate::bandwidth;
+use crate::config::{Config, PurgeConfig, TransportType};
+use crate::metrics::InstanceCounters;
 use crate::prompts::PromptRegistry;
 use crate::resources::ResourceRegistry;
+use crate::state::PurgeRecord;
 use crate::tools::ToolRegistry;
 
 pub struct McpServer {
@@ -53,6 +58,13 @@ impl McpServer {
         let (notification_tx, notification_rx) =
             tokio::sync::mpsc::channel::<Aria2Notification>(100);
 
+        // Before any task can save, which would drop what was not restored
+        for client in &self.clients {
+            if let Err(e) = client.restore_state().await {
+                log::error!("Failed to restore state for {}: {e}", client.name);
+            }
+        }
+
         for client in &self.clients {
             let client_clone = Arc::clone(client);
             let tx_clone = notification_tx.clone();
@@ -74,9 +86,12 @@ impl McpServer {
             });
 
             let client_clone = Arc::clone(client);
+            let instances = self.clients.clone();
             let recovery_manager_clone = Arc::clone(&self.recovery_manager);
             tokio::spawn(async move {
-                if let Err(e) = start_recovery_task(client_clone, recovery_manager_clone).await {
+                if let Err(e) =
+                    start_recovery_task(client_clone, instances, recovery_manager_clone).await
+                {
                     log::error!("Recovery task error: {e}");
                 }
             });
@@ -94,6 +109,13 @@ impl McpServer {
                     log::error!("Purge task error: {e}");
                 }
             });
+
+            let client_clone = Arc::clone(client);
+            tokio::spawn(async move {
+                if let Err(e) = start_stats_sampler(client_clone).await {
+                    log::error!("Stats sampler error: {e}");
+                }
+            });
         }
 
         match self.config.transport {
@@ -142,60 +164,177 @@ pub async fn start_purge_task(client: Arc<Aria2Client>) -> Result<()> {
         let config = client.config();
         let purge_config = {
             let config_guard = config.read().await;
-            config_guard.purge_config.clone()
+            config_guard.purge_config.for_instance(&client.name)
         };
 
-        if !purge_config.enabled {
+        if purge_config.enabled {
+            // Adjust interval if needed
+            if interval.period().as_secs() != purge_config.interval_secs {
+                interval = time::interval(Duration::from_secs(purge_config.interval_secs));
+                interval.tick().await;
+            }
+
+            if let Err(e) = purge_stopped(&client, &purge_config).await {
+                log::error!("Failed to purge stopped downloads: {e}");
+            }
+        }
+
+        // Persist completion times recorded from events since the last tick
+        if client.completions().take_dirty() {
+            if let Err(e) = client.save_state().await {
+                log::error!("Failed to save completion times: {e}");
+            }
+        }
+    }
+}
+
+/// Removes the stopped downloads that `policy` allows to be purged, adds
+/// them to the purge history and returns them.
+pub async fn purge_stopped(client: &Aria2Client, policy: &PurgeConfig) -> Result<Vec<PurgeRecord>> {
+    const PAGE: i32 = 1000;
+
+    let stopped = client.stopped_downloads(0, PAGE, None).await?;
+    let completions = client.completions();
+    if stopped.len() < PAGE as usize {
+        // Downloads that are no longer listed were removed some other way
+        let listed: HashSet<&str> = stopped.iter().map(|d| d.gid.as_str()).collect();
+        completions.retain(|gid| listed.contains(gid));
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let mut purged = Vec::new();
+    for download in &stopped {
+        if download.gid.is_empty() || policy.excluded_gids.contains(&download.gid) {
             continue;
         }
 
-        // Adjust interval if needed
-        if interval.period().as_secs() != purge_config.interval_secs {
-            interval = time::interval(Duration::from_secs(purge_config.interval_secs));
-            interval.tick().await;
+        // Downloads that stopped while no event was received are timed from
+        // when they are first seen here
+        let completed_at = completions.stopped_at_or(&download.gid, now);
+        if !is_purgeable(download, policy, completed_at, now) {
+            continue;
         }
 
-        let stopped = match client.tell_stopped(0, 1000, None).await {
-            Ok(s) => s,
-            Err(e) => {
-                log::error!("Failed to fetch stopped downloads for purge: {e}");
-                continue;
+        log::info!(
+            "Purging download {} (instance {})...",
+            download.gid,
+            client.name
+        );
+        match client.remove_download_result(&download.gid).await {
+            Ok(()) => {
+                completions.forget(&download.gid);
+                purged.push(PurgeRecord {
+                    gid: download.gid.clone(),
+                    instance: client.name.clone(),
+                    name: download.name(),
+                    status: download
+                        .status
+                        .map(|s| s.as_str().to_string())
+                        .unwrap_or_default(),
+                    completed_at,
+                    purged_at: now,
+                });
             }
+            Err(e) => log::error!("Failed to purge download {}: {e}", download.gid),
+        }
+    }
+
+    if !purged.is_empty() {
+        InstanceCounters::add(&client.counters.purges, purged.len() as u64);
+        client
+            .record_purges(purged.clone(), policy.history_limit)
+            .await?;
+    }
+    Ok(purged)
+}
+
+/// Whether a download that stopped at `completed_at` is old enough for
+/// `policy` to purge it at `now`.
+#[must_use]
+pub fn is_purgeable(
+    download: &DownloadStatus,
+    policy: &PurgeConfig,
+    completed_at: i64,
+    now: i64,
+) -> bool {
+    let Some(min_age) = download.status.and_then(|s| policy.min_age_for(s.as_str())) else {
+        return false;
+    };
+    now.saturating_sub(completed_at) >= i64::try_from(min_age).unwrap_or(i64::MAX)
+}
+
+/// Fields of each download that the stats sampler needs.
+const STATS_KEYS: &[&str] = &[
+    "gid",
+    "status",
+    "completedLength",
+    "uploadLength",
+    "downloadSpeed",
+    "uploadSpeed",
+    "infoHash",
+    "files",
+];
+
+pub async fn start_stats_sampler(client: Arc<Aria2Client>) -> Result<()> {
+    let mut interval = time::interval(Duration::from_secs(60));
+
+    loop {
+        interval.tick().await;
+
+        let stats_config = {
+            let config = client.config();
+            let config_guard = config.read().await;
+            config_guard.stats_config.clone()
         };
 
-        if let Some(items) = stopped.as_array() {
-            for item in items {
-                if let Some(gid) = item.get("gid").and_then(|v| v.as_str()) {
-                    if purge_config.excluded_gids.contains(gid) {
-                        continue;
-                    }
+        if stats_config.enabled {
+            // Adjust interval if needed
+            let interval_secs = stats_config.interval_secs.max(1);
+            if interval.period().as_secs() != interval_secs {
+                interval = time::interval(Duration::from_secs(interval_secs));
+                interval.tick().await;
+            }
 
-                    if is_purgeable(item) {
-                        log::info!("Purging download {} (instance {})...", gid, client.name);
-                        if let Err(e) = client.remove_download_result(gid).await {
-                            log::error!("Failed to purge download {gid}: {e}");
-                        }
-                    }
-                }
+            if let Err(e) = sample_stats(&client, stats_config.retention_secs).await {
+                log::debug!("Failed to sample stats for {}: {e}", client.name);
             }
         }
     }
 }
 
-#[must_use]
-pub fn is_purgeable(item: &serde_json::Value) -> bool {
-    if let Some(status) = item.get("status").and_then(|v| v.as_str()) {
-        // For now, we purge anything that is complete or error.
-        // We could add more complex logic here later if aria2 provides timestamps.
-        return status == "complete" || status == "error";
-    }
-    false
+/// Records the current throughput of `client` in its stats recorder.
+pub async fn sample_stats(client: &Aria2Client, retention_secs: u64) -> Result<()> {
+    let keys = STATS_KEYS.iter().map(ToString::to_string).collect();
+    let snapshot = client.queue_snapshot(Some(keys)).await?;
+    client
+        .stats
+        .record(&snapshot, chrono::Utc::now().timestamp(), retention_secs);
+    Ok(())
 }
 
 async fn start_recovery_task(
     client: Arc<Aria2Client>,
+    instances: Vec<Arc<Aria2Client>>,
     recovery_manager: Arc<RecoveryManager>,
 ) -> Result<()> {
+    // Retries that were pending when the server stopped run when they were due
+    for (gid, state) in client.retries.all() {
+        if let Some(due_at) = state.due_at {
+            log::info!(
+                "Rescheduling retry of download {gid} (instance {})",
+                client.name
+            );
+            schedule_retry(
+                &client,
+                &instances,
+                &recovery_manager,
+                &gid,
+                state.strategy.unwrap_or_default(),
+                due_at,
+            );
+        }
+    }
+
     let mut interval = time::interval(Duration::from_secs(30));
 
     loop {
@@ -212,38 +351,114 @@ async fn start_recovery_task(
 
         if let Some(items) = stopped.as_array() {
             for item in items {
-                if let Some(gid) = item.get("gid").and_then(|v| v.as_str()) {
-                    if let Some(backoff) = recovery_manager
-                        .analyze_and_get_retry_backoff(gid, item)
-                        .await
-                    {
+                let Some(gid) = item.get("gid").and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                // Already retried; the failed download stays in the stopped list
+                if client.lineage.successor(gid).is_some() {
+                    continue;
+                }
+                match recovery_manager.plan_recovery(&client, gid, item).await {
+                    Some(RecoveryAction::Retry {
+                        strategy,
+                        backoff_secs,
+                        due_at,
+                    }) => {
                         log::info!(
-                            "Recovery needed for download {} (instance {}). Retrying in {} seconds.",
+                            "Recovery needed for download {} (instance {}). Retrying ({:?}) in {} seconds.",
                             gid,
                             client.name,
-                            backoff
+                            strategy,
+                            backoff_secs
                         );
-
-                        let client_retry = Arc::clone(&client);
-                        let recovery_manager_retry = Arc::clone(&recovery_manager);
-                        let gid_retry = gid.to_string();
-
-                        tokio::spawn(async move {
-                            tokio::time::sleep(Duration::from_secs(backoff)).await;
-                            if let Err(e) = recovery_manager_retry
-                                .perform_retry(&client_retry, &gid_retry)
-                                .await
-                            {
-                                log::error!("Retry failed for download {gid_retry}: {e}");
-                            }
-                        });
+                        schedule_retry(
+                            &client,
+                            &instances,
+                            &recovery_manager,
+                            gid,
+                            strategy,
+                            due_at,
+                        );
+                    }
+                    Some(RecoveryAction::GiveUp { reason }) => {
+                        log::warn!(
+                            "Giving up on download {gid} (instance {}): {reason}",
+                            client.name
+                        );
+                        client.emit_event(Aria2Event::RetryGivenUp, gid);
                     }
+                    None => {}
                 }
             }
         }
+
+        let now = chrono::Utc::now().timestamp();
+        if let Err(e) = recovery_manager.prune_state(&client, now).await {
+            log::error!(
+                "Failed to prune recovery state for instance {}: {e}",
+                client.name
+            );
+        }
+
+        if client.retries.take_dirty() | client.lineage.take_dirty() {
+            if let Err(e) = client.save_state().await {
+                log::error!("Failed to save recovery state: {e}");
+            }
+        }
+
+        if recovery_manager.tracker_injection_enabled() {
+            if let Err(e) = recovery_manager.inject_stalled_trackers(&client).await {
+                log::error!("Tracker injection failed for instance {}: {e}", client.name);
+            }
+        }
     }
 }
 
+/// Retries `gid` at `due_at`, unless the retry was cancelled or rescheduled
+/// in the meantime.
+fn schedule_retry(
+    client: &Arc<Aria2Client>,
+    instances: &[Arc<Aria2Client>],
+    recovery_manager: &Arc<RecoveryManager>,
+    gid: &str,
+    strategy: RecoveryStrategy,
+    due_at: i64,
+) {
+    let client = Arc::clone(client);
+    let target = if strategy == RecoveryStrategy::SwitchInstance {
+        next_instance(&client, instances)
+    } else {
+        Arc::clone(&client)
+    };
+    let recovery_manager = Arc::clone(recovery_manager);
+    let gid = gid.to_string();
+
+    tokio::spawn(async move {
+        let delay = due_at.saturating_sub(chrono::Utc::now().timestamp()).max(0);
+        tokio::time::sleep(Duration::from_secs(delay.unsigned_abs())).await;
+        if !client.retries.is_due(&gid, due_at) {
+            return;
+        }
+        match recovery_manager
+            .perform_retry_on(&client, &target, &gid)
+            .await
+        {
+            Ok(_) => InstanceCounters::add(&client.counters.recoveries, 1),
+            Err(e) => log::error!("Retry failed for download {gid}: {e}"),
+        }
+    });
+}
+
+/// The instance after `client` in the configured order, or `client` itself if
+/// it is the only one.
+fn next_instance(client: &Arc<Aria2Client>, instances: &[Arc<Aria2Client>]) -> Arc<Aria2Client> {
+    instances
+        .iter()
+        .position(|c| Arc::ptr_eq(c, client))
+        .and_then(|i| instances.get((i + 1) % instances.len()))
+        .map_or_else(|| Arc::clone(client), Arc::clone)
+}
+
 async fn check_port_available(host: &str, port: u16) -> bool {
     let addr_str = format!("{host}:{port}");
     tokio::net::TcpListener::bind(&addr_str).await.is_ok()
@@ -303,25 +518,47 @@ async fn start_scheduler(client: Arc<Aria2Client>) -> Result<()> {
             )
         };
 
-        let active_profile_name = get_active_profile(current_day, &current_time, &schedules);
+        // Profiles limited to other instances are not active on this one
+        let active_profile_name = get_active_profile(current_day, &current_time, &schedules)
+            .filter(|name| {
+                profiles
+                    .get(name)
+                    .is_some_and(|p| p.applies_to(&client.name))
+            });
+        let active_profile = active_profile_name
+            .as_ref()
+            .and_then(|name| profiles.get(name));
 
-        if let Some(profile_name) = active_profile_name {
-            if last_profile.as_ref() != Some(&profile_name) {
-                if let Some(profile) = profiles.get(&profile_name) {
+        if let Some(profile_name) = active_profile_name.as_ref() {
+            if last_profile.as_ref() != Some(profile_name) {
+                if let Some(profile) = active_profile {
                     log::info!("Activating bandwidth profile: {profile_name}");
-                    let options = serde_json::json!({
-                        "max-overall-download-limit": profile.max_download,
-                        "max-overall-upload-limit": profile.max_upload,
-                    });
-                    if let Err(e) = client.change_global_option(options).await {
+                    if let Err(e) = bandwidth::activate_globals(&client, profile).await {
                         log::error!("Failed to activate profile '{profile_name}': {e}");
                     } else {
-                        last_profile = Some(profile_name);
+                        last_profile = Some(profile_name.clone());
                     }
                 }
             }
-        } else if last_profile.is_some() {
-            last_profile = None;
+        } else {
+            // Also when the profile ended while the server was down
+            match bandwidth::restore_globals(&client).await {
+                Ok(()) => last_profile = None,
+                Err(e) => log::error!("Failed to restore global options on {}: {e}", client.name),
+            }
+        }
+
+        // Every tick, so downloads added since are limited too. Only the
+        // limits of the profile whose global options are in place apply.
+        let limits_profile = last_profile.as_ref().and_then(|name| profiles.get(name));
+        if let Err(e) = bandwidth::apply_download_limits(&client, limits_profile).await {
+            log::warn!("Failed to apply download limits on {}: {e}", client.name);
+        }
+
+        if client.bandwidth.take_dirty() {
+            if let Err(e) = client.save_state().await {
+                log::error!("Failed to save bandwidth overrides: {e}");
+            }
         }
     }
 }
@@ -331,6 +568,24 @@ mod tests {
     use super::*;
     use crate::config::BandwidthSchedule;
 
+    #[test]
+    fn test_next_instance() {
+        let clients: Vec<_> = (0..3)
+            .map(|_| Arc::new(Aria2Client::new(Config::default())))
+            .collect();
+        assert!(Arc::ptr_eq(
+            &next_instance(&clients[0], &clients),
+            &clients[1]
+        ));
+        assert!(Arc::ptr_eq(
+            &next_instance(&clients[2], &clients),
+            &clients[0]
+        ));
+
+        let alone = Arc::new(Aria2Client::new(Config::default()));
+        assert!(Arc::ptr_eq(&next_instance(&alone, &[]), &alone));
+    }
+
     #[test]
     fn test_get_active_profile() {
         let schedules = vec![
@@ -430,15 +685,187 @@ mod tests {
 
     #[test]
     fn test_is_purgeable() {
-        let item_complete = serde_json::json!({ "status": "complete" });
-        let item_error = serde_json::json!({ "status": "error" });
-        let item_active = serde_json::json!({ "status": "active" });
-        let item_removed = serde_json::json!({ "status": "removed" });
-
-        assert!(is_purgeable(&item_complete));
-        assert!(is_purgeable(&item_error));
-        assert!(!is_purgeable(&item_active));
-        assert!(!is_purgeable(&item_removed));
+        use crate::aria2::DownloadState;
+
+        let download = |status| DownloadStatus {
+            status: Some(status),
+            ..Default::default()
+        };
+        let policy = crate::config::PurgeConfig {
+            min_age_secs: 100,
+            ..Default::default()
+        };
+        let now = 1_000;
+
+        assert!(is_purgeable(
+            &download(DownloadState::Complete),
+            &policy,
+            900,
+            now
+        ));
+        assert!(is_purgeable(
+            &download(DownloadState::Error),
+            &policy,
+            0,
+            now
+        ));
+        assert!(!is_purgeable(
+            &download(DownloadState::Active),
+            &policy,
+            0,
+            now
+        ));
+        assert!(!is_purgeable(
+            &download(DownloadState::Removed),
+            &policy,
+            0,
+            now
+        ));
+        assert!(!is_purgeable(&DownloadStatus::default(), &policy, 0, now));
+
+        // Too recent
+        assert!(!is_purgeable(
+            &download(DownloadState::Complete),
+            &policy,
+            901,
+            now
+        ));
+
+        let policy = crate::config::PurgeConfig {
+            statuses: vec!["removed".to_string()],
+            status_min_age_secs: std::collections::HashMap::from([("removed".to_string(), 0)]),
+            ..policy
+        };
+        assert!(is_purgeable(
+            &download(DownloadState::Removed),
+            &policy,
+            now,
+            now
+        ));
+        assert!(!is_purgeable(
+            &download(DownloadState::Complete),
+            &policy,
+            0,
+            now
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_purge_stopped_honors_min_age() {
+        use wiremock::matchers::{body_string_contains, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_string_contains("aria2.tellStopped"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "1",
+                "result": [
+                    { "gid": "old", "status": "complete" },
+                    { "gid": "new", "status": "complete" },
+                    { "gid": "unseen", "status": "error" },
+                    { "gid": "kept", "status": "complete" }
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_string_contains("aria2.removeDownloadResult"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "1",
+                "result": "OK"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut client = Aria2Client::new(Config {
+            rpc_url: format!("{}/jsonrpc", mock_server.uri()),
+            ..Default::default()
+        });
+        client.state_manager = Arc::new(crate::state::StateManager::new(
+            dir.path().join("state.json"),
+        ));
+
+        let now = chrono::Utc::now().timestamp();
+        client
+            .completions()
+            .restore(&std::collections::HashMap::from([
+                ("old".to_string(), now - 7200),
+                ("new".to_string(), now - 60),
+                ("kept".to_string(), now - 7200),
+                ("gone".to_string(), now - 7200),
+            ]));
+        let policy = crate::config::PurgeConfig {
+            enabled: true,
+            min_age_secs: 3600,
+            excluded_gids: HashSet::from(["kept".to_string()]),
+            ..Default::default()
+        };
+
+        let purged = purge_stopped(&client, &policy).await.unwrap();
+        assert_eq!(purged.len(), 1);
+        assert_eq!(purged[0].gid, "old");
+        assert_eq!(purged[0].completed_at, now - 7200);
+
+        let completions = client.completions();
+        assert!(completions.stopped_at("old").is_none());
+        assert!(completions.stopped_at("gone").is_none());
+        // First seen now, so its age starts counting
+        assert!(completions.stopped_at("unseen").is_some());
+
+        let state = client.state_manager().load().await.unwrap();
+        assert_eq!(state.purge_history, purged);
+    }
+
+    #[tokio::test]
+    async fn test_sample_stats() {
+        use wiremock::matchers::{body_string_contains, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let client = Aria2Client::new(Config {
+            rpc_url: format!("{}/jsonrpc", mock_server.uri()),
+            ..Config::default()
+        });
+
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_string_contains("completedLength"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": [
+                    [[{
+                        "gid": "1",
+                        "status": "active",
+                        "completedLength": "2048",
+                        "downloadSpeed": "512",
+                        "files": [{ "uris": [{ "uri": "http://mirror.example.com/a.iso" }] }]
+                    }]],
+                    [[]],
+                    [[]],
+                    [{ "downloadSpeed": "512", "numActive": "1" }]
+                ]
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        sample_stats(&client, 3600).await.unwrap();
+        sample_stats(&client, 3600).await.unwrap();
+
+        let now = chrono::Utc::now().timestamp();
+        let summary = client.stats.summarize(now, 3600, 5);
+        assert_eq!(summary.samples, 2);
+        assert_eq!(summary.peak_download_speed, 512);
+        assert_eq!(summary.top_sources[0].source, "mirror.example.com");
+        assert_eq!(summary.top_sources[0].avg_download_speed, 512);
     }
 
     #[tokio::test]
@@ -454,6 +881,7 @@ mod tests {
                 name: "test".to_string(),
                 rpc_url,
                 rpc_secret: None,
+                rpc_transport: None,
             }],
             purge_config: crate::config::PurgeConfig {
                 enabled: true,
@@ -463,10 +891,14 @@ mod tests {
             ..Default::default()
         };
 
-        let client = Arc::new(Aria2Client::new_with_instance(
-            config.clone(),
-            config.instances[0].clone(),
+        let mut client =
+            Aria2Client::new_with_instance(config.clone(), config.instances[0].clone());
+        // Completion times are saved on every tick
+        let state_dir = tempfile::tempdir().unwrap();
+        client.state_manager = Arc::new(crate::state::StateManager::new(
+            state_dir.path().join("state.json"),
         ));
+        let client = Arc::new(client);
 
         // Mock tellStopped
         Mock::given(method("POST"))
@@ -516,6 +948,7 @@ mod tests {
                 name: "test".to_string(),
                 rpc_url,
                 rpc_secret: None,
+                rpc_transport: None,
             }],
             retry_config: crate::aria2::recovery::RetryConfig {
                 max_retries: 3,
@@ -558,7 +991,7 @@ mod tests {
         let task_client = Arc::clone(&client);
         let task_manager = Arc::clone(&recovery_manager);
         tokio::spawn(async move {
-            let _ = start_recovery_task(task_client, task_manager).await;
+            let _ = start_recovery_task(task_client, Vec::new(), task_manager).await;
         });
 
         tokio::time::sleep(Duration::from_millis(100)).await;
@@ -577,12 +1010,14 @@ mod tests {
                 name: "test".to_string(),
                 rpc_url,
                 rpc_secret: None,
+                rpc_transport: None,
             }],
             bandwidth_profiles: std::collections::HashMap::from([(
                 "night".to_string(),
                 crate::config::BandwidthProfile {
                     max_download: "1M".to_string(),
                     max_upload: "100K".to_string(),
+                    ..Default::default()
                 },
             )]),
             bandwidth_schedules: vec![crate::config::BandwidthSchedule {
@@ -618,6 +1053,134 @@ mod tests {
         tokio::time::sleep(Duration::from_millis(100)).await;
     }
 
+    #[tokio::test]
+    async fn test_start_scheduler_skips_profile_for_other_instances() {
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(wiremock::matchers::method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "1",
+                "result": "OK"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let config = Config {
+            rpc_url: mock_server.uri(),
+            bandwidth_profiles: std::collections::HashMap::from([(
+                "work".to_string(),
+                crate::config::BandwidthProfile {
+                    max_download: "1M".to_string(),
+                    max_upload: "100K".to_string(),
+                    instances: vec!["seedbox".to_string()],
+                    ..Default::default()
+                },
+            )]),
+            bandwidth_schedules: vec![crate::config::BandwidthSchedule {
+                day: "daily".to_string(),
+                start_time: "00:00".to_string(),
+                end_time: "23:59".to_string(),
+                profile_name: "work".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        let client = Arc::new(Aria2Client::new(config));
+        let task_client = Arc::clone(&client);
+        let handle = tokio::spawn(async move {
+            let _ = start_scheduler(task_client).await;
+        });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        handle.abort();
+        assert!(mock_server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_start_recovery_task_resumes_pending_retries() {
+        use crate::aria2::recovery::{RetryState, SavedRecovery};
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        for (rpc_method, result) in [
+            ("aria2.tellStopped", serde_json::json!([])),
+            (
+                "aria2.tellStatus",
+                serde_json::json!({
+                    "gid": "pending",
+                    "status": "error",
+                    "files": [{ "index": "1", "uris": [{ "uri": "http://example.com/a.iso" }] }]
+                }),
+            ),
+            ("aria2.getOption", serde_json::json!({})),
+            ("aria2.removeDownloadResult", serde_json::json!("OK")),
+        ] {
+            Mock::given(method("POST"))
+                .and(body_partial_json(
+                    serde_json::json!({ "method": rpc_method }),
+                ))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": "1",
+                    "result": result
+                })))
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(method("POST"))
+            .and(body_partial_json(
+                serde_json::json!({ "method": "aria2.addUri" }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "1",
+                "result": "resumed"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let state_manager = crate::state::StateManager::new(dir.path().join("state.json"));
+        let mut state_data = crate::state::StateData::default();
+        state_data.recovery.insert(
+            "default".to_string(),
+            SavedRecovery {
+                retries: std::collections::HashMap::from([(
+                    "pending".to_string(),
+                    RetryState {
+                        attempts: 2,
+                        due_at: Some(chrono::Utc::now().timestamp() - 10),
+                        ..Default::default()
+                    },
+                )]),
+                lineage: Vec::new(),
+            },
+        );
+        state_manager.save(&state_data).await.unwrap();
+
+        let mut client = Aria2Client::new(Config::new(mock_server.uri(), None));
+        client.state_manager = Arc::new(state_manager);
+        client.restore_state().await.unwrap();
+        let client = Arc::new(client);
+        let recovery_manager = Arc::new(RecoveryManager::new(Default::default()));
+
+        let task_client = Arc::clone(&client);
+        tokio::spawn(async move {
+            let _ = start_recovery_task(task_client, Vec::new(), recovery_manager).await;
+        });
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(
+            client.lineage.successor("pending").as_deref(),
+            Some("resumed")
+        );
+        assert_eq!(client.retries.get("resumed").unwrap().attempts, 2);
+    }
+
     #[tokio::test]
     async fn test_start_recovery_task_empty_mock() {
         use wiremock::matchers::{method, path};
@@ -631,6 +1194,7 @@ mod tests {
                 name: "test".to_string(),
                 rpc_url,
                 rpc_secret: None,
+                rpc_transport: None,
             }],
             ..Default::default()
         };
@@ -655,7 +1219,7 @@ mod tests {
         let task_client = Arc::clone(&client);
         let task_manager = Arc::clone(&recovery_manager);
         tokio::spawn(async move {
-            let _ = start_recovery_task(task_client, task_manager).await;
+            let _ = start_recovery_task(task_client, Vec::new(), task_manager).await;
         });
 
         tokio::time::sleep(Duration::from_millis(100)).await;
@@ -672,6 +1236,7 @@ mod tests {
                 name: "test".to_string(),
                 rpc_url: format!("{}/jsonrpc", mock_server.uri()),
                 rpc_secret: None,
+                rpc_transport: None,
             }],
             bandwidth_schedules: vec![crate::config::BandwidthSchedule {
                 day: "daily".to_string(),
@@ -708,6 +1273,7 @@ mod tests {
                 name: "test".to_string(),
                 rpc_url,
                 rpc_secret: None,
+                rpc_transport: None,
             }],
             purge_config: crate::config::PurgeConfig {
                 enabled: true,
@@ -770,12 +1336,14 @@ mod tests {
                 name: "test".to_string(),
                 rpc_url,
                 rpc_secret: None,
+                rpc_transport: None,
             }],
             bandwidth_profiles: std::collections::HashMap::from([(
                 "night".to_string(),
                 crate::config::BandwidthProfile {
                     max_download: "1M".to_string(),
                     max_upload: "100K".to_string(),
+                    ..Default::default()
                 },
             )]),
             bandwidth_schedules: vec![crate::config::BandwidthSchedule {
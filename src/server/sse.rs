@@ -8,12 +8,16 @@ use axum::{
 };
 use std::sync::Arc;
 use tokio::net::TcpListener;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::RwLock;
 
+use super::mcp;
+use super::streamable_http::{self, StreamableHttp};
 use crate::aria2::notifications::Aria2Notification;
 use crate::aria2::Aria2Client;
 use crate::prompts::PromptRegistry;
 use crate::resources::ResourceRegistry;
+use crate::tools::list_instances::InstanceStatus;
 use crate::tools::ToolRegistry;
 
 #[allow(clippy::too_many_arguments)]
@@ -59,21 +63,55 @@ pub async fn run_server_with_shutdown<F>(
 where
     F: std::future::Future<Output = ()> + Send + 'static,
 {
-    // For now, we'll just drain the notifications or ignore them in SSE
-    // until a proper SSE endpoint is implemented.
+    let streamable = Arc::new(StreamableHttp::new(
+        Arc::clone(&registry),
+        Arc::clone(&resource_registry),
+        Arc::clone(&prompt_registry),
+        clients.clone(),
+    ));
+
+    // Push aria2 events to every session whose event filter allows them
+    let streamable_clone = Arc::clone(&streamable);
     tokio::spawn(async move {
-        while notification_rx.recv().await.is_some() {
-            // Ignored for now in SSE
+        while let Some(notification) = notification_rx.recv().await {
+            streamable_clone.publish_event(&notification).await;
         }
     });
 
+    // Drop sessions whose clients went away without sending DELETE
+    let streamable_clone = Arc::clone(&streamable);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            streamable_clone.evict_idle().await;
+        }
+    });
+
+    // Tell every session when lazy mode enables or disables tools
+    let mut tool_changes = registry.read().await.subscribe_changes();
+    let streamable_clone = Arc::clone(&streamable);
+    tokio::spawn(async move {
+        while let Ok(()) | Err(RecvError::Lagged(_)) = tool_changes.recv().await {
+            streamable_clone
+                .broadcast(&serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "method": mcp::TOOLS_LIST_CHANGED_METHOD
+                }))
+                .await;
+        }
+    });
+
+    let clients_for_health = clients.clone();
     let mut app = Router::new()
         .route("/tools", get(list_tools))
         .route("/tools/execute", post(execute_tool))
+        .route("/metrics", get(metrics))
         .layer(Extension(registry))
         .layer(Extension(resource_registry))
         .layer(Extension(prompt_registry))
-        .layer(Extension(clients));
+        .layer(Extension(clients))
+        .merge(streamable_http::routes(streamable));
 
     if let Some(token) = http_auth_token {
         app = app.layer(middleware::from_fn(move |req, next| {
@@ -81,6 +119,10 @@ where
         }));
     }
 
+    // Probes from the orchestrator carry no token, so these are added after
+    // the auth layer
+    let app = app.merge(health_routes(clients_for_health));
+
     let addr_str = format!("{http_host}:{http_port}");
     let listener = TcpListener::bind(&addr_str).await?;
     let addr = listener.local_addr()?;
@@ -94,6 +136,42 @@ where
     Ok(())
 }
 
+/// Liveness and readiness probes, served without authentication.
+fn health_routes(clients: Vec<Arc<Aria2Client>>) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .layer(Extension(clients))
+}
+
+async fn healthz() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+/// Ready when every instance answers `getVersion`. The WebSocket state is
+/// reported but does not affect readiness, as RPC over HTTP works without it.
+async fn readyz(
+    Extension(clients): Extension<Vec<Arc<Aria2Client>>>,
+) -> (axum::http::StatusCode, Json<serde_json::Value>) {
+    let instances =
+        futures_util::future::join_all(clients.iter().map(|client| InstanceStatus::probe(client)))
+            .await;
+    let ready = instances.iter().all(|instance| instance.reachable);
+
+    let status = if ready {
+        axum::http::StatusCode::OK
+    } else {
+        axum::http::StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(serde_json::json!({
+            "status": if ready { "ready" } else { "not_ready" },
+            "instances": instances
+        })),
+    )
+}
+
 async fn auth_middleware(
     req: Request,
     next: Next,
@@ -136,31 +214,26 @@ async fn list_tools(
         .collect();
 
     if registry.is_lazy_mode() {
-        infos.push(serde_json::json!({
-            "name": "manage_tools",
-            "description": "Manage available tools (enable/disable) to save tokens.",
-            "inputSchema": {
-                "type": "object",
-                "properties": {
-                    "action": {
-                        "type": "string",
-                        "enum": ["list", "enable", "disable"],
-                        "description": "The action to perform."
-                    },
-                    "tools": {
-                        "type": "array",
-                        "items": { "type": "string" },
-                        "description": "List of tool names to enable or disable."
-                    }
-                },
-                "required": ["action"]
-            }
-        }));
+        infos.push(ToolRegistry::manage_tools_info());
     }
 
     Json(serde_json::json!({ "tools": infos }))
 }
 
+async fn metrics(
+    Extension(registry): Extension<Arc<RwLock<ToolRegistry>>>,
+    Extension(clients): Extension<Vec<Arc<Aria2Client>>>,
+) -> impl IntoResponse {
+    let tool_metrics = registry.read().await.metrics();
+    (
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        crate::metrics::render(&tool_metrics, &clients).await,
+    )
+}
+
 async fn execute_tool(
     Extension(registry): Extension<Arc<RwLock<ToolRegistry>>>,
     Extension(clients): Extension<Vec<Arc<Aria2Client>>>,
@@ -273,6 +346,28 @@ mod tests {
         assert!(!tools.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_metrics() {
+        let registry = Arc::new(RwLock::new(ToolRegistry::new(&Config::default())));
+        let response = metrics(
+            axum::extract::Extension(registry),
+            axum::extract::Extension(Vec::new()),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), axum::http::StatusCode::OK);
+        assert!(response.headers()[axum::http::header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain"));
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("# TYPE aria2_mcp_tool_calls_total counter"));
+    }
+
     #[tokio::test]
     async fn test_execute_tool_not_found() {
         let registry = Arc::new(RwLock::new(ToolRegistry::new(&Config::default())));
@@ -528,6 +623,71 @@ mod tests {
         // But run_server_with_shutdown is already tested.
     }
 
+    #[tokio::test]
+    async fn test_health_routes_bypass_auth() {
+        use axum::http::{Request, StatusCode};
+        use tower::ServiceExt;
+        use wiremock::matchers::{body_string_contains, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_string_contains("aria2.getVersion"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": { "version": "1.37.0" }
+            })))
+            .mount(&mock_server)
+            .await;
+        let online = Arc::new(Aria2Client::new(Config {
+            rpc_url: format!("{}/jsonrpc", mock_server.uri()),
+            ..Config::default()
+        }));
+        let offline = Arc::new(Aria2Client::new(Config {
+            rpc_url: "http://127.0.0.1:1/jsonrpc".to_string(),
+            ..Config::default()
+        }));
+
+        let token = "test-token".to_string();
+        let app = |clients: Vec<Arc<Aria2Client>>| {
+            let token = token.clone();
+            Router::new()
+                .route("/tools", get(|| async { "ok" }))
+                .layer(middleware::from_fn(move |req, next| {
+                    auth_middleware(req, next, token.clone())
+                }))
+                .merge(health_routes(clients))
+        };
+        let get = |uri: &str| {
+            Request::builder()
+                .uri(uri)
+                .body(axum::body::Body::empty())
+                .unwrap()
+        };
+
+        let ready = app(vec![Arc::clone(&online)]);
+        let response = ready.clone().oneshot(get("/tools")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = ready.clone().oneshot(get("/healthz")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = ready.oneshot(get("/readyz")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["status"], "ready");
+        assert_eq!(body["instances"][0]["version"], "1.37.0");
+        assert_eq!(body["instances"][0]["notifications"]["state"], "idle");
+
+        let not_ready = app(vec![online, offline]);
+        let response = not_ready.oneshot(get("/readyz")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
     #[tokio::test]
     async fn test_run_server_with_shutdown() {
         let registry = Arc::new(RwLock::new(ToolRegistry::new(&Config::default())));
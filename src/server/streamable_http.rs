@@ -0,0 +1,818 @@
+use axum::{
+    extract::Json,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::get,
+    Extension, Router,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, RwLock};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+
+use super::mcp::{self, JsonRpcError, JsonRpcRequest, JsonRpcResponse, McpState};
+use crate::aria2::notifications::MCP_EVENT_METHOD;
+use crate::aria2::{Aria2Client, Aria2Notification};
+use crate::prompts::PromptRegistry;
+use crate::resources::ResourceRegistry;
+use crate::tools::ToolRegistry;
+
+pub const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Sessions without an open event stream are evicted after this long without
+/// a request, since clients may go away without sending DELETE.
+pub const SESSION_IDLE_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Maximum number of sessions. Initializing another one evicts the least
+/// recently used session.
+pub const MAX_SESSIONS: usize = 1000;
+
+/// One MCP client connected over Streamable HTTP.
+struct Session {
+    state: Arc<RwLock<McpState>>,
+    /// Open GET event stream, if the client has subscribed to one
+    stream: Option<mpsc::Sender<Value>>,
+    /// Time of the last request on this session
+    last_seen: Instant,
+}
+
+impl Session {
+    fn is_idle(&self, ttl: Duration) -> bool {
+        self.stream.as_ref().is_none_or(mpsc::Sender::is_closed) && self.last_seen.elapsed() >= ttl
+    }
+
+    /// Pushes a message to the open event stream, dropping the stream if its
+    /// client has gone away.
+    fn send(&mut self, message: &Value) {
+        if let Some(stream) = &self.stream {
+            if let Err(mpsc::error::TrySendError::Closed(_)) = stream.try_send(message.clone()) {
+                self.stream = None;
+            }
+        }
+    }
+}
+
+/// Shared state of the Streamable HTTP transport: the MCP sessions and the
+/// registries that `mcp::handle_request` dispatches to.
+pub struct StreamableHttp {
+    sessions: RwLock<HashMap<String, Session>>,
+    registry: Arc<RwLock<ToolRegistry>>,
+    resource_registry: Arc<RwLock<ResourceRegistry>>,
+    prompt_registry: Arc<RwLock<PromptRegistry>>,
+    clients: Vec<Arc<Aria2Client>>,
+    idle_ttl: Duration,
+    max_sessions: usize,
+}
+
+impl StreamableHttp {
+    #[must_use]
+    pub fn new(
+        registry: Arc<RwLock<ToolRegistry>>,
+        resource_registry: Arc<RwLock<ResourceRegistry>>,
+        prompt_registry: Arc<RwLock<PromptRegistry>>,
+        clients: Vec<Arc<Aria2Client>>,
+    ) -> Self {
+        Self {
+            sessions: RwLock::new(HashMap::new()),
+            registry,
+            resource_registry,
+            prompt_registry,
+            clients,
+            idle_ttl: SESSION_IDLE_TTL,
+            max_sessions: MAX_SESSIONS,
+        }
+    }
+
+    #[must_use]
+    pub fn with_session_limits(mut self, idle_ttl: Duration, max_sessions: usize) -> Self {
+        self.idle_ttl = idle_ttl;
+        self.max_sessions = max_sessions;
+        self
+    }
+
+    /// Removes the sessions idle for longer than the TTL, returning how many
+    /// were removed.
+    pub async fn evict_idle(&self) -> usize {
+        let mut sessions = self.sessions.write().await;
+        let before = sessions.len();
+        sessions.retain(|_, session| !session.is_idle(self.idle_ttl));
+        let evicted = before - sessions.len();
+        if evicted > 0 {
+            log::info!("Evicted {evicted} idle MCP session(s)");
+        }
+        evicted
+    }
+
+    /// Adds a session, evicting idle sessions and then the least recently
+    /// used ones if the maximum is reached.
+    async fn insert_session(&self, id: String, session: Session) {
+        let mut sessions = self.sessions.write().await;
+        if sessions.len() >= self.max_sessions {
+            sessions.retain(|_, session| !session.is_idle(self.idle_ttl));
+        }
+        while sessions.len() >= self.max_sessions.max(1) {
+            let Some(oldest) = sessions
+                .iter()
+                .min_by_key(|(_, s)| s.last_seen)
+                .map(|(id, _)| id.clone())
+            else {
+                break;
+            };
+            log::warn!("Session limit reached, evicting session {oldest}");
+            sessions.remove(&oldest);
+        }
+        sessions.insert(id, session);
+    }
+
+    pub async fn session_count(&self) -> usize {
+        self.sessions.read().await.len()
+    }
+
+    /// Sends a server-initiated message to every session with an open event
+    /// stream. Streams whose client has gone away are dropped.
+    pub async fn broadcast(&self, message: &Value) {
+        let mut sessions = self.sessions.write().await;
+        for session in sessions.values_mut() {
+            session.send(message);
+        }
+    }
+
+    /// Sends an aria2 event to every session with an open event stream whose
+    /// event filter allows it, along with `notifications/resources/updated`
+    /// for the subscribed resources it changes. The download snapshot is only
+    /// fetched when at least one session wants the event.
+    pub async fn publish_event(&self, notification: &Aria2Notification) {
+        let updated = self
+            .resource_registry
+            .read()
+            .await
+            .updated_by(notification.method);
+
+        // Each session's state is read without holding the sessions lock,
+        // which is only taken again briefly to push the messages
+        let streaming: Vec<(String, Arc<RwLock<McpState>>)> = self
+            .sessions
+            .read()
+            .await
+            .iter()
+            .filter(|(_, session)| session.stream.is_some())
+            .map(|(id, session)| (id.clone(), Arc::clone(&session.state)))
+            .collect();
+
+        let mut resource_updates = Vec::new();
+        let mut recipients = Vec::new();
+        for (id, state) in streaming {
+            let state = state.read().await;
+            for uri in updated
+                .iter()
+                .filter(|uri| state.resource_subscriptions.contains(*uri))
+            {
+                resource_updates.push((id.clone(), uri.clone()));
+            }
+            if state.event_filter.allows(notification) {
+                recipients.push(id);
+            }
+        }
+        if !resource_updates.is_empty() {
+            let mut sessions = self.sessions.write().await;
+            for (id, uri) in resource_updates {
+                if let Some(session) = sessions.get_mut(&id) {
+                    session.send(&json!({
+                        "jsonrpc": "2.0",
+                        "method": mcp::RESOURCE_UPDATED_METHOD,
+                        "params": { "uri": uri }
+                    }));
+                }
+            }
+        }
+        if recipients.is_empty() {
+            return;
+        }
+
+        let message = json!({
+            "jsonrpc": "2.0",
+            "method": MCP_EVENT_METHOD,
+            "params": mcp::event_params(notification, &self.clients).await
+        });
+        let mut sessions = self.sessions.write().await;
+        for id in recipients {
+            if let Some(session) = sessions.get_mut(&id) {
+                session.send(&message);
+            }
+        }
+    }
+
+    /// Moves notifications queued on a session's `McpState` to its event
+    /// stream. They stay queued until a stream is open.
+    async fn flush(&self, session_id: &str) {
+        let sessions = self.sessions.read().await;
+        let Some(session) = sessions.get(session_id) else {
+            return;
+        };
+        let Some(stream) = &session.stream else {
+            return;
+        };
+        let mut state = session.state.write().await;
+        while let Some(notification) = state.pop_notification() {
+            match serde_json::to_value(&notification) {
+                Ok(message) => {
+                    if stream.try_send(message).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => log::error!("Failed to serialize notification: {e}"),
+            }
+        }
+    }
+}
+
+pub fn routes(ctx: Arc<StreamableHttp>) -> Router {
+    Router::new()
+        .route(
+            "/mcp",
+            get(handle_get).post(handle_post).delete(handle_delete),
+        )
+        .layer(Extension(ctx))
+}
+
+fn session_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(SESSION_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .map(std::string::ToString::to_string)
+}
+
+fn error_response(status: StatusCode, code: i32, message: &str) -> Response {
+    let body = JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        result: None,
+        error: Some(JsonRpcError::new(code, message)),
+        id: None,
+    };
+    (status, Json(body)).into_response()
+}
+
+async fn handle_post(
+    Extension(ctx): Extension<Arc<StreamableHttp>>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let (requests, is_batch) = match serde_json::from_str::<Value>(&body) {
+        Ok(Value::Array(items)) => (
+            items
+                .into_iter()
+                .map(serde_json::from_value::<JsonRpcRequest>)
+                .collect::<Result<Vec<_>, _>>(),
+            true,
+        ),
+        Ok(item) => (
+            serde_json::from_value::<JsonRpcRequest>(item).map(|r| vec![r]),
+            false,
+        ),
+        Err(e) => (Err(e), false),
+    };
+    let requests = match requests {
+        Ok(r) if !r.is_empty() => r,
+        Ok(_) => return error_response(StatusCode::BAD_REQUEST, -32600, "Empty batch"),
+        Err(e) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                -32700,
+                &format!("Parse error: {e}"),
+            )
+        }
+    };
+
+    let is_initialize = requests.iter().any(|r| r.method == "initialize");
+    let (session_id, state) = if is_initialize {
+        let lazy_mode = ctx.registry.read().await.is_lazy_mode();
+        let state = Arc::new(RwLock::new(McpState::new(lazy_mode)));
+        let session_id = uuid::Uuid::new_v4().to_string();
+        ctx.insert_session(
+            session_id.clone(),
+            Session {
+                state: Arc::clone(&state),
+                stream: None,
+                last_seen: Instant::now(),
+            },
+        )
+        .await;
+        (session_id, state)
+    } else {
+        let Some(session_id) = session_id(&headers) else {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                -32600,
+                "Missing Mcp-Session-Id header",
+            );
+        };
+        let state = ctx.sessions.write().await.get_mut(&session_id).map(|s| {
+            s.last_seen = Instant::now();
+            Arc::clone(&s.state)
+        });
+        let Some(state) = state else {
+            return error_response(StatusCode::NOT_FOUND, -32001, "Session not found");
+        };
+        (session_id, state)
+    };
+
+    let mut responses = Vec::new();
+    for req in requests {
+        match mcp::handle_request(
+            req,
+            Arc::clone(&state),
+            Arc::clone(&ctx.registry),
+            Arc::clone(&ctx.resource_registry),
+            Arc::clone(&ctx.prompt_registry),
+            ctx.clients.clone(),
+        )
+        .await
+        {
+            Ok(Some(resp)) => responses.push(resp),
+            Ok(None) => {}
+            Err(e) => log::error!("Error handling request: {e}"),
+        }
+    }
+
+    ctx.flush(&session_id).await;
+
+    let mut response = if responses.is_empty() {
+        StatusCode::ACCEPTED.into_response()
+    } else if is_batch {
+        Json(responses).into_response()
+    } else {
+        Json(responses.remove(0)).into_response()
+    };
+
+    if is_initialize {
+        if let Ok(value) = HeaderValue::from_str(&session_id) {
+            response.headers_mut().insert(SESSION_HEADER, value);
+        }
+    }
+    response
+}
+
+async fn handle_get(
+    Extension(ctx): Extension<Arc<StreamableHttp>>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(session_id) = session_id(&headers) else {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            -32600,
+            "Missing Mcp-Session-Id header",
+        );
+    };
+
+    let (tx, rx) = mpsc::channel::<Value>(100);
+    {
+        let mut sessions = ctx.sessions.write().await;
+        let Some(session) = sessions.get_mut(&session_id) else {
+            return error_response(StatusCode::NOT_FOUND, -32001, "Session not found");
+        };
+        // A new stream replaces any previous one for this session
+        session.stream = Some(tx);
+        session.last_seen = Instant::now();
+    }
+    ctx.flush(&session_id).await;
+
+    let stream = ReceiverStream::new(rx).map(|message| {
+        Ok::<_, Infallible>(Event::default().event("message").data(message.to_string()))
+    });
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+async fn handle_delete(
+    Extension(ctx): Extension<Arc<StreamableHttp>>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(session_id) = session_id(&headers) else {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            -32600,
+            "Missing Mcp-Session-Id header",
+        );
+    };
+    if ctx.sessions.write().await.remove(&session_id).is_some() {
+        StatusCode::OK.into_response()
+    } else {
+        error_response(StatusCode::NOT_FOUND, -32001, "Session not found")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn test_ctx() -> Arc<StreamableHttp> {
+        let config = Config::default();
+        Arc::new(StreamableHttp::new(
+            Arc::new(RwLock::new(ToolRegistry::new(&config))),
+            Arc::new(RwLock::new(ResourceRegistry::default())),
+            Arc::new(RwLock::new(PromptRegistry::default())),
+            vec![Arc::new(Aria2Client::new(config))],
+        ))
+    }
+
+    fn post(body: &Value, session: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder()
+            .method("POST")
+            .uri("/mcp")
+            .header("content-type", "application/json")
+            .header("accept", "application/json, text/event-stream");
+        if let Some(session) = session {
+            builder = builder.header(SESSION_HEADER, session);
+        }
+        builder.body(Body::from(body.to_string())).unwrap()
+    }
+
+    async fn body_json(response: Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    async fn initialize(app: &Router) -> String {
+        let req = json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} });
+        let response = app.clone().oneshot(post(&req, None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let session = response.headers()[SESSION_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = body_json(response).await;
+        assert_eq!(body["result"]["serverInfo"]["name"], "aria2-mcp-rs");
+        session
+    }
+
+    #[tokio::test]
+    async fn test_initialize_creates_session() {
+        let ctx = test_ctx();
+        let app = routes(Arc::clone(&ctx));
+        let session = initialize(&app).await;
+        assert!(!session.is_empty());
+        assert_eq!(ctx.session_count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_request_routed_through_handle_request() {
+        let app = routes(test_ctx());
+        let session = initialize(&app).await;
+
+        let req = json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" });
+        let response = app
+            .clone()
+            .oneshot(post(&req, Some(&session)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = body_json(response).await;
+        assert_eq!(body["id"], 2);
+        assert!(!body["result"]["tools"].as_array().unwrap().is_empty());
+
+        let req = json!({ "jsonrpc": "2.0", "id": 3, "method": "prompts/list" });
+        let body = body_json(app.oneshot(post(&req, Some(&session))).await.unwrap()).await;
+        assert!(body["result"]["prompts"].is_array());
+    }
+
+    #[tokio::test]
+    async fn test_batch_and_notification() {
+        let app = routes(test_ctx());
+        let session = initialize(&app).await;
+
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        let response = app
+            .clone()
+            .oneshot(post(&notification, Some(&session)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let batch = json!([
+            { "jsonrpc": "2.0", "id": 1, "method": "resources/list" },
+            { "jsonrpc": "2.0", "method": "notifications/initialized" },
+            { "jsonrpc": "2.0", "id": 2, "method": "unknown" }
+        ]);
+        let body = body_json(app.oneshot(post(&batch, Some(&session))).await.unwrap()).await;
+        let responses = body.as_array().unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[1]["error"]["code"], -32601);
+    }
+
+    #[tokio::test]
+    async fn test_missing_and_unknown_session() {
+        let app = routes(test_ctx());
+        let req = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" });
+
+        let response = app.clone().oneshot(post(&req, None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app.oneshot(post(&req, Some("nope"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_parse_error() {
+        let app = routes(test_ctx());
+        let req = Request::builder()
+            .method("POST")
+            .uri("/mcp")
+            .body(Body::from("not json"))
+            .unwrap();
+        let response = app.oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(body_json(response).await["error"]["code"], -32700);
+    }
+
+    #[tokio::test]
+    async fn test_delete_session() {
+        let ctx = test_ctx();
+        let app = routes(Arc::clone(&ctx));
+        let session = initialize(&app).await;
+
+        let delete = || {
+            Request::builder()
+                .method("DELETE")
+                .uri("/mcp")
+                .header(SESSION_HEADER, &session)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app.clone().oneshot(delete()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(ctx.session_count().await, 0);
+
+        let response = app.oneshot(delete()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    fn limited_ctx(idle_ttl: Duration, max_sessions: usize) -> Arc<StreamableHttp> {
+        let config = Config::default();
+        Arc::new(
+            StreamableHttp::new(
+                Arc::new(RwLock::new(ToolRegistry::new(&config))),
+                Arc::new(RwLock::new(ResourceRegistry::default())),
+                Arc::new(RwLock::new(PromptRegistry::default())),
+                vec![Arc::new(Aria2Client::new(config))],
+            )
+            .with_session_limits(idle_ttl, max_sessions),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_evict_idle_sessions() {
+        let ctx = limited_ctx(Duration::ZERO, MAX_SESSIONS);
+        let app = routes(Arc::clone(&ctx));
+        let idle = initialize(&app).await;
+        let streaming = initialize(&app).await;
+
+        let req = Request::builder()
+            .method("GET")
+            .uri("/mcp")
+            .header(SESSION_HEADER, &streaming)
+            .body(Body::empty())
+            .unwrap();
+        let stream = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(stream.status(), StatusCode::OK);
+
+        // Only the session without an open event stream is evicted
+        assert_eq!(ctx.evict_idle().await, 1);
+        assert_eq!(ctx.session_count().await, 1);
+        let req = json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" });
+        let response = app.clone().oneshot(post(&req, Some(&idle))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // Once its client disconnects, the other one is evicted too
+        drop(stream);
+        assert_eq!(ctx.evict_idle().await, 1);
+        assert_eq!(ctx.session_count().await, 0);
+
+        // Sessions used within the TTL are kept
+        let ctx = limited_ctx(SESSION_IDLE_TTL, MAX_SESSIONS);
+        initialize(&routes(Arc::clone(&ctx))).await;
+        assert_eq!(ctx.evict_idle().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_max_sessions_evicts_least_recently_used() {
+        let ctx = limited_ctx(SESSION_IDLE_TTL, 2);
+        let app = routes(Arc::clone(&ctx));
+        let first = initialize(&app).await;
+        let second = initialize(&app).await;
+
+        // Using the first session makes the second the least recently used
+        let req = json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" });
+        let response = app.clone().oneshot(post(&req, Some(&first))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        initialize(&app).await;
+        assert_eq!(ctx.session_count().await, 2);
+        let response = app
+            .clone()
+            .oneshot(post(&req, Some(&second)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = app.oneshot(post(&req, Some(&first))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_receives_broadcast() {
+        let ctx = test_ctx();
+        let app = routes(Arc::clone(&ctx));
+        let session = initialize(&app).await;
+
+        let req = Request::builder()
+            .method("GET")
+            .uri("/mcp")
+            .header("accept", "text/event-stream")
+            .header(SESSION_HEADER, &session)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+        let notification: Aria2Notification = serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "method": "aria2.onDownloadComplete",
+            "params": [{ "gid": "abc" }]
+        }))
+        .unwrap();
+        ctx.broadcast(&notification.to_mcp_notification()).await;
+
+        let mut body = response.into_body().into_data_stream();
+        let chunk = body.next().await.unwrap().unwrap();
+        let text = String::from_utf8(chunk.to_vec()).unwrap();
+        assert!(text.contains("event: message"));
+        assert!(text.contains("notifications/aria2/event"));
+        assert!(text.contains("abc"));
+    }
+
+    #[tokio::test]
+    async fn test_publish_event_respects_session_filter() {
+        let ctx = test_ctx();
+        let app = routes(Arc::clone(&ctx));
+        let session = initialize(&app).await;
+
+        let req = Request::builder()
+            .method("POST")
+            .uri("/mcp")
+            .header("content-type", "application/json")
+            .header(SESSION_HEADER, &session)
+            .body(Body::from(
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": 2,
+                    "method": "aria2/subscribe",
+                    "params": { "events": ["download_error"] }
+                })
+                .to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let req = Request::builder()
+            .method("GET")
+            .uri("/mcp")
+            .header(SESSION_HEADER, &session)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(req).await.unwrap();
+
+        for method in ["aria2.onDownloadComplete", "aria2.onDownloadError"] {
+            let notification: Aria2Notification = serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "method": method,
+                "params": [{ "gid": "abc" }]
+            }))
+            .unwrap();
+            ctx.publish_event(&notification).await;
+        }
+
+        let mut body = response.into_body().into_data_stream();
+        let chunk = body.next().await.unwrap().unwrap();
+        let text = String::from_utf8(chunk.to_vec()).unwrap();
+        assert!(text.contains("download_error"));
+        assert!(!text.contains("download_complete"));
+    }
+
+    #[tokio::test]
+    async fn test_publish_event_sends_resource_updates() {
+        let ctx = test_ctx();
+        let app = routes(Arc::clone(&ctx));
+        let session = initialize(&app).await;
+
+        for (id, method, params) in [
+            (2, "aria2/subscribe", json!({ "events": [] })),
+            (
+                3,
+                "resources/subscribe",
+                json!({ "uri": "aria2://status/global" }),
+            ),
+        ] {
+            let req = Request::builder()
+                .method("POST")
+                .uri("/mcp")
+                .header("content-type", "application/json")
+                .header(SESSION_HEADER, &session)
+                .body(Body::from(
+                    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+                        .to_string(),
+                ))
+                .unwrap();
+            let response = app.clone().oneshot(req).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let req = Request::builder()
+            .method("GET")
+            .uri("/mcp")
+            .header(SESSION_HEADER, &session)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(req).await.unwrap();
+
+        let notification: Aria2Notification = serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "method": "aria2.onDownloadComplete",
+            "params": [{ "gid": "abc" }]
+        }))
+        .unwrap();
+        ctx.publish_event(&notification).await;
+
+        let mut body = response.into_body().into_data_stream();
+        let chunk = body.next().await.unwrap().unwrap();
+        let text = String::from_utf8(chunk.to_vec()).unwrap();
+        assert!(text.contains("notifications/resources/updated"));
+        assert!(text.contains("aria2://status/global"));
+        assert!(!text.contains("download_complete"));
+    }
+
+    #[tokio::test]
+    async fn test_publish_event_does_not_block_sessions_on_busy_state() {
+        let ctx = test_ctx();
+        let app = routes(Arc::clone(&ctx));
+        let session = initialize(&app).await;
+
+        let req = Request::builder()
+            .method("GET")
+            .uri("/mcp")
+            .header(SESSION_HEADER, &session)
+            .body(Body::empty())
+            .unwrap();
+        let _response = app.oneshot(req).await.unwrap();
+
+        // A request handler of the session holds its state
+        let state = Arc::clone(&ctx.sessions.read().await[&session].state);
+        let guard = state.write().await;
+
+        let notification: Aria2Notification = serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "method": "aria2.onDownloadComplete",
+            "params": [{ "gid": "abc" }]
+        }))
+        .unwrap();
+        let publisher = {
+            let ctx = Arc::clone(&ctx);
+            tokio::spawn(async move { ctx.publish_event(&notification).await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Other sessions can still be looked up and added meanwhile
+        let count = tokio::time::timeout(Duration::from_secs(1), ctx.session_count()).await;
+        assert_eq!(count.unwrap(), 1);
+
+        drop(guard);
+        publisher.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_unknown_session() {
+        let app = routes(test_ctx());
+        let req = Request::builder()
+            .method("GET")
+            .uri("/mcp")
+            .header(SESSION_HEADER, "nope")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -2,8 +2,11 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use tokio::fs;
+use tokio::sync::Mutex;
 
-use crate::config::{BandwidthProfile, BandwidthSchedule};
+use crate::aria2::recovery::SavedRecovery;
+use crate::bandwidth::SavedBandwidth;
+use crate::config::{BandwidthProfile, BandwidthSchedule, RSSFeed};
 use crate::error::{Error, Result};
 use crate::tools::organize_completed::Rule;
 
@@ -16,17 +19,71 @@ pub struct StateData {
     pub bandwidth_schedules: Vec<BandwidthSchedule>,
     #[serde(default)]
     pub organize_rules: Vec<Rule>,
+    /// RSS feeds and their download history, keyed by instance name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub rss_feeds: HashMap<String, Vec<RSSFeed>>,
+    /// Names of RSS feeds removed with `remove_rss_feed`, keyed by instance
+    /// name, so feeds defined in the config file stay removed
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub removed_rss_feeds: HashMap<String, Vec<String>>,
+    /// When stopped downloads finished, keyed by instance name and then GID
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub completion_times: HashMap<String, HashMap<String, i64>>,
+    /// Retry state and lineage of failed downloads, keyed by instance name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub recovery: HashMap<String, SavedRecovery>,
+    /// Options the scheduled bandwidth profile changed, with the values to
+    /// restore, keyed by instance name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub bandwidth_overrides: HashMap<String, SavedBandwidth>,
+    /// Downloads removed by the purge task, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub purge_history: Vec<PurgeRecord>,
 }
 
-#[derive(Debug, Clone)]
+/// A download result removed by the purge task.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgeRecord {
+    pub gid: String,
+    pub instance: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub status: String,
+    /// Unix time the download stopped
+    pub completed_at: i64,
+    /// Unix time it was purged
+    pub purged_at: i64,
+}
+
+/// Reads and writes the state file. Share one manager between every client
+/// using the same file so that `update` serializes their writes.
+#[derive(Debug)]
 pub struct StateManager {
     path: PathBuf,
+    lock: Mutex<()>,
 }
 
 impl StateManager {
     #[must_use]
     pub fn new(path: PathBuf) -> Self {
-        Self { path }
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Loads the state, applies `f` and saves it, holding the lock so that
+    /// concurrent updates do not drop each other's changes. Nothing is written
+    /// if the state file cannot be loaded.
+    pub async fn update<F>(&self, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut StateData),
+    {
+        let _guard = self.lock.lock().await;
+        let mut data = self.load().await?;
+        f(&mut data);
+        self.save_unlocked(&data).await
     }
 
     pub async fn load(&self) -> Result<StateData> {
@@ -49,6 +106,13 @@ impl StateManager {
     }
 
     pub async fn save(&self, data: &StateData) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        self.save_unlocked(data).await
+    }
+
+    /// Writes to a temporary file renamed over the state file, so readers
+    /// never see a partial write.
+    async fn save_unlocked(&self, data: &StateData) -> Result<()> {
         let content = serde_json::to_string_pretty(data)
             .map_err(|e| Error::Internal(format!("Failed to serialize state: {e}")))?;
 
@@ -62,9 +126,15 @@ impl StateManager {
             }
         }
 
-        fs::write(&self.path, content)
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        fs::write(&tmp_path, content)
             .await
             .map_err(|e| Error::Internal(format!("Failed to write state file: {e}")))?;
+        if let Err(e) = fs::rename(&tmp_path, &self.path).await {
+            let _ = fs::remove_file(&tmp_path).await;
+            return Err(Error::Internal(format!("Failed to write state file: {e}")));
+        }
 
         Ok(())
     }
@@ -0,0 +1,421 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::aria2::{DownloadState, DownloadStatus, QueueSnapshot};
+
+/// Source reported for BitTorrent downloads, which have no single host.
+pub const BITTORRENT_SOURCE: &str = "bittorrent";
+
+/// Throughput of one instance at one point in time.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsSample {
+    /// Unix time the sample was taken
+    pub time: i64,
+    pub download_speed: u64,
+    pub upload_speed: u64,
+    pub num_active: u64,
+    pub num_waiting: u64,
+    /// Downloads that transferred data or finished since the previous sample
+    pub downloads: Vec<DownloadSample>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadSample {
+    pub gid: String,
+    /// Host of the first URI, or `bittorrent`
+    pub source: String,
+    pub download_speed: u64,
+    pub upload_speed: u64,
+    /// Bytes downloaded since the previous sample
+    pub downloaded: u64,
+    /// Bytes uploaded since the previous sample
+    pub uploaded: u64,
+    /// `complete` or `error` if the download finished since the previous sample
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished: Option<String>,
+}
+
+/// Progress of a download when it was last sampled.
+#[derive(Debug, Clone, Copy)]
+struct Progress {
+    completed_length: u64,
+    upload_length: u64,
+    stopped: bool,
+}
+
+#[derive(Debug, Default)]
+struct RecorderState {
+    samples: VecDeque<StatsSample>,
+    progress: HashMap<String, Progress>,
+    /// Set once the first snapshot has been taken as the baseline
+    primed: bool,
+}
+
+/// In-memory throughput samples of an instance, taken by the stats sampler
+/// and kept for the configured retention period.
+#[derive(Debug, Default)]
+pub struct StatsRecorder {
+    state: Mutex<RecorderState>,
+}
+
+impl StatsRecorder {
+    /// Adds a sample taken from `snapshot` at `now` and drops samples older
+    /// than `retention_secs`. The first snapshot only sets the baseline the
+    /// transferred bytes are counted from.
+    pub fn record(&self, snapshot: &QueueSnapshot, now: i64, retention_secs: u64) {
+        let mut state = self.state.lock().unwrap();
+        let primed = state.primed;
+
+        let mut downloads = Vec::new();
+        let mut progress = HashMap::new();
+        for download in snapshot
+            .active
+            .iter()
+            .chain(&snapshot.waiting)
+            .chain(&snapshot.stopped)
+        {
+            let current = Progress {
+                completed_length: download.completed_length,
+                upload_length: download.upload_length,
+                stopped: matches!(
+                    download.status,
+                    Some(DownloadState::Complete | DownloadState::Error | DownloadState::Removed)
+                ),
+            };
+            let previous = state.progress.get(&download.gid).copied();
+            progress.insert(download.gid.clone(), current);
+
+            // Downloads first seen after the baseline started since then
+            let previous = match previous {
+                Some(previous) => previous,
+                None if primed => Progress {
+                    completed_length: 0,
+                    upload_length: 0,
+                    stopped: false,
+                },
+                None => continue,
+            };
+
+            let finished = match download.status {
+                Some(status @ (DownloadState::Complete | DownloadState::Error))
+                    if !previous.stopped =>
+                {
+                    Some(status.as_str().to_string())
+                }
+                _ => None,
+            };
+            let sample = DownloadSample {
+                gid: download.gid.clone(),
+                source: source(download),
+                download_speed: download.download_speed,
+                upload_speed: download.upload_speed,
+                downloaded: current
+                    .completed_length
+                    .saturating_sub(previous.completed_length),
+                uploaded: current.upload_length.saturating_sub(previous.upload_length),
+                finished,
+            };
+            if sample.download_speed > 0
+                || sample.upload_speed > 0
+                || sample.downloaded > 0
+                || sample.uploaded > 0
+                || sample.finished.is_some()
+            {
+                downloads.push(sample);
+            }
+        }
+
+        state.progress = progress;
+        state.primed = true;
+        state.samples.push_back(StatsSample {
+            time: now,
+            download_speed: snapshot.global_stat.download_speed,
+            upload_speed: snapshot.global_stat.upload_speed,
+            num_active: snapshot.global_stat.num_active,
+            num_waiting: snapshot.global_stat.num_waiting,
+            downloads,
+        });
+
+        let cutoff = now.saturating_sub(i64::try_from(retention_secs).unwrap_or(i64::MAX));
+        while state.samples.front().is_some_and(|s| s.time < cutoff) {
+            state.samples.pop_front();
+        }
+    }
+
+    /// Unix time of the oldest sample still kept.
+    #[must_use]
+    pub fn oldest(&self) -> Option<i64> {
+        self.state.lock().unwrap().samples.front().map(|s| s.time)
+    }
+
+    /// Summarizes the samples taken in the `window_secs` before `now`,
+    /// listing at most `top` sources.
+    #[must_use]
+    pub fn summarize(&self, now: i64, window_secs: u64, top: usize) -> StatsSummary {
+        let from = now.saturating_sub(i64::try_from(window_secs).unwrap_or(i64::MAX));
+        let state = self.state.lock().unwrap();
+        let samples: Vec<&StatsSample> = state
+            .samples
+            .iter()
+            .filter(|s| s.time > from && s.time <= now)
+            .collect();
+
+        let mut summary = StatsSummary {
+            window_secs,
+            samples: samples.len(),
+            ..StatsSummary::default()
+        };
+        let mut sources: HashMap<&str, SourceTotals> = HashMap::new();
+        for sample in &samples {
+            summary.peak_download_speed = summary.peak_download_speed.max(sample.download_speed);
+            summary.peak_upload_speed = summary.peak_upload_speed.max(sample.upload_speed);
+
+            // Speed of each source in this sample, summed over its downloads
+            let mut speeds: HashMap<&str, u64> = HashMap::new();
+            for download in &sample.downloads {
+                summary.bytes_downloaded += download.downloaded;
+                summary.bytes_uploaded += download.uploaded;
+                if download.finished.is_none() {
+                    *speeds.entry(&download.source).or_default() += download.download_speed;
+                }
+
+                let totals = sources.entry(&download.source).or_default();
+                totals.bytes += download.downloaded;
+                match download.finished.as_deref() {
+                    Some("complete") => {
+                        summary.completed += 1;
+                        totals.completed += 1;
+                    }
+                    Some("error") => {
+                        summary.errors += 1;
+                        totals.errors += 1;
+                    }
+                    _ => {}
+                }
+            }
+            for (source, speed) in speeds {
+                let totals = sources.entry(source).or_default();
+                totals.speed_sum += speed;
+                totals.speed_samples += 1;
+            }
+        }
+
+        if !samples.is_empty() {
+            let count = samples.len() as u64;
+            summary.avg_download_speed =
+                samples.iter().map(|s| s.download_speed).sum::<u64>() / count;
+            summary.avg_upload_speed = samples.iter().map(|s| s.upload_speed).sum::<u64>() / count;
+        }
+        summary.error_rate = error_rate(summary.completed, summary.errors);
+
+        let mut top_sources: Vec<SourceStats> = sources
+            .into_iter()
+            .map(|(source, totals)| SourceStats {
+                source: source.to_string(),
+                bytes: totals.bytes,
+                avg_download_speed: totals
+                    .speed_sum
+                    .checked_div(totals.speed_samples)
+                    .unwrap_or(0),
+                completed: totals.completed,
+                errors: totals.errors,
+                error_rate: error_rate(totals.completed, totals.errors),
+            })
+            .collect();
+        top_sources.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.source.cmp(&b.source)));
+        top_sources.truncate(top);
+        summary.top_sources = top_sources;
+        summary
+    }
+}
+
+#[derive(Debug, Default)]
+struct SourceTotals {
+    bytes: u64,
+    speed_sum: u64,
+    speed_samples: u64,
+    completed: u64,
+    errors: u64,
+}
+
+/// Throughput of an instance over a time window.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsSummary {
+    pub window_secs: u64,
+    pub samples: usize,
+    pub bytes_downloaded: u64,
+    pub bytes_uploaded: u64,
+    pub avg_download_speed: u64,
+    pub peak_download_speed: u64,
+    pub avg_upload_speed: u64,
+    pub peak_upload_speed: u64,
+    pub completed: u64,
+    pub errors: u64,
+    /// Share of finished downloads that failed, if any finished
+    pub error_rate: Option<f64>,
+    pub top_sources: Vec<SourceStats>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceStats {
+    pub source: String,
+    pub bytes: u64,
+    /// Mean download speed over the samples in which the source had
+    /// unfinished downloads
+    pub avg_download_speed: u64,
+    pub completed: u64,
+    pub errors: u64,
+    pub error_rate: Option<f64>,
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn error_rate(completed: u64, errors: u64) -> Option<f64> {
+    let finished = completed + errors;
+    (finished > 0).then(|| errors as f64 / finished as f64)
+}
+
+/// The host a download is fetched from, `bittorrent` for torrents, or
+/// `unknown` if it has no parsable URI.
+fn source(download: &DownloadStatus) -> String {
+    if download.info_hash.is_some() || download.bittorrent.is_some() {
+        return BITTORRENT_SOURCE.to_string();
+    }
+    download
+        .uris()
+        .find_map(|uri| {
+            reqwest::Url::parse(uri)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_string))
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aria2::GlobalStat;
+    use serde_json::json;
+
+    fn download(gid: &str, status: &str, uri: &str, completed: u64, speed: u64) -> DownloadStatus {
+        serde_json::from_value(json!({
+            "gid": gid,
+            "status": status,
+            "completedLength": completed.to_string(),
+            "downloadSpeed": speed.to_string(),
+            "files": [{ "path": "", "uris": [{ "uri": uri, "status": "used" }] }]
+        }))
+        .unwrap()
+    }
+
+    fn snapshot(active: Vec<DownloadStatus>, stopped: Vec<DownloadStatus>) -> QueueSnapshot {
+        let download_speed = active.iter().map(|d| d.download_speed).sum();
+        QueueSnapshot {
+            global_stat: GlobalStat {
+                download_speed,
+                num_active: active.len() as u64,
+                ..GlobalStat::default()
+            },
+            active,
+            waiting: vec![],
+            stopped,
+        }
+    }
+
+    #[test]
+    fn test_record_counts_bytes_from_baseline() {
+        let recorder = StatsRecorder::default();
+        recorder.record(
+            &snapshot(
+                vec![download("1", "active", "http://a.com/x", 100, 10)],
+                vec![],
+            ),
+            1_000,
+            3_600,
+        );
+        recorder.record(
+            &snapshot(
+                vec![
+                    download("1", "active", "http://a.com/x", 700, 20),
+                    download("2", "active", "https://b.org/y", 50, 5),
+                ],
+                vec![],
+            ),
+            1_060,
+            3_600,
+        );
+        recorder.record(
+            &snapshot(
+                vec![],
+                vec![
+                    download("1", "complete", "http://a.com/x", 1_000, 0),
+                    download("2", "error", "https://b.org/y", 50, 0),
+                ],
+            ),
+            1_120,
+            3_600,
+        );
+
+        let summary = recorder.summarize(1_120, 3_600, 5);
+        assert_eq!(summary.samples, 3);
+        // The baseline's 100 bytes were downloaded before sampling began
+        assert_eq!(summary.bytes_downloaded, 950);
+        assert_eq!(summary.peak_download_speed, 25);
+        assert_eq!(summary.avg_download_speed, 11);
+        assert_eq!(summary.completed, 1);
+        assert_eq!(summary.errors, 1);
+        assert_eq!(summary.error_rate, Some(0.5));
+        assert_eq!(summary.top_sources[0].source, "a.com");
+        assert_eq!(summary.top_sources[0].bytes, 900);
+        assert_eq!(summary.top_sources[0].avg_download_speed, 20);
+        assert_eq!(summary.top_sources[1].source, "b.org");
+        assert_eq!(summary.top_sources[1].error_rate, Some(1.0));
+
+        // Only the last sample falls in a one minute window
+        let summary = recorder.summarize(1_120, 60, 1);
+        assert_eq!(summary.samples, 1);
+        assert_eq!(summary.bytes_downloaded, 300);
+        assert_eq!(summary.top_sources.len(), 1);
+
+        // Finished downloads are not counted again
+        recorder.record(
+            &snapshot(
+                vec![],
+                vec![download("1", "complete", "http://a.com/x", 1_000, 0)],
+            ),
+            1_180,
+            3_600,
+        );
+        assert_eq!(recorder.summarize(1_180, 60, 5).completed, 0);
+    }
+
+    #[test]
+    fn test_record_drops_old_samples() {
+        let recorder = StatsRecorder::default();
+        recorder.record(&snapshot(vec![], vec![]), 1_000, 100);
+        recorder.record(&snapshot(vec![], vec![]), 1_050, 100);
+        assert_eq!(recorder.oldest(), Some(1_000));
+        recorder.record(&snapshot(vec![], vec![]), 1_150, 100);
+        assert_eq!(recorder.oldest(), Some(1_050));
+        assert!(recorder.summarize(1_150, 10, 5).error_rate.is_none());
+    }
+
+    #[test]
+    fn test_source() {
+        let torrent: DownloadStatus =
+            serde_json::from_value(json!({ "gid": "1", "infoHash": "abc" })).unwrap();
+        assert_eq!(source(&torrent), BITTORRENT_SOURCE);
+        assert_eq!(
+            source(&download("2", "active", "ftp://mirror.example.com/f", 0, 0)),
+            "mirror.example.com"
+        );
+        assert_eq!(
+            source(&download("3", "active", "not a uri", 0, 0)),
+            "unknown"
+        );
+    }
+}
@@ -0,0 +1,520 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::{self, Duration};
+
+use crate::aria2::Aria2Client;
+use crate::config::AutoShutdownConfig;
+use crate::tools::registry::McpeTool;
+
+/// Runs the system commands of drain actions. Tests substitute a fake.
+#[async_trait]
+pub trait CommandExecutor: Send + Sync {
+    /// Runs `command`, the program followed by its arguments, and fails if
+    /// it cannot be started or exits unsuccessfully.
+    async fn execute(&self, command: &[String]) -> Result<()>;
+}
+
+/// Runs commands as child processes.
+pub struct SystemExecutor;
+
+#[async_trait]
+impl CommandExecutor for SystemExecutor {
+    async fn execute(&self, command: &[String]) -> Result<()> {
+        let (program, args) = command.split_first().context("Empty command")?;
+        let status = tokio::process::Command::new(program)
+            .args(args)
+            .status()
+            .await
+            .with_context(|| format!("Failed to run '{program}'"))?;
+        if !status.success() {
+            anyhow::bail!("'{program}' exited with {status}");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DrainAction {
+    /// Power off the machine
+    Shutdown,
+    /// Suspend the machine
+    Suspend,
+    /// Run the configured `auto_shutdown.command`
+    Command,
+    /// Stop aria2 itself with `aria2.shutdown`
+    Aria2,
+}
+
+impl DrainAction {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Shutdown => "shutdown",
+            Self::Suspend => "suspend",
+            Self::Command => "command",
+            Self::Aria2 => "aria2",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArmState {
+    Idle,
+    /// Waiting for every queue to drain
+    Armed,
+    /// The queues drained and the grace period is running
+    Grace,
+    Fired,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArmStatus {
+    pub state: ArmState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<DrainAction>,
+    pub grace_secs: u64,
+    /// Unix times of each step
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub armed_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drained_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fired_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Default for ArmStatus {
+    fn default() -> Self {
+        Self {
+            state: ArmState::Idle,
+            action: None,
+            grace_secs: 0,
+            armed_at: None,
+            drained_at: None,
+            fired_at: None,
+            error: None,
+        }
+    }
+}
+
+/// Arms a one-shot action that runs once no instance has active or waiting
+/// downloads.
+pub struct AutoShutdownTool {
+    executor: Arc<dyn CommandExecutor>,
+    status: Arc<Mutex<ArmStatus>>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Default for AutoShutdownTool {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemExecutor))
+    }
+}
+
+impl AutoShutdownTool {
+    #[must_use]
+    pub fn new(executor: Arc<dyn CommandExecutor>) -> Self {
+        Self {
+            executor,
+            status: Arc::new(Mutex::new(ArmStatus::default())),
+            task: Mutex::new(None),
+        }
+    }
+
+    fn status(&self) -> Value {
+        json!(*self.status.lock().unwrap())
+    }
+
+    async fn handle(&self, clients: Vec<Arc<Aria2Client>>, args: Value) -> Result<Value> {
+        let action = args
+            .get("action")
+            .and_then(|v| v.as_str())
+            .context("Missing or invalid 'action' argument")?;
+
+        match action {
+            "arm" => {
+                let drain_action: DrainAction = serde_json::from_value(
+                    args.get("onDrain")
+                        .cloned()
+                        .context("Missing 'onDrain' argument")?,
+                )
+                .context("Invalid 'onDrain' argument")?;
+
+                let client = clients.first().context("No clients provided")?;
+                let config = {
+                    let config = client.config();
+                    let config_guard = config.read().await;
+                    config_guard.auto_shutdown.clone()
+                };
+                if !config.allowed_actions.contains(&drain_action) {
+                    anyhow::bail!(
+                        "The '{}' action is not allowed; add it to auto_shutdown.allowed_actions",
+                        drain_action.as_str()
+                    );
+                }
+                if drain_action == DrainAction::Command && config.command.is_empty() {
+                    anyhow::bail!("No command is configured in auto_shutdown.command");
+                }
+                let grace_secs = args
+                    .get("graceSecs")
+                    .and_then(Value::as_u64)
+                    .unwrap_or(config.grace_secs);
+
+                if let Some(task) = self.task.lock().unwrap().take() {
+                    task.abort();
+                }
+                *self.status.lock().unwrap() = ArmStatus {
+                    state: ArmState::Armed,
+                    action: Some(drain_action),
+                    grace_secs,
+                    armed_at: Some(chrono::Utc::now().timestamp()),
+                    ..ArmStatus::default()
+                };
+
+                let task = tokio::spawn(watch(
+                    clients,
+                    drain_action,
+                    grace_secs,
+                    config,
+                    Arc::clone(&self.executor),
+                    Arc::clone(&self.status),
+                ));
+                *self.task.lock().unwrap() = Some(task);
+                Ok(self.status())
+            }
+            "cancel" => {
+                if let Some(task) = self.task.lock().unwrap().take() {
+                    task.abort();
+                }
+                let mut status = self.status.lock().unwrap();
+                if !matches!(status.state, ArmState::Armed | ArmState::Grace) {
+                    anyhow::bail!("No drain action is armed");
+                }
+                status.state = ArmState::Cancelled;
+                Ok(json!(*status))
+            }
+            "status" => Ok(self.status()),
+            _ => Err(anyhow::anyhow!("Unknown action: {action}")),
+        }
+    }
+}
+
+#[async_trait]
+impl McpeTool for AutoShutdownTool {
+    fn name(&self) -> String {
+        "auto_shutdown".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Arm a one-shot action (shut down or suspend the machine, run the configured command, or stop aria2) that runs once every instance has no active or waiting downloads, after a grace period. Also cancels it or reports its status.".to_string()
+    }
+
+    fn schema(&self) -> Result<Value> {
+        Ok(json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["arm", "cancel", "status"],
+                    "description": "The action to perform"
+                },
+                "onDrain": {
+                    "type": "string",
+                    "enum": ["shutdown", "suspend", "command", "aria2"],
+                    "description": "What to do once the queues drain (required for arm)"
+                },
+                "graceSecs": {
+                    "type": "integer",
+                    "description": "Seconds to wait after the queues drain before acting, in case new downloads arrive (defaults to auto_shutdown.grace_secs)"
+                }
+            },
+            "required": ["action"]
+        }))
+    }
+
+    async fn run(&self, client: &Aria2Client, args: Value) -> Result<Value> {
+        self.handle(vec![Arc::new(client.clone())], args).await
+    }
+
+    async fn run_multi(&self, clients: &[Arc<Aria2Client>], args: Value) -> Result<Value> {
+        self.handle(clients.to_vec(), args).await
+    }
+}
+
+/// Waits for every queue to drain and stay drained for `grace_secs`, then
+/// runs `action` once.
+async fn watch(
+    clients: Vec<Arc<Aria2Client>>,
+    action: DrainAction,
+    grace_secs: u64,
+    config: AutoShutdownConfig,
+    executor: Arc<dyn CommandExecutor>,
+    status: Arc<Mutex<ArmStatus>>,
+) {
+    let mut interval = time::interval(Duration::from_secs(config.check_interval_secs.max(1)));
+
+    loop {
+        interval.tick().await;
+        if !queues_drained(&clients).await {
+            continue;
+        }
+
+        {
+            let mut status = status.lock().unwrap();
+            status.state = ArmState::Grace;
+            status.drained_at = Some(chrono::Utc::now().timestamp());
+        }
+        log::info!("Download queues drained, running {action:?} in {grace_secs} seconds");
+        time::sleep(Duration::from_secs(grace_secs)).await;
+
+        if !queues_drained(&clients).await {
+            log::info!("New downloads arrived during the grace period, waiting again");
+            let mut status = status.lock().unwrap();
+            status.state = ArmState::Armed;
+            status.drained_at = None;
+            continue;
+        }
+
+        let result = fire(&clients, action, &config, executor.as_ref()).await;
+        let mut status = status.lock().unwrap();
+        status.fired_at = Some(chrono::Utc::now().timestamp());
+        match result {
+            Ok(()) => status.state = ArmState::Fired,
+            Err(e) => {
+                log::error!("Drain action {action:?} failed: {e}");
+                status.state = ArmState::Failed;
+                status.error = Some(e.to_string());
+            }
+        }
+        return;
+    }
+}
+
+/// Whether no instance has active or waiting (including paused) downloads.
+/// An instance that cannot be reached is not considered drained.
+async fn queues_drained(clients: &[Arc<Aria2Client>]) -> bool {
+    for client in clients {
+        match client.global_stat().await {
+            Ok(stat) if stat.num_active == 0 && stat.num_waiting == 0 => {}
+            Ok(_) => return false,
+            Err(e) => {
+                log::warn!("Failed to check the queue of {}: {e}", client.name);
+                return false;
+            }
+        }
+    }
+    true
+}
+
+async fn fire(
+    clients: &[Arc<Aria2Client>],
+    action: DrainAction,
+    config: &AutoShutdownConfig,
+    executor: &dyn CommandExecutor,
+) -> Result<()> {
+    match action {
+        DrainAction::Shutdown => executor.execute(&config.shutdown_command).await,
+        DrainAction::Suspend => executor.execute(&config.suspend_command).await,
+        DrainAction::Command => executor.execute(&config.command).await,
+        DrainAction::Aria2 => {
+            let mut errors = Vec::new();
+            for client in clients {
+                if let Err(e) = client.shutdown().await {
+                    errors.push(format!("{}: {e}", client.name));
+                }
+            }
+            if errors.is_empty() {
+                Ok(())
+            } else {
+                Err(anyhow::anyhow!(
+                    "Failed to shut down aria2: {}",
+                    errors.join("; ")
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[derive(Default)]
+    struct FakeExecutor {
+        commands: Mutex<Vec<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl CommandExecutor for FakeExecutor {
+        async fn execute(&self, command: &[String]) -> Result<()> {
+            self.commands.lock().unwrap().push(command.to_vec());
+            Ok(())
+        }
+    }
+
+    async fn mock_aria2(num_active: u64) -> (MockServer, Arc<Aria2Client>) {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_string_contains("aria2.getGlobalStat"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": { "numActive": num_active.to_string(), "numWaiting": "0" }
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let mut config = Config {
+            rpc_url: format!("{}/jsonrpc", mock_server.uri()),
+            ..Config::default()
+        };
+        config.auto_shutdown.check_interval_secs = 1;
+        config.auto_shutdown.command = vec!["notify-send".to_string(), "done".to_string()];
+        config.auto_shutdown.allowed_actions = vec![
+            DrainAction::Shutdown,
+            DrainAction::Command,
+            DrainAction::Aria2,
+        ];
+        (mock_server, Arc::new(Aria2Client::new(config)))
+    }
+
+    async fn wait_for(tool: &AutoShutdownTool, state: &str) -> Value {
+        for _ in 0..50 {
+            let status = tool.status();
+            if status["state"] == state {
+                return status;
+            }
+            time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("State never became {state}: {}", tool.status());
+    }
+
+    #[tokio::test]
+    async fn test_arm_runs_command_when_drained() {
+        let (_server, client) = mock_aria2(0).await;
+        let executor = Arc::new(FakeExecutor::default());
+        let tool = AutoShutdownTool::new(executor.clone());
+
+        let status = tool
+            .run_multi(
+                &[client],
+                json!({ "action": "arm", "onDrain": "command", "graceSecs": 0 }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(status["action"], "command");
+
+        let status = wait_for(&tool, "fired").await;
+        assert!(status["firedAt"].is_i64());
+        assert_eq!(
+            *executor.commands.lock().unwrap(),
+            vec![vec!["notify-send".to_string(), "done".to_string()]]
+        );
+
+        // One-shot: nothing is left to cancel
+        assert!(tool
+            .run_multi(&[], json!({ "action": "cancel" }))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_while_busy() {
+        let (_server, client) = mock_aria2(1).await;
+        let executor = Arc::new(FakeExecutor::default());
+        let tool = AutoShutdownTool::new(executor.clone());
+
+        tool.run_multi(
+            &[client],
+            json!({ "action": "arm", "onDrain": "shutdown", "graceSecs": 0 }),
+        )
+        .await
+        .unwrap();
+        time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(tool.status()["state"], "armed");
+
+        let status = tool
+            .run_multi(&[], json!({ "action": "cancel" }))
+            .await
+            .unwrap();
+        assert_eq!(status["state"], "cancelled");
+        assert!(executor.commands.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_arm_aria2_shutdown() {
+        let (server, client) = mock_aria2(0).await;
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_string_contains("aria2.shutdown"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": "OK"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let tool = AutoShutdownTool::new(Arc::new(FakeExecutor::default()));
+        tool.run_multi(
+            &[client],
+            json!({ "action": "arm", "onDrain": "aria2", "graceSecs": 0 }),
+        )
+        .await
+        .unwrap();
+        wait_for(&tool, "fired").await;
+    }
+
+    #[tokio::test]
+    async fn test_arm_validation() {
+        let client = Arc::new(Aria2Client::new(Config::default()));
+        let tool = AutoShutdownTool::new(Arc::new(FakeExecutor::default()));
+
+        // No command configured
+        assert!(tool
+            .run_multi(
+                &[Arc::clone(&client)],
+                json!({ "action": "arm", "onDrain": "command" })
+            )
+            .await
+            .is_err());
+        assert!(tool
+            .run_multi(
+                &[Arc::clone(&client)],
+                json!({ "action": "arm", "onDrain": "reboot" })
+            )
+            .await
+            .is_err());
+        assert!(tool
+            .run_multi(&[Arc::clone(&client)], json!({ "action": "arm" }))
+            .await
+            .is_err());
+        // Powering off or suspending needs the operator's opt-in
+        for action in ["shutdown", "suspend"] {
+            let err = tool
+                .run_multi(
+                    &[Arc::clone(&client)],
+                    json!({ "action": "arm", "onDrain": action }),
+                )
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("allowed_actions"));
+        }
+        assert_eq!(tool.status()["state"], "idle");
+    }
+}
@@ -1,10 +1,9 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use futures_util::future::join_all;
 use serde::Deserialize;
 use serde_json::{json, Value};
 
-use crate::aria2::Aria2Client;
+use crate::aria2::{Aria2Client, MultiCall};
 use crate::tools::registry::McpeTool;
 
 pub struct BulkManageDownloadsTool;
@@ -50,24 +49,43 @@ impl McpeTool for BulkManageDownloadsTool {
     async fn run(&self, client: &Aria2Client, args: Value) -> Result<Value> {
         let args: BulkManageDownloadsArgs = serde_json::from_value(args)?;
 
-        let futures = args.gids.iter().map(|gid| {
-            let client = client.clone();
-            let action = args.action.clone();
-            let gid = gid.clone();
-            async move {
-                let result: Result<()> = match action.as_str() {
-                    "pause" => client.pause(&gid).await,
-                    "resume" => client.unpause(&gid).await,
-                    "remove" => client.remove(&gid).await,
-                    "forcePause" => client.force_pause(&gid).await,
-                    "forceRemove" => client.force_remove(&gid).await,
-                    _ => Err(anyhow::anyhow!("Unknown action: {action}")),
-                };
-                (gid, result)
+        let method = match args.action.as_str() {
+            "pause" => Some("aria2.pause"),
+            "resume" => Some("aria2.unpause"),
+            "remove" => Some("aria2.remove"),
+            "forcePause" => Some("aria2.forcePause"),
+            "forceRemove" => Some("aria2.forceRemove"),
+            _ => None,
+        };
+
+        let results: Vec<(String, Result<()>)> = if let Some(method) = method {
+            let mut calls = MultiCall::new();
+            for gid in &args.gids {
+                calls.push(method, vec![json!(gid)]);
             }
-        });
 
-        let results = join_all(futures).await;
+            match client.multicall(&calls).await {
+                Ok(outcomes) => args
+                    .gids
+                    .into_iter()
+                    .zip(outcomes)
+                    .map(|(gid, res)| (gid, res.map(|_| ()).map_err(anyhow::Error::from)))
+                    .collect(),
+                // The whole batch failed, e.g. aria2 is unreachable
+                Err(e) => {
+                    let message = e.to_string();
+                    args.gids
+                        .into_iter()
+                        .map(|gid| (gid, Err(anyhow::anyhow!("{message}"))))
+                        .collect()
+                }
+            }
+        } else {
+            args.gids
+                .into_iter()
+                .map(|gid| (gid, Err(anyhow::anyhow!("Unknown action: {}", args.action))))
+                .collect()
+        };
 
         let mut success_count = 0;
         let mut failure_count = 0;
@@ -151,4 +169,47 @@ mod tests {
             .unwrap()
             .contains("Unknown action"));
     }
+
+    #[tokio::test]
+    async fn test_bulk_manage_downloads_multicall_partial_failure() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let config = Config {
+            rpc_url: format!("{}/jsonrpc", mock_server.uri()),
+            ..Config::default()
+        };
+        let client = Aria2Client::new(config);
+
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_partial_json(json!({
+                "method": "system.multicall",
+                "params": [[
+                    { "methodName": "aria2.pause", "params": ["1"] },
+                    { "methodName": "aria2.pause", "params": ["2"] }
+                ]]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": [["1"], { "code": 1, "message": "GID 2 is not found" }]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let tool = BulkManageDownloadsTool;
+        let args = json!({ "action": "pause", "gids": ["1", "2"] });
+        let result = tool.run(&client, args).await.unwrap();
+        assert_eq!(result["success_count"], 1);
+        assert_eq!(result["failure_count"], 1);
+        assert_eq!(result["results"][0]["status"], "success");
+        assert_eq!(result["results"][1]["gid"], "2");
+        assert!(result["results"][1]["message"]
+            .as_str()
+            .unwrap()
+            .contains("GID 2 is not found"));
+    }
 }
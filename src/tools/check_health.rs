@@ -4,7 +4,8 @@ use serde_json::{json, Value};
 use std::path::Path;
 use std::sync::Arc;
 
-use crate::aria2::Aria2Client;
+use crate::aria2::recovery::TrackerInjection;
+use crate::aria2::{Aria2Client, DownloadState, DownloadStatus, GlobalStat};
 use crate::tools::registry::McpeTool;
 
 pub struct CheckHealthTool;
@@ -27,10 +28,7 @@ impl McpeTool for CheckHealthTool {
     }
 
     async fn run(&self, client: &Aria2Client, _args: Value) -> Result<Value> {
-        let stats = client.get_global_stat().await?;
-        let active = client.tell_active(None).await?;
-        let _waiting = client.tell_waiting(0, 1000, None).await?;
-        let stopped = client.tell_stopped(0, 1000, None).await?;
+        let snapshot = client.queue_snapshot(None).await?;
 
         let global_options = client.get_global_option().await?;
         let download_dir = global_options
@@ -41,7 +39,14 @@ impl McpeTool for CheckHealthTool {
         // Check disk space
         let disk_info = get_disk_info(download_dir).ok();
 
-        let report = self.analyze_health(&stats, &active, &stopped, &disk_info, download_dir);
+        let report = self.analyze_health(
+            &snapshot.global_stat,
+            &snapshot.active,
+            &snapshot.stopped,
+            &disk_info,
+            download_dir,
+            &client.trackers.injections(),
+        );
         Ok(report)
     }
 
@@ -66,76 +71,53 @@ impl McpeTool for CheckHealthTool {
 impl CheckHealthTool {
     fn analyze_health(
         &self,
-        stats: &Value,
-        active: &Value,
-        stopped: &Value,
+        stats: &GlobalStat,
+        active: &[DownloadStatus],
+        stopped: &[DownloadStatus],
         disk_info: &Option<DiskInfo>,
         download_dir: &str,
+        injections: &[TrackerInjection],
     ) -> Value {
         let mut issues = Vec::new();
         let mut recommendations = Vec::new();
 
         // Check for stalled active downloads
-        if let Some(active_arr) = active.as_array() {
-            for item in active_arr {
-                let gid = item
-                    .get("gid")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown");
-                let connections = item
-                    .get("connections")
-                    .and_then(|v| v.as_str())
-                    .and_then(|v| v.parse::<u64>().ok())
-                    .or_else(|| item.get("connections").and_then(serde_json::Value::as_u64))
-                    .unwrap_or(0);
-                let download_speed = item
-                    .get("downloadSpeed")
-                    .and_then(|v| v.as_str())
-                    .and_then(|v| v.parse::<u64>().ok())
-                    .or_else(|| {
-                        item.get("downloadSpeed")
-                            .and_then(serde_json::Value::as_u64)
-                    })
-                    .unwrap_or(0);
-
-                if connections == 0 && download_speed == 0 {
-                    issues.push(json!({
-                        "type": "stalled_download",
-                        "gid": gid,
-                        "message": format!("Download {} has 0 peers and 0 speed.", gid)
-                    }));
+        for item in active {
+            if item.connections == 0 && item.download_speed == 0 {
+                let gid = &item.gid;
+                issues.push(json!({
+                    "type": "stalled_download",
+                    "gid": gid,
+                    "message": format!("Download {} has 0 peers and 0 speed.", gid)
+                }));
+                if let Some(injection) = injections.iter().find(|i| &i.gid == gid) {
+                    recommendations.push(format!("{} trackers were already injected into download {gid}; check your network connection or whether the torrent still has seeders.", injection.trackers_added));
+                } else {
                     recommendations.push(format!("Consider adding more trackers to download {gid} or check your network connection."));
                 }
             }
         }
 
         // Check for errors in stopped downloads
-        if let Some(stopped_arr) = stopped.as_array() {
-            for item in stopped_arr {
-                let item_status = item.get("status").and_then(|v| v.as_str()).unwrap_or("");
-                if item_status == "error" {
-                    let gid = item
-                        .get("gid")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("unknown");
-                    let error_code = item
-                        .get("errorCode")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("unknown");
-                    let error_msg = item
-                        .get("errorMessage")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("No error message provided.");
-
-                    issues.push(json!({
-                        "type": "download_error",
-                        "gid": gid,
-                        "message": format!("Download {} failed with error code {}: {}", gid, error_code, error_msg)
-                    }));
-                    recommendations.push(format!(
-                        "Check the error details for download {gid} and retry if appropriate."
-                    ));
-                }
+        for item in stopped {
+            if item.status == Some(DownloadState::Error) {
+                let gid = &item.gid;
+                let error_code = item
+                    .error_code
+                    .map_or_else(|| "unknown".to_string(), |c| c.to_string());
+                let error_msg = item
+                    .error_message
+                    .as_deref()
+                    .unwrap_or("No error message provided.");
+
+                issues.push(json!({
+                    "type": "download_error",
+                    "gid": gid,
+                    "message": format!("Download {} failed with error code {}: {}", gid, error_code, error_msg)
+                }));
+                recommendations.push(format!(
+                    "Check the error details for download {gid} and retry if appropriate."
+                ));
             }
         }
 
@@ -154,11 +136,11 @@ impl CheckHealthTool {
 
         // Summary
         let summary = json!({
-            "num_active": stats.get("numActive").and_then(|v| v.as_str()).map(std::string::ToString::to_string).or_else(|| stats.get("numActive").and_then(|v| v.as_u64().map(|u| u.to_string()))),
-            "num_waiting": stats.get("numWaiting").and_then(|v| v.as_str()).map(std::string::ToString::to_string).or_else(|| stats.get("numWaiting").and_then(|v| v.as_u64().map(|u| u.to_string()))),
-            "num_stopped": stats.get("numStopped").and_then(|v| v.as_str()).map(std::string::ToString::to_string).or_else(|| stats.get("numStopped").and_then(|v| v.as_u64().map(|u| u.to_string()))),
-            "download_speed": stats.get("downloadSpeed").and_then(|v| v.as_str()).map(std::string::ToString::to_string).or_else(|| stats.get("downloadSpeed").and_then(|v| v.as_u64().map(|u| u.to_string()))),
-            "upload_speed": stats.get("uploadSpeed").and_then(|v| v.as_str()).map(std::string::ToString::to_string).or_else(|| stats.get("uploadSpeed").and_then(|v| v.as_u64().map(|u| u.to_string()))),
+            "num_active": stats.num_active,
+            "num_waiting": stats.num_waiting,
+            "num_stopped": stats.num_stopped,
+            "download_speed": stats.download_speed,
+            "upload_speed": stats.upload_speed,
             "disk_available_gb": disk_info.as_ref().map_or(0.0, |i| i.available as f64 / 1e9)
         });
 
@@ -166,19 +148,27 @@ impl CheckHealthTool {
             "summary": summary,
             "issues": issues,
             "recommendations": recommendations,
+            "tracker_injections": injections
+                .iter()
+                .map(|i| json!({
+                    "gid": i.gid,
+                    "trackers_added": i.trackers_added,
+                    "injected_at": chrono::DateTime::from_timestamp(i.injected_at, 0).map(|t| t.to_rfc3339())
+                }))
+                .collect::<Vec<_>>(),
             "status": if issues.is_empty() { "healthy" } else { "unhealthy" }
         })
     }
 }
 
 #[derive(Clone)]
-struct DiskInfo {
-    available: u64,
+pub(crate) struct DiskInfo {
+    pub(crate) available: u64,
     _total: u64,
 }
 
 #[cfg(target_os = "linux")]
-fn get_disk_info<P: AsRef<Path>>(path: P) -> Result<DiskInfo> {
+pub(crate) fn get_disk_info<P: AsRef<Path>>(path: P) -> Result<DiskInfo> {
     use std::mem;
     unsafe {
         let mut stats: libc::statvfs = mem::zeroed();
@@ -196,7 +186,7 @@ fn get_disk_info<P: AsRef<Path>>(path: P) -> Result<DiskInfo> {
 }
 
 #[cfg(not(target_os = "linux"))]
-fn get_disk_info<P: AsRef<Path>>(_path: P) -> Result<DiskInfo> {
+pub(crate) fn get_disk_info<P: AsRef<Path>>(_path: P) -> Result<DiskInfo> {
     // Fallback for non-linux systems if needed, or just return dummy info
     Ok(DiskInfo {
         available: 10 * 1024 * 1024 * 1024, // 10GB dummy
@@ -210,6 +200,10 @@ mod tests {
     use crate::aria2::Aria2Client;
     use crate::config::Config;
 
+    fn from_json<T: serde::de::DeserializeOwned>(value: Value) -> T {
+        serde_json::from_value(value).unwrap()
+    }
+
     #[tokio::test]
     async fn test_check_health_name() {
         let tool = CheckHealthTool;
@@ -227,15 +221,17 @@ mod tests {
     #[test]
     fn test_analyze_health_healthy() {
         let tool = CheckHealthTool;
-        let stats = json!({ "numActive": "0", "numWaiting": "0", "numStopped": "0", "downloadSpeed": "0", "uploadSpeed": "0" });
-        let active = json!([]);
-        let stopped = json!([]);
+        let stats: GlobalStat = from_json(
+            json!({ "numActive": "0", "numWaiting": "0", "numStopped": "0", "downloadSpeed": "0", "uploadSpeed": "0" }),
+        );
+        let active: Vec<DownloadStatus> = from_json(json!([]));
+        let stopped: Vec<DownloadStatus> = from_json(json!([]));
         let disk_info = Some(DiskInfo {
             available: 10 * 1024 * 1024 * 1024,
             _total: 100 * 1024 * 1024 * 1024,
         });
 
-        let report = tool.analyze_health(&stats, &active, &stopped, &disk_info, "/tmp");
+        let report = tool.analyze_health(&stats, &active, &stopped, &disk_info, "/tmp", &[]);
         assert_eq!(report["status"], "healthy");
         assert!(report["issues"].as_array().unwrap().is_empty());
     }
@@ -243,24 +239,43 @@ mod tests {
     #[test]
     fn test_analyze_health_stalled() {
         let tool = CheckHealthTool;
-        let stats = json!({ "numActive": "1", "numWaiting": "0", "numStopped": "0", "downloadSpeed": "0", "uploadSpeed": "0" });
-        let active = json!([{ "gid": "1", "connections": "0", "downloadSpeed": "0" }]);
-        let stopped = json!([]);
-
-        let report = tool.analyze_health(&stats, &active, &stopped, &None, "/tmp");
+        let stats: GlobalStat = from_json(
+            json!({ "numActive": "1", "numWaiting": "0", "numStopped": "0", "downloadSpeed": "0", "uploadSpeed": "0" }),
+        );
+        let active: Vec<DownloadStatus> =
+            from_json(json!([{ "gid": "1", "connections": "0", "downloadSpeed": "0" }]));
+        let stopped: Vec<DownloadStatus> = from_json(json!([]));
+
+        let report = tool.analyze_health(&stats, &active, &stopped, &None, "/tmp", &[]);
         assert_eq!(report["status"], "unhealthy");
         assert_eq!(report["issues"][0]["type"], "stalled_download");
+
+        let injections = [TrackerInjection {
+            gid: "1".to_string(),
+            trackers_added: 12,
+            injected_at: 1_700_000_000,
+        }];
+        let report = tool.analyze_health(&stats, &active, &stopped, &None, "/tmp", &injections);
+        assert_eq!(report["tracker_injections"][0]["gid"], "1");
+        assert_eq!(report["tracker_injections"][0]["trackers_added"], 12);
+        assert!(report["recommendations"][0]
+            .as_str()
+            .unwrap()
+            .contains("already injected"));
     }
 
     #[test]
     fn test_analyze_health_error() {
         let tool = CheckHealthTool;
-        let stats = json!({ "numActive": "0", "numWaiting": "0", "numStopped": "1", "downloadSpeed": "0", "uploadSpeed": "0" });
-        let active = json!([]);
-        let stopped =
-            json!([{ "gid": "2", "status": "error", "errorCode": "1", "errorMessage": "Failed" }]);
-
-        let report = tool.analyze_health(&stats, &active, &stopped, &None, "/tmp");
+        let stats: GlobalStat = from_json(
+            json!({ "numActive": "0", "numWaiting": "0", "numStopped": "1", "downloadSpeed": "0", "uploadSpeed": "0" }),
+        );
+        let active: Vec<DownloadStatus> = from_json(json!([]));
+        let stopped: Vec<DownloadStatus> = from_json(
+            json!([{ "gid": "2", "status": "error", "errorCode": "1", "errorMessage": "Failed" }]),
+        );
+
+        let report = tool.analyze_health(&stats, &active, &stopped, &None, "/tmp", &[]);
         assert_eq!(report["status"], "unhealthy");
         assert_eq!(report["issues"][0]["type"], "download_error");
     }
@@ -268,15 +283,17 @@ mod tests {
     #[test]
     fn test_analyze_health_low_disk() {
         let tool = CheckHealthTool;
-        let stats = json!({ "numActive": "0", "numWaiting": "0", "numStopped": "0", "downloadSpeed": "0", "uploadSpeed": "0" });
-        let active = json!([]);
-        let stopped = json!([]);
+        let stats: GlobalStat = from_json(
+            json!({ "numActive": "0", "numWaiting": "0", "numStopped": "0", "downloadSpeed": "0", "uploadSpeed": "0" }),
+        );
+        let active: Vec<DownloadStatus> = from_json(json!([]));
+        let stopped: Vec<DownloadStatus> = from_json(json!([]));
         let disk_info = Some(DiskInfo {
             available: 500 * 1024 * 1024,
             _total: 100 * 1024 * 1024 * 1024,
         });
 
-        let report = tool.analyze_health(&stats, &active, &stopped, &disk_info, "/tmp");
+        let report = tool.analyze_health(&stats, &active, &stopped, &disk_info, "/tmp", &[]);
         assert_eq!(report["status"], "unhealthy");
         assert_eq!(report["issues"][0]["type"], "low_disk_space");
     }
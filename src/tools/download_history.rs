@@ -0,0 +1,206 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::aria2::Aria2Client;
+use crate::history::HistoryQuery;
+use crate::tools::registry::McpeTool;
+
+pub struct DownloadHistoryTool;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadHistoryArgs {
+    /// Substring of the name, a file path or a URI
+    pub name: Option<String>,
+    /// Filter by status: complete, error, removed
+    pub status: Option<String>,
+    /// Filter by instance name
+    pub instance_name: Option<String>,
+    pub gid: Option<String>,
+    /// Finished at or after this date (RFC 3339 or YYYY-MM-DD)
+    pub since: Option<String>,
+    /// Finished at or before this date (RFC 3339 or YYYY-MM-DD)
+    pub until: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[async_trait]
+impl McpeTool for DownloadHistoryTool {
+    fn name(&self) -> String {
+        "download_history".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Search the archive of finished downloads whose results were removed from aria2, e.g. to check whether something was already fetched".to_string()
+    }
+
+    fn schema(&self) -> Result<Value> {
+        Ok(json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Substring to search for in the name, file paths or URIs (case-insensitive)"
+                },
+                "status": {
+                    "type": "string",
+                    "enum": ["complete", "error", "removed"],
+                    "description": "Filter by final status"
+                },
+                "instanceName": {
+                    "type": "string",
+                    "description": "Filter by the name of the aria2 instance"
+                },
+                "gid": {
+                    "type": "string",
+                    "description": "Filter by GID"
+                },
+                "since": {
+                    "type": "string",
+                    "description": "Only downloads finished at or after this date (RFC 3339 or YYYY-MM-DD)"
+                },
+                "until": {
+                    "type": "string",
+                    "description": "Only downloads finished at or before this date (RFC 3339 or YYYY-MM-DD, inclusive)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of entries to return, newest first (default 50)"
+                }
+            }
+        }))
+    }
+
+    async fn run(&self, client: &Aria2Client, args: Value) -> Result<Value> {
+        let args: DownloadHistoryArgs = serde_json::from_value(args)?;
+        let query = HistoryQuery {
+            name: args.name,
+            status: args.status,
+            instance: args.instance_name,
+            gid: args.gid,
+            since: args
+                .since
+                .as_deref()
+                .map(|d| parse_date(d, false))
+                .transpose()?,
+            until: args
+                .until
+                .as_deref()
+                .map(|d| parse_date(d, true))
+                .transpose()?,
+            limit: Some(args.limit.unwrap_or(50)),
+        };
+
+        let entries = client
+            .history
+            .query(&query)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to read download history: {e}"))?;
+
+        Ok(json!({
+            "count": entries.len(),
+            "downloads": entries
+        }))
+    }
+}
+
+/// Parses an RFC 3339 timestamp or a `YYYY-MM-DD` date (UTC) into Unix time.
+/// A bare date means its first second, or its last when `end_of_day` is set.
+fn parse_date(date: &str, end_of_day: bool) -> Result<i64> {
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(date) {
+        return Ok(time.timestamp());
+    }
+    let day = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .with_context(|| format!("Invalid date '{date}', expected RFC 3339 or YYYY-MM-DD"))?;
+    let time = if end_of_day {
+        day.and_hms_opt(23, 59, 59)
+    } else {
+        day.and_hms_opt(0, 0, 0)
+    }
+    .context("Invalid time of day")?;
+    Ok(time.and_utc().timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::history::{HistoryEntry, HistoryStore};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("2026-01-02", false).unwrap(), 1_767_312_000);
+        assert_eq!(parse_date("2026-01-02", true).unwrap(), 1_767_398_399);
+        assert_eq!(
+            parse_date("2026-01-02T01:00:00+01:00", false).unwrap(),
+            1_767_312_000
+        );
+        assert!(parse_date("yesterday", false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_download_history_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut client = Aria2Client::new(Config::default());
+        client.history = Arc::new(HistoryStore::new(dir.path().join("history.jsonl")));
+
+        let mut entry = HistoryEntry {
+            gid: "1".to_string(),
+            instance: "default".to_string(),
+            name: Some("ubuntu.iso".to_string()),
+            status: "complete".to_string(),
+            uris: vec![],
+            files: vec![],
+            dir: None,
+            total_length: 10,
+            completed_length: 10,
+            upload_length: 0,
+            download_speed: 0,
+            info_hash: None,
+            error_code: None,
+            error_message: None,
+            completed_at: Some(parse_date("2026-01-02", false).unwrap()),
+            archived_at: parse_date("2026-01-03", false).unwrap(),
+        };
+        client.history.append(&[entry.clone()]).await.unwrap();
+        entry.gid = "2".to_string();
+        entry.instance = "remote".to_string();
+        entry.completed_at = Some(parse_date("2026-02-01", false).unwrap());
+        client.history.append(&[entry]).await.unwrap();
+
+        let tool = DownloadHistoryTool;
+        let result = tool
+            .run(&client, json!({ "name": "ubuntu" }))
+            .await
+            .unwrap();
+        assert_eq!(result["count"], 2);
+        assert_eq!(result["downloads"][0]["gid"], "2");
+
+        let result = tool
+            .run(
+                &client,
+                json!({ "name": "ubuntu", "until": "2026-01-02", "status": "complete" }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result["count"], 1);
+        assert_eq!(result["downloads"][0]["gid"], "1");
+
+        let result = tool
+            .run(
+                &client,
+                json!({ "instanceName": "remote", "since": "2026-01-15" }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result["count"], 1);
+
+        assert!(tool
+            .run(&client, json!({ "since": "last week" }))
+            .await
+            .is_err());
+    }
+}
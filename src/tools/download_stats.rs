@@ -0,0 +1,183 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::aria2::Aria2Client;
+use crate::tools::registry::McpeTool;
+
+pub struct DownloadStatsTool;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadStatsArgs {
+    /// Time windows such as "30m", "24h" or "7d"
+    pub windows: Option<Vec<String>>,
+    /// Number of sources listed per window
+    pub top: Option<usize>,
+}
+
+#[async_trait]
+impl McpeTool for DownloadStatsTool {
+    fn name(&self) -> String {
+        "download_stats".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Report throughput over time: bytes transferred, average and peak speeds, the top sources by bytes and error rates for each time window".to_string()
+    }
+
+    fn schema(&self) -> Result<Value> {
+        Ok(json!({
+            "type": "object",
+            "properties": {
+                "windows": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Time windows to report, as a number followed by s, m, h, d or w (default [\"1h\", \"24h\", \"7d\"])"
+                },
+                "top": {
+                    "type": "integer",
+                    "description": "Number of top sources listed per window (default 5)"
+                }
+            }
+        }))
+    }
+
+    async fn run(&self, client: &Aria2Client, args: Value) -> Result<Value> {
+        let args: DownloadStatsArgs = serde_json::from_value(args)?;
+        let windows = args
+            .windows
+            .unwrap_or_else(|| vec!["1h".to_string(), "24h".to_string(), "7d".to_string()]);
+        let top = args.top.unwrap_or(5);
+
+        let stats_config = {
+            let config = client.config();
+            let config_guard = config.read().await;
+            config_guard.stats_config.clone()
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        let mut reports = Vec::new();
+        for window in &windows {
+            let window_secs = parse_window(window)?;
+            let mut report = serde_json::to_value(client.stats.summarize(now, window_secs, top))?;
+            report["window"] = json!(window);
+            reports.push(report);
+        }
+
+        let since = client
+            .stats
+            .oldest()
+            .and_then(|time| chrono::DateTime::from_timestamp(time, 0).map(|t| t.to_rfc3339()));
+
+        Ok(json!({
+            "instance": client.name,
+            "sampling": {
+                "enabled": stats_config.enabled,
+                "intervalSecs": stats_config.interval_secs,
+                "retentionSecs": stats_config.retention_secs,
+                "since": since
+            },
+            "windows": reports
+        }))
+    }
+
+    async fn run_multi(&self, clients: &[Arc<Aria2Client>], args: Value) -> Result<Value> {
+        let mut results = Vec::new();
+
+        for client in clients {
+            results.push(self.run(client, args.clone()).await?);
+        }
+
+        Ok(json!({
+            "results": results
+        }))
+    }
+}
+
+/// Parses a window such as `90s`, `30m`, `24h`, `7d` or `2w` into seconds.
+fn parse_window(window: &str) -> Result<u64> {
+    let window = window.trim();
+    let split = window
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(window.len());
+    let (amount, unit) = window.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .ok()
+        .filter(|a| *a > 0)
+        .with_context(|| format!("Invalid window '{window}', expected e.g. 24h or 7d"))?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        "w" => 604_800,
+        _ => anyhow::bail!("Invalid window unit in '{window}', expected s, m, h, d or w"),
+    };
+    Ok(amount * unit_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aria2::{GlobalStat, QueueSnapshot};
+    use crate::config::Config;
+
+    #[test]
+    fn test_parse_window() {
+        assert_eq!(parse_window("90s").unwrap(), 90);
+        assert_eq!(parse_window("30m").unwrap(), 1800);
+        assert_eq!(parse_window("24h").unwrap(), 86_400);
+        assert_eq!(parse_window("7d").unwrap(), 604_800);
+        assert_eq!(parse_window("2w").unwrap(), 1_209_600);
+        assert!(parse_window("0h").is_err());
+        assert!(parse_window("h").is_err());
+        assert!(parse_window("3y").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_download_stats_run() {
+        let client = Aria2Client::new(Config::default());
+        let snapshot = QueueSnapshot {
+            active: vec![],
+            waiting: vec![],
+            stopped: vec![],
+            global_stat: GlobalStat {
+                download_speed: 1000,
+                ..GlobalStat::default()
+            },
+        };
+        let now = chrono::Utc::now().timestamp();
+        client.stats.record(&snapshot, now - 7200, 604_800);
+        client.stats.record(&snapshot, now - 60, 604_800);
+
+        let tool = DownloadStatsTool;
+        let result = tool
+            .run(&client, json!({ "windows": ["1h", "1d"] }))
+            .await
+            .unwrap();
+        assert_eq!(result["instance"], "default");
+        assert_eq!(result["sampling"]["intervalSecs"], 60);
+        assert!(result["sampling"]["since"].is_string());
+        assert_eq!(result["windows"][0]["window"], "1h");
+        assert_eq!(result["windows"][0]["samples"], 1);
+        assert_eq!(result["windows"][1]["samples"], 2);
+        assert_eq!(result["windows"][1]["avgDownloadSpeed"], 1000);
+        assert!(result["windows"][1]["errorRate"].is_null());
+
+        let result = tool
+            .run_multi(&[Arc::new(client)], json!({}))
+            .await
+            .unwrap();
+        assert_eq!(result["results"][0]["windows"].as_array().unwrap().len(), 3);
+
+        let client = Aria2Client::new(Config::default());
+        assert!(tool
+            .run(&client, json!({ "windows": ["soon"] }))
+            .await
+            .is_err());
+    }
+}
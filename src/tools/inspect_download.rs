@@ -18,7 +18,8 @@ pub struct InspectDownloadParams {
 #[derive(Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum InspectAction {
-    /// Get detailed state, progress, and error info for a GID.
+    /// Get detailed state, progress, and error info for a GID, and the GIDs it
+    /// had across automatic retries.
     Status,
     /// List all files in a task (useful for torrents).
     Files,
@@ -51,8 +52,24 @@ impl McpeTool for InspectDownloadTool {
         let params: InspectDownloadParams = serde_json::from_value(params)?;
         match params.action {
             InspectAction::Status => {
-                let status = client.tell_status(&params.gid).await?;
-                Ok(json!({ "status": status }))
+                let retries = client.lineage.chain(&params.gid);
+                let status = match client.tell_status(&params.gid).await {
+                    Ok(status) => status,
+                    Err(e) => {
+                        return Err(match retries.last() {
+                            Some(latest) => e.context(format!(
+                                "Download {} was retried as {}",
+                                params.gid, latest.to
+                            )),
+                            None => e,
+                        })
+                    }
+                };
+                if retries.is_empty() {
+                    Ok(json!({ "status": status }))
+                } else {
+                    Ok(json!({ "status": status, "retries": retries }))
+                }
             }
             InspectAction::Files => {
                 let files = client.get_files(&params.gid).await?;
@@ -86,4 +103,20 @@ mod tests {
         let result = tool.run(&client, args).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_inspect_download_status_follows_retries() {
+        let tool = InspectDownloadTool;
+        let client = Aria2Client::new(Config {
+            rpc_url: "http://127.0.0.1:1/jsonrpc".to_string(),
+            ..Config::default()
+        });
+        client.lineage.record("old", "new", 1, 1_700_000_000, None);
+
+        let err = tool
+            .run(&client, json!({ "gid": "old", "action": "status" }))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("retried as new"));
+    }
 }
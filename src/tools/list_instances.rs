@@ -0,0 +1,151 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::aria2::{Aria2Client, ConnectionStatus};
+use crate::tools::registry::McpeTool;
+
+pub struct ListInstancesTool;
+
+#[async_trait]
+impl McpeTool for ListInstancesTool {
+    fn name(&self) -> String {
+        "list_instances".to_string()
+    }
+
+    fn description(&self) -> String {
+        "List the configured aria2 instances with their index, whether they are reachable, their version and the state of their notification connection".to_string()
+    }
+
+    fn schema(&self) -> Result<Value> {
+        Ok(json!({
+            "type": "object",
+            "properties": {}
+        }))
+    }
+
+    async fn run(&self, client: &Aria2Client, _args: Value) -> Result<Value> {
+        Ok(json!({
+            "instances": [describe(None, client).await]
+        }))
+    }
+
+    async fn run_multi(&self, clients: &[Arc<Aria2Client>], _args: Value) -> Result<Value> {
+        let instances = futures_util::future::join_all(
+            clients
+                .iter()
+                .enumerate()
+                .map(|(index, client)| describe(Some(index), client)),
+        )
+        .await;
+
+        Ok(json!({
+            "instances": instances
+        }))
+    }
+}
+
+async fn describe(index: Option<usize>, client: &Aria2Client) -> Value {
+    let (rpc_url, rpc_transport) = {
+        let config = client.config();
+        let config_guard = config.read().await;
+        (config_guard.rpc_url.clone(), config_guard.rpc_transport)
+    };
+
+    let status = InstanceStatus::probe(client).await;
+    let mut description = json!(status);
+    if let Some(error) = status.error {
+        description["error"] = json!(error);
+    }
+    description["rpcUrl"] = json!(rpc_url);
+    description["rpcTransport"] = json!(rpc_transport);
+    if let Some(index) = index {
+        description["index"] = json!(index);
+    }
+    description
+}
+
+/// Whether an instance's aria2 answers `getVersion`, and the state of its
+/// WebSocket connection.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceStatus {
+    pub name: String,
+    pub reachable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Why aria2 could not be reached. Not serialized, as it may name the
+    /// RPC URL and `/readyz` is served without authentication.
+    #[serde(skip)]
+    pub error: Option<String>,
+    pub notifications: ConnectionStatus,
+}
+
+impl InstanceStatus {
+    pub async fn probe(client: &Aria2Client) -> Self {
+        let (version, error) = match client.get_version().await {
+            Ok(version) => (Some(version), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        Self {
+            name: client.name.clone(),
+            reachable: version.is_some(),
+            version,
+            error,
+            notifications: client.connection_status(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_list_instances_run_multi() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_string_contains("aria2.getVersion"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": { "version": "1.37.0", "enabledFeatures": [] }
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let online = Aria2Client::new(Config {
+            rpc_url: format!("{}/jsonrpc", mock_server.uri()),
+            rpc_secret: Some("secret".to_string()),
+            ..Config::default()
+        });
+        let mut offline = Aria2Client::new(Config {
+            rpc_url: "http://127.0.0.1:1/jsonrpc".to_string(),
+            ..Config::default()
+        });
+        offline.name = "offline".to_string();
+
+        let result = ListInstancesTool
+            .run_multi(&[Arc::new(online), Arc::new(offline)], json!({}))
+            .await
+            .unwrap();
+        let instances = result["instances"].as_array().unwrap();
+        assert_eq!(instances.len(), 2);
+        assert_eq!(instances[0]["index"], 0);
+        assert_eq!(instances[0]["name"], "default");
+        assert_eq!(instances[0]["reachable"], true);
+        assert_eq!(instances[0]["version"], "1.37.0");
+        assert_eq!(instances[0]["rpcTransport"], "http");
+        assert_eq!(instances[0]["notifications"]["state"], "idle");
+        assert!(!result.to_string().contains("secret"));
+        assert_eq!(instances[1]["name"], "offline");
+        assert_eq!(instances[1]["reachable"], false);
+        assert!(instances[1]["error"].is_string());
+    }
+}
@@ -0,0 +1,181 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::aria2::Aria2Client;
+use crate::tools::registry::McpeTool;
+
+pub struct ManageAria2ProcessTool;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManageAria2ProcessArgs {
+    /// Action to perform: sessionInfo, saveSession, shutdown, forceShutdown
+    pub action: String,
+    /// Must be true for shutdown and forceShutdown
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+#[async_trait]
+impl McpeTool for ManageAria2ProcessTool {
+    fn name(&self) -> String {
+        "manage_aria2_process".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Manage the aria2 process itself: show session info, save the session now, or shut aria2 down gracefully or forcibly (requires confirm)".to_string()
+    }
+
+    fn schema(&self) -> Result<Value> {
+        Ok(json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["sessionInfo", "saveSession", "shutdown", "forceShutdown"],
+                    "description": "Action to perform"
+                },
+                "confirm": {
+                    "type": "boolean",
+                    "description": "Set to true to confirm shutdown or forceShutdown. aria2 stops and must be restarted outside this server."
+                }
+            },
+            "required": ["action"]
+        }))
+    }
+
+    async fn run(&self, client: &Aria2Client, args: Value) -> Result<Value> {
+        let args: ManageAria2ProcessArgs = serde_json::from_value(args)?;
+
+        match args.action.as_str() {
+            "sessionInfo" => {
+                let session = client.session_info().await?;
+                let version = client.version_info().await?;
+                Ok(json!({
+                    "instance": client.name,
+                    "sessionId": session.session_id,
+                    "version": version.version,
+                    "enabledFeatures": version.enabled_features
+                }))
+            }
+            "saveSession" => {
+                client.save_session().await?;
+                Ok(json!({ "status": "saved", "instance": client.name }))
+            }
+            action @ ("shutdown" | "forceShutdown") => {
+                if !args.confirm {
+                    return Ok(json!({
+                        "status": "confirmation_required",
+                        "instance": client.name,
+                        "message": format!(
+                            "This stops aria2 on instance '{}' and it will not be restarted by this server. Call again with confirm: true to proceed.",
+                            client.name
+                        )
+                    }));
+                }
+                if action == "shutdown" {
+                    client.shutdown().await?;
+                } else {
+                    client.force_shutdown().await?;
+                }
+                log::warn!("aria2 instance {} was shut down ({action})", client.name);
+                Ok(json!({
+                    "status": "shutting_down",
+                    "instance": client.name,
+                    "forced": action == "forceShutdown"
+                }))
+            }
+            _ => Err(anyhow::anyhow!("Unknown action: {}", args.action)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn mock_method(server: &MockServer, rpc_method: &str, result: Value, calls: u64) {
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_partial_json(json!({ "method": rpc_method })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": result
+            })))
+            .expect(calls)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_manage_aria2_process_actions() {
+        let mock_server = MockServer::start().await;
+        mock_method(
+            &mock_server,
+            "aria2.getSessionInfo",
+            json!({ "sessionId": "cd6a3bc6a1de28eb5bfa181e5f6b916d44af31a9" }),
+            1,
+        )
+        .await;
+        mock_method(
+            &mock_server,
+            "aria2.getVersion",
+            json!({ "version": "1.37.0", "enabledFeatures": ["BitTorrent"] }),
+            1,
+        )
+        .await;
+        mock_method(&mock_server, "aria2.saveSession", json!("OK"), 1).await;
+        mock_method(&mock_server, "aria2.shutdown", json!("OK"), 0).await;
+        mock_method(&mock_server, "aria2.forceShutdown", json!("OK"), 1).await;
+
+        let client = Aria2Client::new(Config {
+            rpc_url: format!("{}/jsonrpc", mock_server.uri()),
+            ..Config::default()
+        });
+        let tool = ManageAria2ProcessTool;
+
+        let result = tool
+            .run(&client, json!({ "action": "sessionInfo" }))
+            .await
+            .unwrap();
+        assert_eq!(
+            result["sessionId"],
+            "cd6a3bc6a1de28eb5bfa181e5f6b916d44af31a9"
+        );
+        assert_eq!(result["enabledFeatures"], json!(["BitTorrent"]));
+
+        let result = tool
+            .run(&client, json!({ "action": "saveSession" }))
+            .await
+            .unwrap();
+        assert_eq!(result["status"], "saved");
+
+        // Without confirmation nothing is sent to aria2
+        let result = tool
+            .run(&client, json!({ "action": "shutdown" }))
+            .await
+            .unwrap();
+        assert_eq!(result["status"], "confirmation_required");
+
+        let result = tool
+            .run(
+                &client,
+                json!({ "action": "forceShutdown", "confirm": true }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result["status"], "shutting_down");
+        assert_eq!(result["forced"], true);
+
+        assert!(tool
+            .run(&client, json!({ "action": "restart" }))
+            .await
+            .is_err());
+    }
+}
@@ -1,9 +1,10 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use base64::prelude::*;
 use serde::Deserialize;
 use serde_json::{json, Value};
 
-use crate::aria2::Aria2Client;
+use crate::aria2::{Aria2Client, UriStatus};
 use crate::tools::registry::McpeTool;
 
 pub struct ManageDownloadsTool;
@@ -11,13 +12,17 @@ pub struct ManageDownloadsTool;
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ManageDownloadsArgs {
-    /// Action to perform: add, pause, resume, remove, forcePause, forceRemove, move
+    /// Action to perform: add, addTorrent, addMetalink, pause, resume, remove, forcePause, forceRemove, move, changeUri, replaceUri
     pub action: String,
-    /// GID of the download (required for all actions except 'add')
+    /// GID of the download (required for all actions except the add actions)
     pub gid: Option<String>,
-    /// URIs to add (required for 'add')
+    /// URIs to add (required for 'add', web-seed URIs for 'addTorrent')
     pub uris: Option<Vec<String>>,
-    /// New position (required for 'move')
+    /// Path of a .torrent or .metalink file relative to the download directory
+    pub path: Option<String>,
+    /// Inline base64-encoded .torrent or .metalink content
+    pub data: Option<String>,
+    /// New position (required for 'move'), or where to insert URIs for 'changeUri'
     pub pos: Option<i32>,
     /// How to move: `POS_SET`, `POS_CUR`, `POS_END` (required for 'move')
     pub how: Option<String>,
@@ -25,6 +30,12 @@ pub struct ManageDownloadsArgs {
     pub options: Option<Value>,
     /// Whether to download sequentially (for `BitTorrent`)
     pub sequential: Option<bool>,
+    /// 1-based index of the file whose URIs are changed (default 1)
+    pub file_index: Option<u64>,
+    /// URIs to remove from the file (for 'changeUri'/'replaceUri')
+    pub del_uris: Option<Vec<String>>,
+    /// URIs to add to the file (for 'changeUri'/'replaceUri')
+    pub add_uris: Option<Vec<String>>,
 }
 
 #[async_trait]
@@ -34,7 +45,7 @@ impl McpeTool for ManageDownloadsTool {
     }
 
     fn description(&self) -> String {
-        "Monitor and manage aria2 downloads: add, pause, resume, remove, force-pause, force-remove, move".to_string()
+        "Monitor and manage aria2 downloads: add (URIs, torrent or metalink), pause, resume, remove, force-pause, force-remove, move, and change or replace the URIs of a running download".to_string()
     }
 
     fn schema(&self) -> Result<Value> {
@@ -43,7 +54,7 @@ impl McpeTool for ManageDownloadsTool {
             "properties": {
                 "action": {
                     "type": "string",
-                    "enum": ["add", "pause", "resume", "remove", "forcePause", "forceRemove", "move"],
+                    "enum": ["add", "addTorrent", "addMetalink", "pause", "resume", "remove", "forcePause", "forceRemove", "move", "changeUri", "replaceUri"],
                     "description": "Action to perform"
                 },
                 "gid": {
@@ -53,11 +64,19 @@ impl McpeTool for ManageDownloadsTool {
                 "uris": {
                     "type": "array",
                     "items": { "type": "string" },
-                    "description": "URIs to add (for action='add')"
+                    "description": "URIs to add (for action='add') or web-seed URIs (for action='addTorrent')"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Path of the .torrent/.metalink file relative to the download directory (for action='addTorrent'/'addMetalink')"
+                },
+                "data": {
+                    "type": "string",
+                    "description": "Base64-encoded .torrent/.metalink content, used instead of 'path' (for action='addTorrent'/'addMetalink')"
                 },
                 "pos": {
                     "type": "integer",
-                    "description": "New position (for action='move')"
+                    "description": "New position (for action='move'), or the 0-based position in the file's waiting URIs where 'addUris' are inserted (for action='changeUri', default: append)"
                 },
                 "how": {
                     "type": "string",
@@ -66,11 +85,25 @@ impl McpeTool for ManageDownloadsTool {
                 },
                 "options": {
                     "type": "object",
-                    "description": "Options for the added download (for action='add')"
+                    "description": "Options for the added download (for the add actions)"
                 },
                 "sequential": {
                     "type": "boolean",
-                    "description": "Whether to download sequentially (for action='add', BitTorrent only)"
+                    "description": "Whether to download sequentially (for action='add'/'addTorrent', BitTorrent only)"
+                },
+                "fileIndex": {
+                    "type": "integer",
+                    "description": "1-based index of the file whose URIs are changed (for action='changeUri'/'replaceUri', default 1)"
+                },
+                "delUris": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "URIs to remove, e.g. dead mirrors (for action='changeUri'/'replaceUri')"
+                },
+                "addUris": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "URIs to add (for action='changeUri'/'replaceUri'). With 'replaceUri' they take the place of the first removed URI."
                 }
             },
             "required": ["action"]
@@ -96,6 +129,31 @@ impl McpeTool for ManageDownloadsTool {
                 let gid = client.add_uri(uris, Some(options)).await?;
                 Ok(json!({ "gid": gid }))
             }
+            "addTorrent" => {
+                let torrent = load_payload(client, args.path, args.data, "addTorrent").await?;
+
+                let mut options = args.options.unwrap_or(json!({}));
+                if let Some(sequential) = args.sequential {
+                    if let Some(obj) = options.as_object_mut() {
+                        obj.insert("bt-sequential".to_string(), json!(sequential.to_string()));
+                    }
+                }
+
+                let gid = client
+                    .add_torrent(&torrent, args.uris.unwrap_or_default(), Some(options))
+                    .await?;
+                Ok(json!({ "gids": [gid] }))
+            }
+            "addMetalink" => {
+                if args.uris.is_some() {
+                    return Err(anyhow::anyhow!(
+                        "'uris' is not supported for action 'addMetalink'; mirrors come from the metalink itself"
+                    ));
+                }
+                let metalink = load_payload(client, args.path, args.data, "addMetalink").await?;
+                let gids = client.add_metalink(&metalink, args.options).await?;
+                Ok(json!({ "gids": gids }))
+            }
             "pause" => {
                 let gid = args
                     .gid
@@ -144,11 +202,129 @@ impl McpeTool for ManageDownloadsTool {
                 let new_pos = client.move_position(&gid, pos, &how).await?;
                 Ok(json!({ "newPosition": new_pos, "gid": gid }))
             }
+            "changeUri" => {
+                let gid = args
+                    .gid
+                    .ok_or_else(|| anyhow::anyhow!("'gid' is required for action 'changeUri'"))?;
+                let del_uris = args.del_uris.unwrap_or_default();
+                let add_uris = args.add_uris.unwrap_or_default();
+                if del_uris.is_empty() && add_uris.is_empty() {
+                    return Err(anyhow::anyhow!(
+                        "'delUris' or 'addUris' is required for action 'changeUri'"
+                    ));
+                }
+                let position = args
+                    .pos
+                    .map(|p| {
+                        u64::try_from(p).map_err(|_| anyhow::anyhow!("'pos' must not be negative"))
+                    })
+                    .transpose()?;
+                let file_index = args.file_index.unwrap_or(1);
+                let (deleted, added) = client
+                    .change_uri(&gid, file_index, &del_uris, &add_uris, position)
+                    .await?;
+                Ok(
+                    json!({ "gid": gid, "fileIndex": file_index, "deleted": deleted, "added": added }),
+                )
+            }
+            "replaceUri" => {
+                let gid = args
+                    .gid
+                    .ok_or_else(|| anyhow::anyhow!("'gid' is required for action 'replaceUri'"))?;
+                let del_uris = args.del_uris.filter(|u| !u.is_empty()).ok_or_else(|| {
+                    anyhow::anyhow!("'delUris' is required for action 'replaceUri'")
+                })?;
+                let add_uris = args.add_uris.filter(|u| !u.is_empty()).ok_or_else(|| {
+                    anyhow::anyhow!("'addUris' is required for action 'replaceUri'")
+                })?;
+                let file_index = args.file_index.unwrap_or(1);
+
+                // Insert the new URIs where the first dead one is waiting
+                let files = client.download_files(&gid).await?;
+                let file = files
+                    .iter()
+                    .find(|f| f.index == file_index)
+                    .ok_or_else(|| anyhow::anyhow!("Download {gid} has no file {file_index}"))?;
+                let waiting: Vec<&str> = file
+                    .uris
+                    .iter()
+                    .filter(|u| u.status == Some(UriStatus::Waiting))
+                    .map(|u| u.uri.as_str())
+                    .collect();
+                let position = replacement_position(&waiting, &del_uris);
+
+                let (deleted, added) = client
+                    .change_uri(&gid, file_index, &del_uris, &add_uris, position)
+                    .await?;
+                Ok(json!({
+                    "gid": gid,
+                    "fileIndex": file_index,
+                    "deleted": deleted,
+                    "added": added,
+                    "position": position
+                }))
+            }
             _ => Err(anyhow::anyhow!("Unknown action: {}", args.action)),
         }
     }
 }
 
+/// Where `changeUri` should insert URIs replacing `del_uris[0]`: its index
+/// in the waiting list once every URI in `del_uris` is removed, since aria2
+/// applies the position after the removal. Like aria2, each entry of
+/// `del_uris` removes one occurrence, the earliest.
+fn replacement_position(waiting: &[&str], del_uris: &[String]) -> Option<u64> {
+    let mut to_delete: Vec<&str> = del_uris.iter().map(String::as_str).collect();
+    let mut kept = 0;
+    for uri in waiting {
+        match to_delete.iter().position(|d| d == uri) {
+            Some(i) => {
+                if *uri == del_uris[0] {
+                    return Some(kept);
+                }
+                to_delete.swap_remove(i);
+            }
+            None => kept += 1,
+        }
+    }
+    None
+}
+
+/// Reads a torrent/metalink payload either from a file inside the download
+/// directory sandbox or from inline base64 data.
+async fn load_payload(
+    client: &Aria2Client,
+    path: Option<String>,
+    data: Option<String>,
+    action: &str,
+) -> Result<Vec<u8>> {
+    match (path, data) {
+        (Some(_), Some(_)) => Err(anyhow::anyhow!(
+            "Only one of 'path' or 'data' may be provided for action '{action}'"
+        )),
+        (None, Some(data)) => BASE64_STANDARD
+            .decode(data.trim())
+            .map_err(|e| anyhow::anyhow!("Invalid base64 in 'data': {e}")),
+        (Some(path), None) => {
+            let global_options = client.get_global_option().await?;
+            let dir_str = global_options
+                .get("dir")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| anyhow::anyhow!("Failed to get 'dir' option from aria2"))?;
+            read_sandboxed_file(std::path::Path::new(dir_str), &path).await
+        }
+        (None, None) => Err(anyhow::anyhow!(
+            "'path' or 'data' is required for action '{action}'"
+        )),
+    }
+}
+
+async fn read_sandboxed_file(base_dir: &std::path::Path, path: &str) -> Result<Vec<u8>> {
+    let sandbox = crate::tools::sandbox::PathSandbox::new(base_dir.to_path_buf());
+    let resolved = sandbox.resolve(path)?;
+    Ok(tokio::fs::read(&resolved).await?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -207,4 +383,294 @@ mod tests {
         let err_msg = result.unwrap_err().to_string();
         assert!(err_msg.contains("error sending request") || err_msg.contains("ConnectError"));
     }
+
+    #[tokio::test]
+    async fn test_manage_downloads_add_torrent_missing_source() {
+        let tool = ManageDownloadsTool;
+        let client = Aria2Client::new(Config::default());
+        let args = json!({ "action": "addTorrent" });
+        let result = tool.run(&client, args).await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("'path' or 'data' is required"));
+    }
+
+    #[tokio::test]
+    async fn test_manage_downloads_add_metalink_rejects_uris() {
+        let tool = ManageDownloadsTool;
+        let client = Aria2Client::new(Config::default());
+        let args = json!({
+            "action": "addMetalink",
+            "data": "bWV0YWxpbms=",
+            "uris": ["http://example.com"]
+        });
+        let result = tool.run(&client, args).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_manage_downloads_add_torrent_invalid_base64() {
+        let tool = ManageDownloadsTool;
+        let client = Aria2Client::new(Config::default());
+        let args = json!({ "action": "addTorrent", "data": "not base64!" });
+        let result = tool.run(&client, args).await;
+        assert!(result.unwrap_err().to_string().contains("Invalid base64"));
+    }
+
+    #[tokio::test]
+    async fn test_read_sandboxed_file() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        std::fs::write(temp_dir.path().join("file.torrent"), b"torrent")?;
+
+        let content = read_sandboxed_file(temp_dir.path(), "file.torrent").await?;
+        assert_eq!(content, b"torrent");
+
+        assert!(read_sandboxed_file(temp_dir.path(), "../file.torrent")
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_manage_downloads_add_torrent_and_metalink_mock() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let config = Config {
+            rpc_url: format!("{}/jsonrpc", mock_server.uri()),
+            ..Config::default()
+        };
+        let client = Aria2Client::new(config);
+
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_partial_json(json!({ "method": "aria2.addTorrent" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": "torrentgid"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_partial_json(json!({ "method": "aria2.addMetalink" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": ["gid1", "gid2"]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let tool = ManageDownloadsTool;
+        let result = tool
+            .run(
+                &client,
+                json!({
+                    "action": "addTorrent",
+                    "data": "dG9ycmVudA==",
+                    "uris": ["http://seed.example.com/file"],
+                    "options": { "dir": "/downloads" }
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result["gids"], json!(["torrentgid"]));
+
+        let result = tool
+            .run(
+                &client,
+                json!({ "action": "addMetalink", "data": "bWV0YWxpbms=" }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result["gids"], json!(["gid1", "gid2"]));
+    }
+
+    #[tokio::test]
+    async fn test_manage_downloads_change_uri_validation() {
+        let tool = ManageDownloadsTool;
+        let client = Aria2Client::new(Config::default());
+        let result = tool
+            .run(&client, json!({ "action": "changeUri", "gid": "1" }))
+            .await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("'delUris' or 'addUris'"));
+
+        let result = tool
+            .run(
+                &client,
+                json!({ "action": "changeUri", "gid": "1", "addUris": ["http://a"], "pos": -1 }),
+            )
+            .await;
+        assert!(result.is_err());
+
+        let result = tool
+            .run(
+                &client,
+                json!({ "action": "replaceUri", "gid": "1", "delUris": ["http://a"] }),
+            )
+            .await;
+        assert!(result.unwrap_err().to_string().contains("'addUris'"));
+    }
+
+    #[tokio::test]
+    async fn test_manage_downloads_replace_uri_mock() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let client = Aria2Client::new(Config {
+            rpc_url: format!("{}/jsonrpc", mock_server.uri()),
+            ..Config::default()
+        });
+
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_partial_json(json!({ "method": "aria2.getFiles" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": [{
+                    "index": "1",
+                    "path": "/downloads/file.iso",
+                    "uris": [
+                        { "uri": "http://mirror1/file.iso", "status": "used" },
+                        { "uri": "http://mirror2/file.iso", "status": "waiting" },
+                        { "uri": "http://dead/file.iso", "status": "waiting" }
+                    ]
+                }]
+            })))
+            .mount(&mock_server)
+            .await;
+        // The new mirror takes the dead one's place in the waiting list
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_partial_json(json!({
+                "method": "aria2.changeUri",
+                "params": ["1", 1, ["http://dead/file.iso"], ["http://mirror3/file.iso"], 1]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": [1, 1]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = ManageDownloadsTool
+            .run(
+                &client,
+                json!({
+                    "action": "replaceUri",
+                    "gid": "1",
+                    "delUris": ["http://dead/file.iso"],
+                    "addUris": ["http://mirror3/file.iso"]
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result["deleted"], 1);
+        assert_eq!(result["added"], 1);
+        assert_eq!(result["position"], 1);
+
+        assert!(ManageDownloadsTool
+            .run(
+                &client,
+                json!({
+                    "action": "replaceUri",
+                    "gid": "1",
+                    "fileIndex": 2,
+                    "delUris": ["http://dead/file.iso"],
+                    "addUris": ["http://mirror3/file.iso"]
+                }),
+            )
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_replacement_position() {
+        let del = |uris: &[&str]| uris.iter().map(ToString::to_string).collect::<Vec<_>>();
+        let waiting = ["dead1", "good", "dead2", "other"];
+        assert_eq!(replacement_position(&waiting, &del(&["dead1"])), Some(0));
+        assert_eq!(replacement_position(&waiting, &del(&["dead2"])), Some(2));
+        // dead1 is removed too, so dead2's place is right after good
+        assert_eq!(
+            replacement_position(&waiting, &del(&["dead2", "dead1"])),
+            Some(1)
+        );
+        assert_eq!(replacement_position(&waiting, &del(&["missing"])), None);
+        // A repeated URI only loses as many occurrences as listed
+        let waiting = ["dup", "good", "dup"];
+        assert_eq!(replacement_position(&waiting, &del(&["dup"])), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_manage_downloads_replace_two_dead_mirrors() {
+        use wiremock::matchers::{body_partial_json, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        let client = Aria2Client::new(Config::new(mock_server.uri(), None));
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "method": "aria2.getFiles" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": [{
+                    "index": "1",
+                    "path": "/downloads/file.iso",
+                    "uris": [
+                        { "uri": "http://dead1/file.iso", "status": "waiting" },
+                        { "uri": "http://mirror/file.iso", "status": "waiting" },
+                        { "uri": "http://dead2/file.iso", "status": "waiting" }
+                    ]
+                }]
+            })))
+            .mount(&mock_server)
+            .await;
+        // After both dead mirrors go, dead2's place is index 1
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({
+                "method": "aria2.changeUri",
+                "params": [
+                    "1",
+                    1,
+                    ["http://dead2/file.iso", "http://dead1/file.iso"],
+                    ["http://new/file.iso"],
+                    1
+                ]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": [2, 1]
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = ManageDownloadsTool
+            .run(
+                &client,
+                json!({
+                    "action": "replaceUri",
+                    "gid": "1",
+                    "delUris": ["http://dead2/file.iso", "http://dead1/file.iso"],
+                    "addUris": ["http://new/file.iso"]
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result["deleted"], 2);
+        assert_eq!(result["position"], 1);
+    }
 }
@@ -0,0 +1,289 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::aria2::recovery::RecoveryManager;
+use crate::aria2::{Aria2Client, DownloadState};
+use crate::metrics::InstanceCounters;
+use crate::tools::registry::McpeTool;
+
+pub struct ManageRetriesTool;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManageRetriesArgs {
+    /// Action to perform: status, reset, cancel, retryNow
+    #[serde(default = "default_action")]
+    pub action: String,
+    /// Limits status to one download. Required by the other actions
+    pub gid: Option<String>,
+}
+
+fn default_action() -> String {
+    "status".to_string()
+}
+
+#[async_trait]
+impl McpeTool for ManageRetriesTool {
+    fn name(&self) -> String {
+        "manage_retries".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Show and control how the recovery task handles failed downloads: the retries scheduled so far, the strategy and when the next retry runs, whether recovery gave up, and the GIDs each download had across retries. Reset a download's retry budget, cancel its retries, or retry it now".to_string()
+    }
+
+    fn schema(&self) -> Result<Value> {
+        Ok(json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["status", "reset", "cancel", "retryNow"],
+                    "description": "status: show retry state (default). reset: forget the retries so far, giving the full budget again. cancel: stop retrying the download. retryNow: retry a failed download immediately on this instance, with its recorded strategy."
+                },
+                "gid": {
+                    "type": "string",
+                    "description": "GID of one download, or any GID it had across retries. Omit with status to list every tracked download."
+                }
+            }
+        }))
+    }
+
+    async fn run(&self, client: &Aria2Client, args: Value) -> Result<Value> {
+        let args: ManageRetriesArgs = serde_json::from_value(args)?;
+
+        match args.action.as_str() {
+            "status" => match args.gid {
+                Some(gid) => {
+                    let lineage = client.lineage.chain(&gid);
+                    let latest = lineage.last().map_or(gid.clone(), |l| l.to.clone());
+                    Ok(json!({
+                        "instance": client.name,
+                        "gid": gid,
+                        "latestGid": latest,
+                        "state": client.retries.get(&latest).or_else(|| client.retries.get(&gid)),
+                        "lineage": lineage
+                    }))
+                }
+                None => {
+                    let downloads: Vec<Value> = client
+                        .retries
+                        .all()
+                        .into_iter()
+                        .map(|(gid, state)| {
+                            json!({
+                                "gid": gid,
+                                "state": state,
+                                "lineage": client.lineage.chain(&gid)
+                            })
+                        })
+                        .collect();
+                    Ok(json!({
+                        "instance": client.name,
+                        "downloads": downloads
+                    }))
+                }
+            },
+            action @ ("reset" | "cancel" | "retryNow") => {
+                let gid = args
+                    .gid
+                    .ok_or_else(|| anyhow::anyhow!("gid is required for {action}"))?;
+                let gid = client
+                    .lineage
+                    .chain(&gid)
+                    .last()
+                    .map_or(gid, |l| l.to.clone());
+
+                let result = match action {
+                    "reset" => {
+                        client.retries.remove(&gid);
+                        json!({ "status": "reset", "instance": client.name, "gid": gid })
+                    }
+                    "cancel" => {
+                        let state = client.retries.cancel(&gid);
+                        json!({ "status": "cancelled", "instance": client.name, "gid": gid, "state": state })
+                    }
+                    _ => {
+                        let status = client.download_status(&gid).await?;
+                        if status.status != Some(DownloadState::Error) {
+                            anyhow::bail!("Download {gid} has not failed and cannot be retried");
+                        }
+                        let retry_config = {
+                            let config = client.config();
+                            let config_guard = config.read().await;
+                            config_guard.retry_config.clone()
+                        };
+                        // Replaces any pending retry, so its timer does nothing
+                        client.retries.set_due(&gid, chrono::Utc::now().timestamp());
+                        let new_gid = RecoveryManager::new(retry_config)
+                            .perform_retry(client, &gid)
+                            .await?;
+                        InstanceCounters::add(&client.counters.recoveries, 1);
+                        json!({
+                            "status": "retried",
+                            "instance": client.name,
+                            "gid": gid,
+                            "newGid": new_gid
+                        })
+                    }
+                };
+                client.save_state().await?;
+                Ok(result)
+            }
+            _ => Err(anyhow::anyhow!("Unknown action: {}", args.action)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aria2::recovery::{RecoveryStrategy, RetryState};
+    use crate::config::Config;
+    use std::sync::Arc;
+    use wiremock::matchers::{body_partial_json, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client_with_state(dir: &tempfile::TempDir, rpc_url: String) -> Aria2Client {
+        let mut client = Aria2Client::new(Config::new(rpc_url, None));
+        client.state_manager = Arc::new(crate::state::StateManager::new(
+            dir.path().join("state.json"),
+        ));
+        client
+    }
+
+    async fn mock_method(server: &MockServer, rpc_method: &str, result: Value) {
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "method": rpc_method })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": result
+            })))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_manage_retries_status() {
+        let client = Aria2Client::new(Config::default());
+        client.lineage.record("a", "b", 1, 100, None);
+        client.retries.insert(
+            "b",
+            RetryState {
+                attempts: 2,
+                strategy: Some(RecoveryStrategy::Backoff),
+                due_at: Some(200),
+                ..Default::default()
+            },
+        );
+
+        let result = ManageRetriesTool
+            .run(&client, json!({ "gid": "a" }))
+            .await
+            .unwrap();
+        assert_eq!(result["latestGid"], "b");
+        assert_eq!(result["state"]["attempts"], 2);
+        assert_eq!(result["state"]["strategy"], "backoff");
+        assert_eq!(result["lineage"][0]["to"], "b");
+
+        let result = ManageRetriesTool.run(&client, json!({})).await.unwrap();
+        assert_eq!(result["downloads"].as_array().unwrap().len(), 1);
+        assert_eq!(result["downloads"][0]["gid"], "b");
+        assert_eq!(result["downloads"][0]["state"]["dueAt"], 200);
+
+        assert!(ManageRetriesTool
+            .run(&client, json!({ "action": "explode" }))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_manage_retries_cancel_and_reset_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_state(&dir, "http://127.0.0.1:1/jsonrpc".to_string());
+        client.retries.insert(
+            "gid1",
+            RetryState {
+                attempts: 1,
+                due_at: Some(1_700_000_000),
+                ..Default::default()
+            },
+        );
+
+        let result = ManageRetriesTool
+            .run(&client, json!({ "action": "cancel", "gid": "gid1" }))
+            .await
+            .unwrap();
+        assert_eq!(result["state"]["gaveUp"], true);
+        assert!(!client.retries.is_due("gid1", 1_700_000_000));
+
+        let restarted = client_with_state(&dir, "http://127.0.0.1:1/jsonrpc".to_string());
+        restarted.restore_recovery().await.unwrap();
+        let state = restarted.retries.get("gid1").unwrap();
+        assert_eq!(state.attempts, 1);
+        assert!(state.gave_up);
+
+        ManageRetriesTool
+            .run(&client, json!({ "action": "reset", "gid": "gid1" }))
+            .await
+            .unwrap();
+        assert_eq!(client.retries.get("gid1"), None);
+
+        assert!(ManageRetriesTool
+            .run(&client, json!({ "action": "reset" }))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_manage_retries_retry_now() {
+        let mock_server = MockServer::start().await;
+        mock_method(
+            &mock_server,
+            "aria2.tellStatus",
+            json!({
+                "gid": "failed",
+                "status": "error",
+                "files": [{ "index": "1", "uris": [{ "uri": "http://example.com/a.iso", "status": "used" }] }]
+            }),
+        )
+        .await;
+        mock_method(&mock_server, "aria2.getOption", json!({ "dir": "/data" })).await;
+        mock_method(&mock_server, "aria2.addUri", json!("retried")).await;
+        mock_method(&mock_server, "aria2.removeDownloadResult", json!("OK")).await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_state(&dir, mock_server.uri());
+        client.retries.insert(
+            "failed",
+            RetryState {
+                attempts: 3,
+                gave_up: true,
+                ..Default::default()
+            },
+        );
+
+        let result = ManageRetriesTool
+            .run(&client, json!({ "action": "retryNow", "gid": "failed" }))
+            .await
+            .unwrap();
+        assert_eq!(result["newGid"], "retried");
+        assert_eq!(
+            client.lineage.successor("failed").as_deref(),
+            Some("retried")
+        );
+        let state = client.retries.get("retried").unwrap();
+        assert_eq!(state.attempts, 3);
+        assert!(!state.gave_up);
+
+        let restarted = client_with_state(&dir, mock_server.uri());
+        restarted.restore_recovery().await.unwrap();
+        assert_eq!(
+            restarted.lineage.successor("failed").as_deref(),
+            Some("retried")
+        );
+    }
+}
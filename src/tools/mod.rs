@@ -1,10 +1,16 @@
+pub mod auto_shutdown;
 pub mod bulk_manage_downloads;
 pub mod check_health;
 pub mod configure_aria2;
+pub mod download_history;
+pub mod download_stats;
 pub mod inspect_download;
 pub mod list_download_files;
+pub mod list_instances;
 pub mod manage_all_instances;
+pub mod manage_aria2_process;
 pub mod manage_downloads;
+pub mod manage_retries;
 pub mod manage_torrent;
 pub mod monitor_queue;
 pub mod organize_completed;
@@ -15,18 +21,27 @@ pub mod sandbox;
 pub mod schedule_limits;
 pub mod search_downloads;
 
+pub use auto_shutdown::AutoShutdownTool;
 pub use bulk_manage_downloads::BulkManageDownloadsTool;
 pub use check_health::CheckHealthTool;
 pub use configure_aria2::ConfigureAria2Tool;
+pub use download_history::DownloadHistoryTool;
+pub use download_stats::DownloadStatsTool;
 pub use inspect_download::InspectDownloadTool;
 pub use list_download_files::ListDownloadFilesTool;
+pub use list_instances::ListInstancesTool;
 pub use manage_all_instances::ManageAllInstancesTool;
+pub use manage_aria2_process::ManageAria2ProcessTool;
 pub use manage_downloads::ManageDownloadsTool;
+pub use manage_retries::ManageRetriesTool;
 pub use manage_torrent::ManageTorrentTool;
 pub use monitor_queue::MonitorQueueTool;
 pub use organize_completed::OrganizeCompletedTool;
 pub use purge_policy::PurgePolicyTool;
 pub use registry::{McpeTool, ToolRegistry};
-pub use rss::{AddRssFeedTool, ListRssFeedsTool};
+pub use rss::{
+    AddRssFeedTool, ClearHistoryTool, ListRssFeedsTool, PollRssFeedTool, PreviewRssFeedTool,
+    RemoveRssFeedTool, UpdateRssFeedTool,
+};
 pub use schedule_limits::ScheduleLimitsTool;
 pub use search_downloads::SearchDownloadsTool;
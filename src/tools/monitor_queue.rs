@@ -76,7 +76,12 @@ impl McpeTool for MonitorQueueTool {
 
                 // Integrate error analysis
                 if let Some(items) = results.as_array_mut() {
-                    let analyzer = crate::aria2::recovery::ErrorAnalyzer::new();
+                    let policies = {
+                        let config = client.config();
+                        let config_guard = config.read().await;
+                        config_guard.retry_config.policies.clone()
+                    };
+                    let analyzer = crate::aria2::recovery::ErrorAnalyzer::with_policies(&policies);
                     for item in items {
                         if analyzer.should_retry(item) {
                             if let Some(obj) = item.as_object_mut() {
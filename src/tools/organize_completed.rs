@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::path::Path;
 
-use crate::aria2::Aria2Client;
+use crate::aria2::{Aria2Client, DownloadState, DownloadStatus};
 use crate::tools::registry::McpeTool;
 
 pub struct OrganizeCompletedTool;
@@ -185,8 +185,8 @@ impl McpeTool for OrganizeCompletedTool {
                 let mut organized_count = 0;
 
                 if let Some(gid) = args_parsed.gid {
-                    let status = client.tell_status(&gid).await?;
-                    if status["status"] == "complete" {
+                    let status = client.download_status(&gid).await?;
+                    if status.status == Some(DownloadState::Complete) {
                         if self.organize_download(&status, &rules).await? {
                             organized_count += 1;
                         }
@@ -194,18 +194,16 @@ impl McpeTool for OrganizeCompletedTool {
                         return Err(anyhow::anyhow!(
                             "Download {} is not complete (status: {})",
                             gid,
-                            status["status"]
+                            status.status.map_or("unknown", |s| s.as_str())
                         ));
                     }
                 } else {
-                    let stopped = client.tell_stopped(0, 1000, None).await?;
-                    if let Some(stopped_list) = stopped.as_array() {
-                        for status in stopped_list {
-                            if status["status"] == "complete"
-                                && self.organize_download(status, &rules).await?
-                            {
-                                organized_count += 1;
-                            }
+                    let stopped = client.stopped_downloads(0, 1000, None).await?;
+                    for status in &stopped {
+                        if status.status == Some(DownloadState::Complete)
+                            && self.organize_download(status, &rules).await?
+                        {
+                            organized_count += 1;
                         }
                     }
                 }
@@ -217,17 +215,15 @@ impl McpeTool for OrganizeCompletedTool {
 }
 
 impl OrganizeCompletedTool {
-    async fn organize_download(&self, status: &Value, rules: &[Rule]) -> Result<bool> {
-        let files = status["files"]
-            .as_array()
-            .ok_or_else(|| anyhow::anyhow!("No files found in download status"))?;
+    async fn organize_download(&self, status: &DownloadStatus, rules: &[Rule]) -> Result<bool> {
+        if status.files.is_empty() {
+            return Err(anyhow::anyhow!("No files found in download status"));
+        }
 
         let mut organized = false;
 
-        for file in files {
-            let path_str = file["path"]
-                .as_str()
-                .ok_or_else(|| anyhow::anyhow!("File path is missing"))?;
+        for file in &status.files {
+            let path_str = file.path.as_str();
 
             if path_str.is_empty() {
                 continue;
@@ -306,7 +302,7 @@ mod tests {
         let movie_file = download_dir.join("movie.mp4");
         tokio::fs::write(&movie_file, "dummy content").await?;
 
-        let status = json!({
+        let status: DownloadStatus = serde_json::from_value(json!({
             "gid": "1",
             "status": "complete",
             "files": [
@@ -315,7 +311,7 @@ mod tests {
                     "selected": "true"
                 }
             ]
-        });
+        }))?;
 
         let target_dir = temp_dir.join("movies");
         let rules = vec![Rule {
@@ -12,7 +12,7 @@ impl McpeTool for PurgePolicyTool {
     }
 
     fn description(&self) -> String {
-        "View or update the automated queue purging policy".to_string()
+        "View or update the automated queue purging policy, or list what it purged".to_string()
     }
 
     fn schema(&self) -> Result<serde_json::Value> {
@@ -21,7 +21,7 @@ impl McpeTool for PurgePolicyTool {
             "properties": {
                 "action": {
                     "type": "string",
-                    "enum": ["get_policy", "update_policy", "exclude_gid", "remove_exclusion"],
+                    "enum": ["get_policy", "update_policy", "exclude_gid", "remove_exclusion", "list_history"],
                     "description": "The action to perform"
                 },
                 "enabled": {
@@ -36,6 +36,24 @@ impl McpeTool for PurgePolicyTool {
                     "type": "integer",
                     "description": "Minimum age of a stopped/errored download before it can be purged (in seconds)"
                 },
+                "statuses": {
+                    "type": "array",
+                    "items": { "type": "string", "enum": ["complete", "error", "removed"] },
+                    "description": "Download statuses that are purged"
+                },
+                "statusMinAgeSecs": {
+                    "type": "object",
+                    "additionalProperties": { "type": "integer" },
+                    "description": "Minimum age per status, overriding minAgeSecs, e.g. {\"error\": 600}"
+                },
+                "historyLimit": {
+                    "type": "integer",
+                    "description": "Number of purged downloads to remember"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of history entries to return for list_history (newest first)"
+                },
                 "gid": {
                     "type": "string",
                     "description": "GID to exclude from or remove from purging exclusions"
@@ -60,7 +78,10 @@ impl McpeTool for PurgePolicyTool {
         match action {
             "get_policy" => {
                 let config_guard = config.read().await;
-                Ok(json!({ "policy": config_guard.purge_config }))
+                Ok(json!({
+                    "policy": config_guard.purge_config,
+                    "effective": config_guard.purge_config.for_instance(&client.name)
+                }))
             }
             "update_policy" => {
                 let mut config_guard = config.write().await;
@@ -75,6 +96,22 @@ impl McpeTool for PurgePolicyTool {
                 if let Some(min_age) = args.get("minAgeSecs").and_then(serde_json::Value::as_u64) {
                     config_guard.purge_config.min_age_secs = min_age;
                 }
+                if let Some(statuses) = args.get("statuses").and_then(|v| v.as_array()) {
+                    config_guard.purge_config.statuses = statuses
+                        .iter()
+                        .filter_map(|s| s.as_str().map(str::to_string))
+                        .collect();
+                }
+                if let Some(ages) = args.get("statusMinAgeSecs").and_then(|v| v.as_object()) {
+                    config_guard.purge_config.status_min_age_secs = ages
+                        .iter()
+                        .filter_map(|(status, age)| Some((status.clone(), age.as_u64()?)))
+                        .collect();
+                }
+                if let Some(limit) = args.get("historyLimit").and_then(serde_json::Value::as_u64) {
+                    config_guard.purge_config.history_limit =
+                        usize::try_from(limit).unwrap_or(usize::MAX);
+                }
 
                 Ok(
                     json!({ "status": "success", "message": "Purge policy updated", "policy": config_guard.purge_config }),
@@ -117,6 +154,26 @@ impl McpeTool for PurgePolicyTool {
                     )
                 }
             }
+            "list_history" => {
+                let limit = args
+                    .get("limit")
+                    .and_then(serde_json::Value::as_u64)
+                    .map_or(usize::MAX, |l| usize::try_from(l).unwrap_or(usize::MAX));
+                let state = client
+                    .state_manager()
+                    .load()
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to load state: {e}"))?;
+                let history: Vec<_> = state
+                    .purge_history
+                    .into_iter()
+                    .rev()
+                    .filter(|record| record.instance == client.name)
+                    .take(limit)
+                    .collect();
+
+                Ok(json!({ "history": history }))
+            }
             _ => Err(anyhow::anyhow!("Unknown action: {action}")),
         }
     }
@@ -156,6 +213,104 @@ mod tests {
         assert_eq!(result["policy"]["interval_secs"], 100);
     }
 
+    #[tokio::test]
+    async fn test_purge_policy_update_statuses() {
+        let tool = PurgePolicyTool;
+        let client = Aria2Client::new(Config::default());
+        let args = json!({
+            "action": "update_policy",
+            "statuses": ["complete", "removed"],
+            "statusMinAgeSecs": { "removed": 0 },
+            "historyLimit": 10
+        });
+        let result = tool.run(&client, args).await.unwrap();
+        assert_eq!(result["policy"]["statuses"], json!(["complete", "removed"]));
+        assert_eq!(result["policy"]["status_min_age_secs"]["removed"], 0);
+        assert_eq!(result["policy"]["history_limit"], 10);
+    }
+
+    #[tokio::test]
+    async fn test_purge_policy_list_history() {
+        use crate::state::{PurgeRecord, StateManager};
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut client = Aria2Client::new(Config::default());
+        client.state_manager = std::sync::Arc::new(StateManager::new(dir.path().join("s.json")));
+
+        let record = |gid: &str, instance: &str| PurgeRecord {
+            gid: gid.to_string(),
+            instance: instance.to_string(),
+            name: None,
+            status: "complete".to_string(),
+            completed_at: 1,
+            purged_at: 2,
+        };
+        client
+            .record_purges(
+                vec![
+                    record("a", "default"),
+                    record("b", "other"),
+                    record("c", "default"),
+                ],
+                2,
+            )
+            .await
+            .unwrap();
+
+        let tool = PurgePolicyTool;
+        let result = tool
+            .run(&client, json!({ "action": "list_history" }))
+            .await
+            .unwrap();
+        // Oldest record was dropped by the limit, the other instance's is hidden
+        assert_eq!(result["history"].as_array().unwrap().len(), 1);
+        assert_eq!(result["history"][0]["gid"], "c");
+        assert_eq!(result["history"][0]["purgedAt"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_record_purges_keeps_concurrent_saves() {
+        use crate::state::{PurgeRecord, StateManager};
+
+        let dir = tempfile::tempdir().unwrap();
+        let manager = std::sync::Arc::new(StateManager::new(dir.path().join("s.json")));
+        let mut purging = Aria2Client::new(Config::default());
+        purging.state_manager = std::sync::Arc::clone(&manager);
+        let mut saving = Aria2Client::new(Config::default());
+        saving.name = "other".to_string();
+        saving.state_manager = std::sync::Arc::clone(&manager);
+        saving
+            .completions()
+            .restore(&std::collections::HashMap::from([("done".to_string(), 1)]));
+
+        let records = (0..10)
+            .map(|i| PurgeRecord {
+                gid: i.to_string(),
+                instance: "default".to_string(),
+                name: None,
+                status: "complete".to_string(),
+                completed_at: 1,
+                purged_at: 2,
+            })
+            .collect::<Vec<_>>();
+        tokio::join!(
+            async {
+                for record in records {
+                    purging.record_purges(vec![record], 100).await.unwrap();
+                }
+            },
+            async {
+                for _ in 0..10 {
+                    saving.save_state().await.unwrap();
+                }
+            }
+        );
+
+        let state = manager.load().await.unwrap();
+        assert_eq!(state.purge_history.len(), 10);
+        assert_eq!(state.completion_times["other"]["done"], 1);
+    }
+
     #[tokio::test]
     async fn test_purge_policy_exclude_gid() {
         let tool = PurgePolicyTool;
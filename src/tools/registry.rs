@@ -4,22 +4,33 @@ use serde::Serialize;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use tokio::sync::broadcast;
 
 use crate::aria2::Aria2Client;
+use crate::metrics::ToolMetrics;
 use crate::Config;
 
+use super::auto_shutdown::AutoShutdownTool;
 use super::bulk_manage_downloads::BulkManageDownloadsTool;
 use super::check_health::CheckHealthTool;
 use super::configure_aria2::ConfigureAria2Tool;
+use super::download_history::DownloadHistoryTool;
+use super::download_stats::DownloadStatsTool;
 use super::inspect_download::InspectDownloadTool;
 use super::list_download_files::ListDownloadFilesTool;
+use super::list_instances::ListInstancesTool;
 use super::manage_all_instances::ManageAllInstancesTool;
+use super::manage_aria2_process::ManageAria2ProcessTool;
 use super::manage_downloads::ManageDownloadsTool;
+use super::manage_retries::ManageRetriesTool;
 use super::manage_torrent::ManageTorrentTool;
 use super::monitor_queue::MonitorQueueTool;
 use super::organize_completed::OrganizeCompletedTool;
 use super::purge_policy::PurgePolicyTool;
-use super::rss::{AddRssFeedTool, ListRssFeedsTool};
+use super::rss::{
+    AddRssFeedTool, ClearHistoryTool, ListRssFeedsTool, PollRssFeedTool, PreviewRssFeedTool,
+    RemoveRssFeedTool, UpdateRssFeedTool,
+};
 use super::schedule_limits::ScheduleLimitsTool;
 use super::search_downloads::SearchDownloadsTool;
 
@@ -53,10 +64,21 @@ pub struct ToolRegistry {
     tools: HashMap<String, Arc<dyn McpeTool>>,
     enabled_tools: HashSet<String>,
     lazy_mode: bool,
+    /// Signalled whenever the set of enabled tools changes
+    changes: broadcast::Sender<()>,
+    metrics: Arc<ToolMetrics>,
 }
 
 struct ToolWrapper {
     tool: Arc<dyn McpeTool>,
+    metrics: Arc<ToolMetrics>,
+}
+
+impl ToolWrapper {
+    fn observe<T>(&self, started: std::time::Instant, result: &Result<T>) {
+        self.metrics
+            .observe(&self.tool.name(), started.elapsed(), result.is_ok());
+    }
 }
 
 #[async_trait]
@@ -97,7 +119,10 @@ impl McpeTool for ToolWrapper {
     }
 
     async fn run(&self, client: &Aria2Client, args: Value) -> Result<Value> {
-        self.tool.run(client, args).await
+        let started = std::time::Instant::now();
+        let result = self.tool.run(client, args).await;
+        self.observe(started, &result);
+        result
     }
 
     async fn run_multi(&self, clients: &[Arc<Aria2Client>], args: Value) -> Result<Value> {
@@ -108,14 +133,18 @@ impl McpeTool for ToolWrapper {
         // 1. If 'instance' is provided, route to specific client.
         // 2. If 'instance' is NOT provided, call the underlying tool's run_multi.
 
-        if let Some(instance_idx) = args.get("instance").and_then(serde_json::Value::as_u64) {
-            let client = clients
-                .get(instance_idx as usize)
-                .ok_or_else(|| anyhow::anyhow!("Invalid instance index: {instance_idx}"))?;
-            self.tool.run(client, args).await
-        } else {
-            self.tool.run_multi(clients, args).await
-        }
+        let started = std::time::Instant::now();
+        let result =
+            if let Some(instance_idx) = args.get("instance").and_then(serde_json::Value::as_u64) {
+                match clients.get(instance_idx as usize) {
+                    Some(client) => self.tool.run(client, args).await,
+                    None => Err(anyhow::anyhow!("Invalid instance index: {instance_idx}")),
+                }
+            } else {
+                self.tool.run_multi(clients, args).await
+            };
+        self.observe(started, &result);
+        result
     }
 }
 
@@ -132,6 +161,8 @@ impl ToolRegistry {
             tools: HashMap::new(),
             enabled_tools: HashSet::new(),
             lazy_mode: config.lazy_mode,
+            changes: broadcast::channel(16).0,
+            metrics: Arc::new(ToolMetrics::default()),
         };
 
         registry.register(Arc::new(ManageDownloadsTool));
@@ -143,12 +174,23 @@ impl ToolRegistry {
         registry.register(Arc::new(BulkManageDownloadsTool));
         registry.register(Arc::new(CheckHealthTool));
         registry.register(Arc::new(ManageAllInstancesTool));
+        registry.register(Arc::new(ListInstancesTool));
+        registry.register(Arc::new(ManageAria2ProcessTool));
         registry.register(Arc::new(ManageTorrentTool));
         registry.register(Arc::new(OrganizeCompletedTool));
         registry.register(Arc::new(ScheduleLimitsTool));
         registry.register(Arc::new(PurgePolicyTool));
+        registry.register(Arc::new(AutoShutdownTool::default()));
+        registry.register(Arc::new(DownloadHistoryTool));
+        registry.register(Arc::new(DownloadStatsTool));
+        registry.register(Arc::new(ManageRetriesTool));
         registry.register(Arc::new(AddRssFeedTool));
         registry.register(Arc::new(ListRssFeedsTool));
+        registry.register(Arc::new(RemoveRssFeedTool));
+        registry.register(Arc::new(UpdateRssFeedTool));
+        registry.register(Arc::new(ClearHistoryTool));
+        registry.register(Arc::new(PreviewRssFeedTool));
+        registry.register(Arc::new(PollRssFeedTool));
 
         // In lazy mode, only enable basic tools by default.
         // register() already enables all tools if !lazy_mode.
@@ -160,7 +202,10 @@ impl ToolRegistry {
     }
 
     pub fn register(&mut self, tool: Arc<dyn McpeTool>) {
-        let wrapped = Arc::new(ToolWrapper { tool });
+        let wrapped = Arc::new(ToolWrapper {
+            tool,
+            metrics: Arc::clone(&self.metrics),
+        });
         let name = wrapped.name();
         self.tools.insert(name.clone(), wrapped);
         if !self.lazy_mode {
@@ -184,7 +229,9 @@ impl ToolRegistry {
 
     pub fn enable_tool(&mut self, name: &str) -> bool {
         if self.tools.contains_key(name) {
-            self.enabled_tools.insert(name.to_string());
+            if self.enabled_tools.insert(name.to_string()) {
+                let _ = self.changes.send(());
+            }
             true
         } else {
             false
@@ -192,7 +239,18 @@ impl ToolRegistry {
     }
 
     pub fn disable_tool(&mut self, name: &str) -> bool {
-        self.enabled_tools.remove(name)
+        let removed = self.enabled_tools.remove(name);
+        if removed {
+            let _ = self.changes.send(());
+        }
+        removed
+    }
+
+    /// Receives a message each time a tool is enabled or disabled, so
+    /// transports can send `notifications/tools/list_changed`.
+    #[must_use]
+    pub fn subscribe_changes(&self) -> broadcast::Receiver<()> {
+        self.changes.subscribe()
     }
 
     #[must_use]
@@ -214,10 +272,41 @@ impl ToolRegistry {
         result
     }
 
+    /// Calls, errors and latency of the registered tools.
+    #[must_use]
+    pub fn metrics(&self) -> Arc<ToolMetrics> {
+        Arc::clone(&self.metrics)
+    }
+
     #[must_use]
     pub fn is_lazy_mode(&self) -> bool {
         self.lazy_mode
     }
+
+    /// Listing of the `manage_tools` meta-tool offered in lazy mode.
+    #[must_use]
+    pub fn manage_tools_info() -> Value {
+        serde_json::json!({
+            "name": "manage_tools",
+            "description": "Manage available tools (enable/disable) to save tokens.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "action": {
+                        "type": "string",
+                        "enum": ["list", "enable", "disable"],
+                        "description": "The action to perform."
+                    },
+                    "tools": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "List of tool names to enable or disable."
+                    }
+                },
+                "required": ["action"]
+            }
+        })
+    }
 }
 
 #[derive(Serialize)]
@@ -235,7 +324,7 @@ mod tests {
     fn test_registry_new() {
         let registry = ToolRegistry::new(&Config::default());
         let tools = registry.list_tools();
-        assert_eq!(tools.len(), 15);
+        assert_eq!(tools.len(), 26);
     }
 
     #[test]
@@ -276,12 +365,33 @@ mod tests {
         assert_eq!(registry.list_tools().len(), 1);
     }
 
+    #[test]
+    fn test_registry_change_notifications() {
+        let config = Config {
+            lazy_mode: true,
+            ..Default::default()
+        };
+        let mut registry = ToolRegistry::new(&config);
+        let mut changes = registry.subscribe_changes();
+
+        assert!(registry.enable_tool("manage_downloads"));
+        assert!(changes.try_recv().is_ok());
+
+        // Enabling an enabled tool or disabling a disabled one is not a change
+        assert!(registry.enable_tool("manage_downloads"));
+        assert!(!registry.disable_tool("check_health"));
+        assert!(changes.try_recv().is_err());
+
+        assert!(registry.disable_tool("manage_downloads"));
+        assert!(changes.try_recv().is_ok());
+    }
+
     #[test]
     fn test_registry_list_available() {
         let config = Config::default();
         let registry = ToolRegistry::new(&config);
         let available = registry.list_available_tools();
-        assert_eq!(available.len(), 15);
+        assert_eq!(available.len(), 26);
         for tool in available {
             assert!(tool["enabled"].as_bool().unwrap());
         }
@@ -296,6 +406,21 @@ mod tests {
         assert_eq!(schema["properties"]["instance"]["type"], "integer");
     }
 
+    #[tokio::test]
+    async fn test_registry_records_tool_metrics() {
+        let registry = ToolRegistry::new(&Config::default());
+        let tool = registry.get_tool("manage_downloads").unwrap();
+        let clients = vec![Arc::new(Aria2Client::new(Config::default()))];
+        assert!(tool
+            .run_multi(&clients, serde_json::json!({ "instance": 5 }))
+            .await
+            .is_err());
+
+        let metrics = crate::metrics::render(&registry.metrics(), &[]).await;
+        assert!(metrics.contains("aria2_mcp_tool_calls_total{tool=\"manage_downloads\"} 1\n"));
+        assert!(metrics.contains("aria2_mcp_tool_errors_total{tool=\"manage_downloads\"} 1\n"));
+    }
+
     #[test]
     fn test_registry_default() {
         let _registry = ToolRegistry::default();
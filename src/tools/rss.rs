@@ -1,110 +1,531 @@
 use crate::aria2::Aria2Client;
 use crate::config::{RSSFeed, RSSFilter};
+use crate::metrics::InstanceCounters;
 use crate::tools::registry::McpeTool;
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use rss::Channel;
+use serde::Serialize;
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::time::{self, Duration};
+use tokio::time::{self, Duration, Instant};
+
+/// How often the monitor wakes up to look for feeds that are due.
+const RSS_SCHEDULER_TICK_SECS: u64 = 30;
 
 pub async fn start_rss_monitoring(client: Arc<Aria2Client>) -> Result<()> {
-    let mut interval = time::interval(Duration::from_secs(600)); // Every 10 minutes
+    let mut last_polled: HashMap<String, Instant> = HashMap::new();
+    let mut interval = time::interval(Duration::from_secs(RSS_SCHEDULER_TICK_SECS));
 
     loop {
         interval.tick().await;
-        log::debug!("Checking RSS feeds for instance {}...", client.name);
 
-        let feeds = {
+        let (feeds, default_interval) = {
             let config = client.config();
             let config_guard = config.read().await;
-            config_guard.rss_config.feeds.clone()
+            (
+                config_guard.rss_config.feeds.clone(),
+                config_guard.rss_config.interval_secs,
+            )
         };
 
-        for (idx, mut feed) in feeds.into_iter().enumerate() {
-            if let Err(e) = process_feed(&client, &mut feed).await {
-                log::error!("Error processing RSS feed '{}': {}", feed.name, e);
-            } else {
-                // Update history in config
-                {
-                    let config = client.config();
-                    let mut config_guard = config.write().await;
-                    if let Some(f) = config_guard.rss_config.feeds.get_mut(idx) {
-                        f.download_history = feed.download_history;
-                    }
-                }
+        let now = Instant::now();
+        last_polled.retain(|name, _| feeds.iter().any(|f| &f.name == name));
+        let due: Vec<RSSFeed> = feeds
+            .into_iter()
+            .filter(|feed| {
+                let every = Duration::from_secs(feed.interval_secs.unwrap_or(default_interval));
+                is_due(last_polled.get(&feed.name).copied(), every, now)
+            })
+            .collect();
+        if due.is_empty() {
+            continue;
+        }
+        log::debug!(
+            "Checking {} RSS feed(s) for instance {}...",
+            due.len(),
+            client.name
+        );
+
+        let mut changed = false;
+        for feed in due {
+            last_polled.insert(feed.name.clone(), now);
+            match poll_feed(&client, &feed.name).await {
+                Ok(added) => changed |= !added.is_empty(),
+                Err(e) => log::error!("Error processing RSS feed '{}': {}", feed.name, e),
             }
         }
+
+        if changed {
+            if let Err(e) = client.save_state().await {
+                log::error!("Failed to save RSS history: {e}");
+            }
+        }
+    }
+}
+
+/// Per-instance locks that serialize the runs of each RSS feed, so the
+/// monitor and `poll_rss_feed` never check the same history at once and add
+/// the same items twice.
+#[derive(Default)]
+pub struct FeedLocks {
+    locks: std::sync::Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl FeedLocks {
+    fn get(&self, name: &str) -> Arc<tokio::sync::Mutex<()>> {
+        Arc::clone(
+            self.locks
+                .lock()
+                .unwrap()
+                .entry(name.to_string())
+                .or_default(),
+        )
     }
 }
 
-pub async fn process_feed(client: &Aria2Client, feed: &mut RSSFeed) -> Result<()> {
-    let content = reqwest::get(&feed.url).await?.bytes().await?;
-    let channel = Channel::read_from(&content[..])?;
+/// Processes the monitored feed `name` and records the items it added in
+/// its history. Holds the feed's lock from reading the history to recording
+/// it. Returns the items that were added.
+pub async fn poll_feed(client: &Aria2Client, name: &str) -> Result<Vec<FeedItem>> {
+    let lock = client.feed_locks.get(name);
+    let _guard = lock.lock().await;
 
-    for item in channel.items() {
-        let title = item.title().unwrap_or("Unknown Title");
-        let link = item.link().and_then(|l| {
-            if l.is_empty() {
-                item.enclosure().map(rss::Enclosure::url)
-            } else {
-                Some(l)
+    let mut feed = find_feed(client, name).await?;
+    let added = process_feed(client, &mut feed).await?;
+    if !added.is_empty() {
+        record_history(client, name, &added).await;
+    }
+    Ok(added)
+}
+
+/// Records the items a run of the feed `name` added in its history in the
+/// config and prunes it. Only those items are merged, since the history may
+/// have been cleared or the feed edited or removed by a tool meanwhile.
+async fn record_history(client: &Aria2Client, name: &str, added: &[FeedItem]) {
+    let config = client.config();
+    let mut config_guard = config.write().await;
+    let (limit, max_age) = (
+        config_guard.rss_config.history_limit,
+        config_guard.rss_config.history_max_age_secs,
+    );
+    if let Some(f) = config_guard
+        .rss_config
+        .feeds
+        .iter_mut()
+        .find(|f| f.name == name)
+    {
+        for item in added {
+            f.mark_downloaded(item.id.clone());
+        }
+        f.prune_history(limit, max_age);
+    }
+}
+
+/// Whether a feed last polled at `last_polled` should be polled again.
+fn is_due(last_polled: Option<Instant>, every: Duration, now: Instant) -> bool {
+    last_polled.is_none_or(|last| now.duration_since(last) >= every)
+}
+
+/// Fetches `feed`, adds every item it accepts to aria2 and records them in
+/// its history. Returns the items that were added.
+pub async fn process_feed(client: &Aria2Client, feed: &mut RSSFeed) -> Result<Vec<FeedItem>> {
+    let filters = FeedFilters::new(feed)?;
+    let items = fetch_feed(&feed.url).await?;
+    let options = (!feed.options.is_empty()).then(|| json!(feed.options));
+
+    let mut added = Vec::new();
+    for item in items {
+        if !item.decide(feed, &filters).download {
+            continue;
+        }
+        let Some(url) = item.link.clone() else {
+            continue;
+        };
+
+        log::info!(
+            "RSS Match: Adding download '{}' from {}",
+            item.title,
+            feed.name
+        );
+        match client.add_uri(vec![url], options.clone()).await {
+            Ok(gid) => {
+                log::info!("Added RSS download. GID: {gid}");
+                InstanceCounters::add(&client.counters.rss_matches, 1);
+                feed.mark_downloaded(item.id.clone());
+                added.push(item);
             }
-        });
+            Err(e) => {
+                log::error!("Failed to add RSS download '{}': {e}", item.title);
+            }
+        }
+    }
+
+    Ok(added)
+}
+
+/// Downloads and parses the feed at `url`.
+pub async fn fetch_feed(url: &str) -> Result<Vec<FeedItem>> {
+    let content = reqwest::get(url).await?.bytes().await?;
+    parse_feed(&content)
+}
+
+/// A feed entry reduced to the fields filters look at, read from either an
+/// RSS item or an Atom entry.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FeedItem {
+    /// GUID, Atom id, link or title, whichever is found first
+    pub id: String,
+    pub title: String,
+    /// The link handed to aria2, preferring enclosures
+    pub link: Option<String>,
+    pub description: Option<String>,
+    /// Category names, including torznab/newznab category ids
+    pub categories: Vec<String>,
+    /// Size in bytes from torznab/newznab attributes or the enclosure length
+    pub size: Option<u64>,
+}
 
+/// Parses an RSS 2.0 or Atom document into its items.
+pub fn parse_feed(content: &[u8]) -> Result<Vec<FeedItem>> {
+    match Channel::read_from(content) {
+        Ok(channel) => Ok(channel.items().iter().map(FeedItem::from_rss).collect()),
+        Err(rss_err) => match atom_syndication::Feed::read_from(content) {
+            Ok(feed) => Ok(feed.entries().iter().map(FeedItem::from_atom).collect()),
+            Err(_) => Err(rss_err).context("Content is neither an RSS nor an Atom feed"),
+        },
+    }
+}
+
+impl FeedItem {
+    fn from_rss(item: &rss::Item) -> Self {
+        let title = item.title().unwrap_or("Unknown Title").to_string();
+        // On torrent feeds `<link>` is usually the details page
+        let link = item
+            .enclosure()
+            .map(rss::Enclosure::url)
+            .filter(|l| !l.is_empty())
+            .or_else(|| item.link().filter(|l| !l.is_empty()))
+            .map(str::to_string);
         let id = item
             .guid()
             .map(rss::Guid::value)
             .or(item.link())
-            .unwrap_or(title);
-
-        if feed.has_downloaded(id) {
-            continue;
-        }
+            .unwrap_or(&title)
+            .to_string();
 
-        if let Some(url) = link {
-            if matches_filters(title, &feed.filters) {
-                log::info!("RSS Match: Adding download '{}' from {}", title, feed.name);
-                match client.add_uri(vec![url.to_string()], None).await {
-                    Ok(gid) => {
-                        log::info!("Added RSS download. GID: {gid}");
-                        feed.mark_downloaded(id.to_string());
-                    }
-                    Err(e) => {
-                        log::error!("Failed to add RSS download '{title}': {e}");
-                    }
+        let mut categories: Vec<String> = item
+            .categories()
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect();
+        let mut size = None;
+        // Torznab and newznab indexers publish `<torznab:attr name="..." value="..."/>`
+        for prefix in ["torznab", "newznab"] {
+            let attrs = item
+                .extensions()
+                .get(prefix)
+                .and_then(|ext| ext.get("attr"))
+                .into_iter()
+                .flatten();
+            for attr in attrs {
+                let (Some(name), Some(value)) =
+                    (attr.attrs().get("name"), attr.attrs().get("value"))
+                else {
+                    continue;
+                };
+                match name.as_str() {
+                    "category" => categories.push(value.clone()),
+                    "size" => size = size.or_else(|| value.parse().ok()),
+                    _ => {}
                 }
             }
         }
+        let size = size.or_else(|| item.enclosure().and_then(|e| e.length().parse().ok()));
+
+        Self {
+            id,
+            title,
+            link,
+            description: item.description().map(str::to_string),
+            categories,
+            size,
+        }
     }
 
-    Ok(())
-}
+    fn from_atom(entry: &atom_syndication::Entry) -> Self {
+        let title = entry.title().as_str().to_string();
+        let enclosure = entry.links().iter().find(|l| l.rel() == "enclosure");
+        let link = enclosure
+            .or_else(|| entry.links().iter().find(|l| l.rel() == "alternate"))
+            .or_else(|| entry.links().first())
+            .map(|l| l.href().to_string());
+        let id = if entry.id().is_empty() {
+            link.clone().unwrap_or_else(|| title.clone())
+        } else {
+            entry.id().to_string()
+        };
+        let description = entry
+            .summary()
+            .map(|s| s.as_str().to_string())
+            .or_else(|| entry.content().and_then(|c| c.value()).map(str::to_string));
+        let categories = entry
+            .categories()
+            .iter()
+            .flat_map(|c| std::iter::once(c.term()).chain(c.label()))
+            .map(str::to_string)
+            .collect();
 
-#[must_use]
-pub fn matches_filters(title: &str, filters: &[RSSFilter]) -> bool {
-    if filters.is_empty() {
-        return true;
+        Self {
+            id,
+            title,
+            link,
+            description,
+            categories,
+            size: enclosure
+                .and_then(|l| l.length())
+                .and_then(|l| l.parse().ok()),
+        }
     }
 
-    for filter in filters {
-        match filter {
-            RSSFilter::Keyword(k) => {
-                if title.to_lowercase().contains(&k.to_lowercase()) {
-                    return true;
-                }
+    /// Whether the item passes `filters`. Filters of the same kind are
+    /// alternatives, while every kind that is used must match, so
+    /// `["ubuntu", "debian", "size:-5GB"]` accepts either distribution when
+    /// it is at most 5 GB. An empty list matches everything; a list with an
+    /// invalid filter matches nothing.
+    #[must_use]
+    pub fn matches(&self, filters: &[RSSFilter]) -> bool {
+        compile_filters(filters).is_ok_and(|filters| self.check_filters(&filters).is_ok())
+    }
+
+    /// Applies `filters` as [`FeedItem::matches`] does. Returns the filters
+    /// that matched, or the kinds of filter none of which did.
+    fn check_filters<'a>(
+        &self,
+        filters: &'a [FeedFilter],
+    ) -> std::result::Result<Vec<&'a RSSFilter>, Vec<&'static str>> {
+        let mut kinds: Vec<(&'static str, bool)> = Vec::new();
+        let mut matched = Vec::new();
+        for filter in filters {
+            let kind = filter_kind(&filter.filter);
+            let is_match = filter.matches(self);
+            if is_match {
+                matched.push(&filter.filter);
             }
-            RSSFilter::Regex(r) => {
-                if let Ok(re) = regex::RegexBuilder::new(r).case_insensitive(true).build() {
-                    if re.is_match(title) {
-                        return true;
-                    }
-                }
+            match kinds.iter_mut().find(|(k, _)| *k == kind) {
+                Some((_, any)) => *any |= is_match,
+                None => kinds.push((kind, is_match)),
+            }
+        }
+
+        let unmatched: Vec<&'static str> = kinds
+            .into_iter()
+            .filter(|(_, any)| !any)
+            .map(|(kind, _)| kind)
+            .collect();
+        if unmatched.is_empty() {
+            Ok(matched)
+        } else {
+            Err(unmatched)
+        }
+    }
+
+    /// Decides whether `feed`, whose filters are `filters`, would download
+    /// this item, and why.
+    #[must_use]
+    pub fn decide(&self, feed: &RSSFeed, filters: &FeedFilters) -> ItemDecision {
+        let reject = |reason: String| ItemDecision {
+            download: false,
+            reason,
+        };
+
+        if feed.has_downloaded(&self.id) {
+            return reject("already downloaded".to_string());
+        }
+        if self.link.is_none() {
+            return reject("item has no link".to_string());
+        }
+        let matched = match self.check_filters(&filters.filters) {
+            Ok(matched) => matched,
+            Err(kinds) => return reject(format!("no {} filter matched", kinds.join(", "))),
+        };
+        let excluded: Vec<&RSSFilter> = filters
+            .exclude
+            .iter()
+            .filter(|f| f.matches(self))
+            .map(|f| &f.filter)
+            .collect();
+        if !excluded.is_empty() {
+            return reject(format!("excluded by {}", quote_filters(&excluded)));
+        }
+
+        ItemDecision {
+            download: true,
+            reason: if matched.is_empty() {
+                "no filters, every item matches".to_string()
+            } else {
+                format!("matched {}", quote_filters(&matched))
+            },
+        }
+    }
+}
+
+/// A filter that was checked, with its pattern compiled.
+#[derive(Debug, Clone)]
+pub struct FeedFilter {
+    filter: RSSFilter,
+    matcher: Matcher,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Keyword(String),
+    Regex(regex::Regex),
+    Category(String),
+    Description(String),
+    Size(Option<u64>, Option<u64>),
+}
+
+impl FeedFilter {
+    /// Checks `filter`, failing for an invalid regex or size range.
+    pub fn new(filter: &RSSFilter) -> Result<Self> {
+        let matcher = match filter {
+            RSSFilter::Keyword(k) => Matcher::Keyword(k.clone()),
+            RSSFilter::Regex(r) => Matcher::Regex(
+                regex::RegexBuilder::new(r)
+                    .case_insensitive(true)
+                    .build()
+                    .with_context(|| format!("Invalid regex filter '{r}'"))?,
+            ),
+            RSSFilter::Category(c) => Matcher::Category(c.clone()),
+            RSSFilter::Description(d) => Matcher::Description(d.clone()),
+            RSSFilter::Size(range) => {
+                let (min, max) = parse_size_range(range).with_context(|| {
+                    format!("Invalid size filter '{range}', expected a range such as '100MB-2GB'")
+                })?;
+                Matcher::Size(min, max)
             }
+        };
+        Ok(Self {
+            filter: filter.clone(),
+            matcher,
+        })
+    }
+
+    fn matches(&self, item: &FeedItem) -> bool {
+        match &self.matcher {
+            Matcher::Keyword(k) => contains_ignore_case(&item.title, k),
+            Matcher::Regex(re) => re.is_match(&item.title),
+            Matcher::Category(c) => item
+                .categories
+                .iter()
+                .any(|cat| cat.eq_ignore_ascii_case(c)),
+            Matcher::Description(d) => item
+                .description
+                .as_deref()
+                .is_some_and(|desc| contains_ignore_case(desc, d)),
+            Matcher::Size(min, max) => item.size.is_some_and(|size| {
+                min.is_none_or(|min| size >= min) && max.is_none_or(|max| size <= max)
+            }),
         }
     }
+}
+
+/// Checks and compiles `filters`, failing on the first invalid one.
+pub fn compile_filters(filters: &[RSSFilter]) -> Result<Vec<FeedFilter>> {
+    filters.iter().map(FeedFilter::new).collect()
+}
+
+/// The filters and exclusions of a feed, compiled once per run.
+#[derive(Debug, Clone)]
+pub struct FeedFilters {
+    filters: Vec<FeedFilter>,
+    exclude: Vec<FeedFilter>,
+}
+
+impl FeedFilters {
+    pub fn new(feed: &RSSFeed) -> Result<Self> {
+        Ok(Self {
+            filters: compile_filters(&feed.filters)?,
+            exclude: compile_filters(&feed.exclude)?,
+        })
+    }
+}
+
+/// Whether an item would be downloaded, with a human readable reason.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ItemDecision {
+    pub download: bool,
+    pub reason: String,
+}
+
+/// The field a filter looks at; filters on the same field are alternatives.
+fn filter_kind(filter: &RSSFilter) -> &'static str {
+    match filter {
+        RSSFilter::Keyword(_) | RSSFilter::Regex(_) => "title",
+        RSSFilter::Category(_) => "category",
+        RSSFilter::Description(_) => "description",
+        RSSFilter::Size(_) => "size",
+    }
+}
+
+fn quote_filters(filters: &[&RSSFilter]) -> String {
+    filters
+        .iter()
+        .map(|f| format!("'{}'", String::from((*f).clone())))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+/// Parses `min-max`, where either bound may be empty, into byte bounds.
+fn parse_size_range(range: &str) -> Option<(Option<u64>, Option<u64>)> {
+    let (min, max) = range.split_once('-')?;
+    let bound = |s: &str| -> Option<Option<u64>> {
+        let s = s.trim();
+        if s.is_empty() {
+            Some(None)
+        } else {
+            parse_size(s).map(Some)
+        }
+    };
+    Some((bound(min)?, bound(max)?))
+}
+
+/// Parses a size such as `700MB`, `1.5G` or `1024`. Units are powers of 1024,
+/// as in aria2.
+fn parse_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let exponent = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" | "KIB" => 1,
+        "M" | "MB" | "MIB" => 2,
+        "G" | "GB" | "GIB" => 3,
+        "T" | "TB" | "TIB" => 4,
+        _ => return None,
+    };
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    Some((number * 1024_f64.powi(exponent)) as u64)
+}
 
-    false
+/// Matches a bare title against `filters`; filters on other fields never
+/// match.
+#[must_use]
+pub fn matches_filters(title: &str, filters: &[RSSFilter]) -> bool {
+    FeedItem {
+        title: title.to_string(),
+        ..FeedItem::default()
+    }
+    .matches(filters)
 }
 
 pub struct AddRssFeedTool;
@@ -116,7 +537,7 @@ impl McpeTool for AddRssFeedTool {
     }
 
     fn description(&self) -> String {
-        "Add a new RSS feed to monitor".to_string()
+        "Add a new RSS or Atom feed to monitor".to_string()
     }
 
     fn schema(&self) -> Result<Value> {
@@ -134,7 +555,22 @@ impl McpeTool for AddRssFeedTool {
                 "filters": {
                     "type": "array",
                     "items": { "type": "string" },
-                    "description": "Optional filters (keywords or 'regex:pattern')"
+                    "description": FILTERS_DESCRIPTION
+                },
+                "exclude": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Items matching any of these filters are skipped"
+                },
+                "intervalSecs": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Polling interval in seconds (defaults to rss_config.interval_secs)"
+                },
+                "options": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "aria2 options for downloads from this feed, e.g. {\"dir\": \"/downloads/tv\"}"
                 }
             },
             "required": ["url", "name"]
@@ -153,71 +589,511 @@ impl McpeTool for AddRssFeedTool {
             .context("Missing 'name'")?
             .to_string();
 
-        let mut filters = Vec::new();
-        if let Some(f_arr) = args.get("filters").and_then(|v| v.as_array()) {
-            for f in f_arr {
-                if let Some(f_str) = f.as_str() {
-                    if let Some(stripped) = f_str.strip_prefix("regex:") {
-                        filters.push(RSSFilter::Regex(stripped.to_string()));
-                    } else {
-                        filters.push(RSSFilter::Keyword(f_str.to_string()));
-                    }
+        let filters = parse_filters(&args, "filters")?;
+        let exclude = parse_filters(&args, "exclude")?;
+        let interval_secs = parse_interval(&args)?.flatten();
+        let options = parse_options(&args)?.unwrap_or_default();
+
+        {
+            let config = client.config();
+            let mut config_guard = config.write().await;
+            if config_guard.rss_config.feeds.iter().any(|f| f.name == name) {
+                return Err(anyhow::anyhow!("RSS feed '{name}' already exists"));
+            }
+            config_guard.rss_config.unmark_removed(&name);
+            config_guard.rss_config.feeds.push(RSSFeed {
+                url: url.clone(),
+                name: name.clone(),
+                filters,
+                exclude,
+                interval_secs,
+                options,
+                download_history: HashMap::new(),
+            });
+        }
+        client.save_state().await?;
+
+        Ok(json!({
+            "status": "success",
+            "message": format!("Added RSS feed '{}' ({})", name, url)
+        }))
+    }
+}
+
+const FILTERS_DESCRIPTION: &str = "Optional filters: title keywords, 'regex:pattern', 'category:name', 'description:keyword' or 'size:min-max' (e.g. 'size:100MB-2GB'). Filters of the same kind are alternatives; every kind used must match";
+
+/// Reads a list of filters from `key`, where prefixes such as `regex:` pick
+/// the kind of filter. Fails if one of them is invalid.
+fn parse_filters(args: &Value, key: &str) -> Result<Vec<RSSFilter>> {
+    let filters: Vec<RSSFilter> = args
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(|f_arr| {
+            f_arr
+                .iter()
+                .filter_map(|f| f.as_str())
+                .map(|f| RSSFilter::from(f.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    compile_filters(&filters).map_err(|e| anyhow::anyhow!("Invalid '{key}': {e:#}"))?;
+    Ok(filters)
+}
+
+/// Reads `intervalSecs`: `None` when absent and `Some(None)` when null.
+fn parse_interval(args: &Value) -> Result<Option<Option<u64>>> {
+    match args.get("intervalSecs") {
+        None => Ok(None),
+        Some(Value::Null) => Ok(Some(None)),
+        Some(v) => match v.as_u64() {
+            Some(secs) if secs > 0 => Ok(Some(Some(secs))),
+            _ => Err(anyhow::anyhow!("'intervalSecs' must be a positive integer")),
+        },
+    }
+}
+
+/// Reads the `options` object, accepting numbers and booleans as strings.
+fn parse_options(args: &Value) -> Result<Option<HashMap<String, String>>> {
+    let Some(options) = args.get("options") else {
+        return Ok(None);
+    };
+    let options = options
+        .as_object()
+        .context("'options' must be an object")?
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(_) | Value::Bool(_) => value.to_string(),
+                _ => return Err(anyhow::anyhow!("Option '{key}' must be a string")),
+            };
+            Ok((key.clone(), value))
+        })
+        .collect::<Result<_>>()?;
+    Ok(Some(options))
+}
+
+pub struct ListRssFeedsTool;
+
+#[async_trait]
+impl McpeTool for ListRssFeedsTool {
+    fn name(&self) -> String {
+        "list_rss_feeds".to_string()
+    }
+
+    fn description(&self) -> String {
+        "List all monitored RSS feeds".to_string()
+    }
+
+    fn schema(&self) -> Result<Value> {
+        Ok(json!({
+            "type": "object",
+            "properties": {}
+        }))
+    }
+
+    async fn run(&self, client: &Aria2Client, _args: Value) -> Result<Value> {
+        let config = client.config();
+        let config_guard = config.read().await;
+
+        Ok(json!({
+            "feeds": config_guard.rss_config.feeds
+        }))
+    }
+}
+
+pub struct RemoveRssFeedTool;
+
+#[async_trait]
+impl McpeTool for RemoveRssFeedTool {
+    fn name(&self) -> String {
+        "remove_rss_feed".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Stop monitoring an RSS feed and forget its download history".to_string()
+    }
+
+    fn schema(&self) -> Result<Value> {
+        Ok(json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "The name of the feed to remove"
                 }
+            },
+            "required": ["name"]
+        }))
+    }
+
+    async fn run(&self, client: &Aria2Client, args: Value) -> Result<Value> {
+        let name = args
+            .get("name")
+            .and_then(|v| v.as_str())
+            .context("Missing 'name'")?;
+
+        {
+            let config = client.config();
+            let mut config_guard = config.write().await;
+            let feeds = &mut config_guard.rss_config.feeds;
+            let before = feeds.len();
+            feeds.retain(|f| f.name != name);
+            if feeds.len() == before {
+                return Err(anyhow::anyhow!("RSS feed '{name}' not found"));
             }
+            // Keeps a feed from the config file from coming back on restart
+            config_guard.rss_config.mark_removed(name);
         }
+        client.save_state().await?;
+
+        Ok(json!({
+            "status": "success",
+            "message": format!("Removed RSS feed '{}'", name)
+        }))
+    }
+}
+
+pub struct UpdateRssFeedTool;
+
+#[async_trait]
+impl McpeTool for UpdateRssFeedTool {
+    fn name(&self) -> String {
+        "update_rss_feed".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Change the URL, name, filters, polling interval or download options of a monitored RSS feed".to_string()
+    }
+
+    fn schema(&self) -> Result<Value> {
+        Ok(json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "The name of the feed to update"
+                },
+                "url": {
+                    "type": "string",
+                    "description": "New URL of the RSS feed"
+                },
+                "newName": {
+                    "type": "string",
+                    "description": "New name for the feed"
+                },
+                "filters": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Replacement filters, in the same form as add_rss_feed; an empty list matches every item"
+                },
+                "exclude": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Replacement exclude filters"
+                },
+                "intervalSecs": {
+                    "type": ["integer", "null"],
+                    "minimum": 1,
+                    "description": "New polling interval in seconds; null restores the default"
+                },
+                "options": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Replacement aria2 options for downloads from this feed"
+                }
+            },
+            "required": ["name"]
+        }))
+    }
+
+    async fn run(&self, client: &Aria2Client, args: Value) -> Result<Value> {
+        let name = args
+            .get("name")
+            .and_then(|v| v.as_str())
+            .context("Missing 'name'")?;
+        let new_name = args.get("newName").and_then(|v| v.as_str());
+        let filters = args
+            .get("filters")
+            .map(|_| parse_filters(&args, "filters"))
+            .transpose()?;
+        let exclude = args
+            .get("exclude")
+            .map(|_| parse_filters(&args, "exclude"))
+            .transpose()?;
+        let interval_secs = parse_interval(&args)?;
+        let options = parse_options(&args)?;
+
+        let feed = {
+            let config = client.config();
+            let mut config_guard = config.write().await;
+            let feeds = &mut config_guard.rss_config.feeds;
+            if let Some(new_name) = new_name.filter(|n| *n != name) {
+                if feeds.iter().any(|f| f.name == new_name) {
+                    return Err(anyhow::anyhow!("RSS feed '{new_name}' already exists"));
+                }
+            }
+            let feed = feeds
+                .iter_mut()
+                .find(|f| f.name == name)
+                .ok_or_else(|| anyhow::anyhow!("RSS feed '{name}' not found"))?;
 
-        let feed = RSSFeed {
-            url: url.clone(),
-            name: name.clone(),
-            filters,
-            download_history: std::collections::HashSet::new(),
+            if let Some(url) = args.get("url").and_then(|v| v.as_str()) {
+                feed.url = url.to_string();
+            }
+            if let Some(new_name) = new_name {
+                feed.name = new_name.to_string();
+            }
+            if let Some(filters) = filters {
+                feed.filters = filters;
+            }
+            if let Some(exclude) = exclude {
+                feed.exclude = exclude;
+            }
+            if let Some(interval_secs) = interval_secs {
+                feed.interval_secs = interval_secs;
+            }
+            if let Some(options) = options {
+                feed.options = options;
+            }
+            let feed = feed.clone();
+            if feed.name != name {
+                config_guard.rss_config.mark_removed(name);
+                config_guard.rss_config.unmark_removed(&feed.name);
+            }
+            feed
         };
+        client.save_state().await?;
 
-        let config = client.config();
-        let mut config_guard = config.write().await;
-        config_guard.rss_config.feeds.push(feed);
+        Ok(json!({
+            "status": "success",
+            "feed": {
+                "name": feed.name,
+                "url": feed.url,
+                "filters": feed.filters,
+                "exclude": feed.exclude,
+                "intervalSecs": feed.interval_secs,
+                "options": feed.options
+            }
+        }))
+    }
+}
+
+pub struct ClearHistoryTool;
+
+#[async_trait]
+impl McpeTool for ClearHistoryTool {
+    fn name(&self) -> String {
+        "clear_history".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Clear the RSS download history of one feed, or of all feeds, so matching items are downloaded again".to_string()
+    }
+
+    fn schema(&self) -> Result<Value> {
+        Ok(json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "The feed whose history to clear. Omit to clear every feed"
+                }
+            }
+        }))
+    }
+
+    async fn run(&self, client: &Aria2Client, args: Value) -> Result<Value> {
+        let name = args.get("name").and_then(|v| v.as_str());
+
+        let cleared = {
+            let config = client.config();
+            let mut config_guard = config.write().await;
+            let mut cleared = 0;
+            let mut found = false;
+            for feed in &mut config_guard.rss_config.feeds {
+                if name.is_some_and(|n| n != feed.name) {
+                    continue;
+                }
+                found = true;
+                cleared += feed.download_history.len();
+                feed.download_history.clear();
+            }
+            if let Some(name) = name.filter(|_| !found) {
+                return Err(anyhow::anyhow!("RSS feed '{name}' not found"));
+            }
+            cleared
+        };
+        client.save_state().await?;
 
         Ok(json!({
             "status": "success",
-            "message": format!("Added RSS feed '{}' ({})", name, url)
+            "cleared": cleared
+        }))
+    }
+}
+
+pub struct PreviewRssFeedTool;
+
+#[async_trait]
+impl McpeTool for PreviewRssFeedTool {
+    fn name(&self) -> String {
+        "preview_rss_feed".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Fetch a feed now and show which items its filters would download, and why, without adding anything".to_string()
+    }
+
+    fn schema(&self) -> Result<Value> {
+        Ok(json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "A monitored feed to preview, including its download history"
+                },
+                "url": {
+                    "type": "string",
+                    "description": "Feed URL to preview instead of the monitored feed's, or on its own to try an unmonitored feed"
+                },
+                "filters": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Filters to try instead of the feed's own, in the same form as add_rss_feed"
+                },
+                "exclude": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Exclude filters to try instead of the feed's own"
+                }
+            }
+        }))
+    }
+
+    async fn run(&self, client: &Aria2Client, args: Value) -> Result<Value> {
+        let name = args.get("name").and_then(|v| v.as_str());
+        let url = args.get("url").and_then(|v| v.as_str());
+
+        let mut feed = match name {
+            Some(name) => find_feed(client, name).await?,
+            None => RSSFeed::default(),
+        };
+        match url {
+            Some(url) => feed.url = url.to_string(),
+            None if name.is_none() => return Err(anyhow::anyhow!("Provide 'name' or 'url'")),
+            None => {}
+        }
+        if args.get("filters").is_some() {
+            feed.filters = parse_filters(&args, "filters")?;
+        }
+        if args.get("exclude").is_some() {
+            feed.exclude = parse_filters(&args, "exclude")?;
+        }
+
+        let filters = FeedFilters::new(&feed)?;
+        let items = fetch_feed(&feed.url).await?;
+        let mut matched = 0;
+        let items = items
+            .iter()
+            .map(|item| {
+                let decision = item.decide(&feed, &filters);
+                if decision.download {
+                    matched += 1;
+                }
+                let mut value = serde_json::to_value(item)?;
+                value["wouldDownload"] = json!(decision.download);
+                value["reason"] = json!(decision.reason);
+                Ok(value)
+            })
+            .collect::<Result<Vec<Value>>>()?;
+
+        Ok(json!({
+            "url": feed.url,
+            "total": items.len(),
+            "matched": matched,
+            "items": items
         }))
     }
 }
 
-pub struct ListRssFeedsTool;
+pub struct PollRssFeedTool;
 
 #[async_trait]
-impl McpeTool for ListRssFeedsTool {
+impl McpeTool for PollRssFeedTool {
     fn name(&self) -> String {
-        "list_rss_feeds".to_string()
+        "poll_rss_feed".to_string()
     }
 
     fn description(&self) -> String {
-        "List all monitored RSS feeds".to_string()
+        "Poll monitored feeds now instead of waiting for their interval, adding matching items to the queue".to_string()
     }
 
     fn schema(&self) -> Result<Value> {
         Ok(json!({
             "type": "object",
-            "properties": {}
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "The feed to poll. Omit to poll every feed"
+                }
+            }
         }))
     }
 
-    async fn run(&self, client: &Aria2Client, _args: Value) -> Result<Value> {
-        let config = client.config();
-        let config_guard = config.read().await;
+    async fn run(&self, client: &Aria2Client, args: Value) -> Result<Value> {
+        let names = match args.get("name").and_then(|v| v.as_str()) {
+            Some(name) => vec![find_feed(client, name).await?.name],
+            None => {
+                let config = client.config();
+                let config_guard = config.read().await;
+                config_guard
+                    .rss_config
+                    .feeds
+                    .iter()
+                    .map(|f| f.name.clone())
+                    .collect()
+            }
+        };
 
-        Ok(json!({
-            "feeds": config_guard.rss_config.feeds
-        }))
+        let mut changed = false;
+        let mut results = Vec::new();
+        for name in names {
+            match poll_feed(client, &name).await {
+                Ok(added) => {
+                    changed |= !added.is_empty();
+                    let titles: Vec<String> = added.into_iter().map(|item| item.title).collect();
+                    results.push(json!({ "name": name, "added": titles }));
+                }
+                Err(e) => results.push(json!({ "name": name, "error": e.to_string() })),
+            }
+        }
+        if changed {
+            client.save_state().await?;
+        }
+
+        Ok(json!({ "feeds": results }))
     }
 }
 
+/// Returns a copy of the monitored feed called `name`.
+async fn find_feed(client: &Aria2Client, name: &str) -> Result<RSSFeed> {
+    let config = client.config();
+    let config_guard = config.read().await;
+    config_guard
+        .rss_config
+        .feeds
+        .iter()
+        .find(|f| f.name == name)
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("RSS feed '{name}' not found"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::Config;
-    use wiremock::matchers::{method, path};
+    use wiremock::matchers::{body_string_contains, method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[tokio::test]
@@ -281,6 +1157,7 @@ mod tests {
             name: "test".to_string(),
             rpc_url: format!("{}/jsonrpc", mock_server.uri()),
             rpc_secret: None,
+            rpc_transport: None,
         }];
         let client = Aria2Client::new_with_instance(config.clone(), config.instances[0].clone());
 
@@ -288,7 +1165,8 @@ mod tests {
             url: format!("{}/rss", mock_server.uri()),
             name: "test_feed".to_string(),
             filters: vec![RSSFilter::Keyword("Test".to_string())],
-            download_history: std::collections::HashSet::new(),
+            download_history: HashMap::new(),
+            ..RSSFeed::default()
         };
 
         process_feed(&client, &mut feed).await.unwrap();
@@ -314,30 +1192,794 @@ mod tests {
             url: format!("{}/rss", mock_server.uri()),
             name: "test_feed".to_string(),
             filters: vec![],
-            download_history: std::collections::HashSet::new(),
+            download_history: HashMap::new(),
+            ..RSSFeed::default()
         };
 
         let result = process_feed(&client, &mut feed).await;
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_feed_atom() {
+        let atom = r#"<?xml version="1.0" encoding="utf-8"?>
+            <feed xmlns="http://www.w3.org/2005/Atom">
+                <title>Atom Feed</title>
+                <id>urn:feed</id>
+                <updated>2026-01-01T00:00:00Z</updated>
+                <entry>
+                    <title>Ubuntu 24.04</title>
+                    <id>urn:ubuntu</id>
+                    <updated>2026-01-01T00:00:00Z</updated>
+                    <link rel="alternate" href="http://example.com/ubuntu"/>
+                    <link rel="enclosure" href="http://example.com/ubuntu.torrent" length="1048576"/>
+                    <category term="linux" label="Linux ISOs"/>
+                    <summary>Desktop image</summary>
+                </entry>
+            </feed>"#;
+
+        let items = parse_feed(atom.as_bytes()).unwrap();
+        assert_eq!(
+            items,
+            vec![FeedItem {
+                id: "urn:ubuntu".to_string(),
+                title: "Ubuntu 24.04".to_string(),
+                link: Some("http://example.com/ubuntu.torrent".to_string()),
+                description: Some("Desktop image".to_string()),
+                categories: vec!["linux".to_string(), "Linux ISOs".to_string()],
+                size: Some(1_048_576),
+            }]
+        );
+
+        assert!(parse_feed(b"not a feed").is_err());
+    }
+
+    #[test]
+    fn test_parse_feed_torznab_attributes() {
+        let rss = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <rss version="2.0" xmlns:torznab="http://torznab.com/schemas/2015/feed">
+            <channel>
+                <title>Indexer</title>
+                <item>
+                    <title>Show S01E01</title>
+                    <guid>show-1</guid>
+                    <enclosure url="http://example.com/show.torrent" length="1" type="application/x-bittorrent"/>
+                    <category>TV</category>
+                    <torznab:attr name="category" value="5040"/>
+                    <torznab:attr name="size" value="734003200"/>
+                </item>
+            </channel>
+            </rss>"#;
+
+        let items = parse_feed(rss.as_bytes()).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, "show-1");
+        assert_eq!(
+            items[0].link.as_deref(),
+            Some("http://example.com/show.torrent")
+        );
+        assert_eq!(items[0].categories, vec!["TV", "5040"]);
+        assert_eq!(items[0].size, Some(734_003_200));
+    }
+
+    #[test]
+    fn test_parse_feed_prefers_enclosure_over_link() {
+        let rss = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <rss version="2.0">
+            <channel>
+                <title>Indexer</title>
+                <item>
+                    <title>Show S01E02</title>
+                    <link>http://example.com/details/2</link>
+                    <enclosure url="http://example.com/show-2.torrent" length="1" type="application/x-bittorrent"/>
+                </item>
+                <item>
+                    <title>Show S01E03</title>
+                    <link>http://example.com/show-3.torrent</link>
+                </item>
+            </channel>
+            </rss>"#;
+
+        let items = parse_feed(rss.as_bytes()).unwrap();
+        assert_eq!(
+            items[0].link.as_deref(),
+            Some("http://example.com/show-2.torrent")
+        );
+        // The details page still identifies the item
+        assert_eq!(items[0].id, "http://example.com/details/2");
+        assert_eq!(
+            items[1].link.as_deref(),
+            Some("http://example.com/show-3.torrent")
+        );
+    }
+
+    #[test]
+    fn test_feed_item_matches() {
+        let item = FeedItem {
+            id: "1".to_string(),
+            title: "Ubuntu 24.04 Desktop".to_string(),
+            link: None,
+            description: Some("Long term support release".to_string()),
+            categories: vec!["Linux".to_string(), "4000".to_string()],
+            size: Some(5 * 1024 * 1024 * 1024),
+        };
+        let filters = |list: &[&str]| -> Vec<RSSFilter> {
+            list.iter()
+                .map(|f| RSSFilter::from((*f).to_string()))
+                .collect()
+        };
+
+        assert!(item.matches(&[]));
+        assert!(item.matches(&filters(&["debian", "ubuntu"])));
+        assert!(item.matches(&filters(&["regex:^ubuntu", "category:linux"])));
+        assert!(item.matches(&filters(&["category:4000", "size:1GB-6GB"])));
+        assert!(item.matches(&filters(&["description:LONG TERM", "size:4.5G-"])));
+        assert!(!item.matches(&filters(&["ubuntu", "category:tv"])));
+        assert!(!item.matches(&filters(&["size:-2GB"])));
+        assert!(!item.matches(&filters(&["size:lots"])));
+
+        let unsized_item = FeedItem { size: None, ..item };
+        assert!(!unsized_item.matches(&filters(&["size:0-"])));
+    }
+
+    #[test]
+    fn test_parse_size_range() {
+        assert_eq!(parse_size("1024"), Some(1024));
+        assert_eq!(parse_size("1.5K"), Some(1536));
+        assert_eq!(parse_size("700MB"), Some(700 * 1024 * 1024));
+        assert_eq!(parse_size("2 GiB"), Some(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("2PB"), None);
+        assert_eq!(
+            parse_size_range("100MB-2GB"),
+            Some((Some(100 * 1024 * 1024), Some(2 * 1024 * 1024 * 1024)))
+        );
+        assert_eq!(parse_size_range("-1K"), Some((None, Some(1024))));
+        assert_eq!(parse_size_range("1K"), None);
+    }
+
+    #[test]
+    fn test_is_due() {
+        let now = Instant::now();
+        let every = Duration::from_secs(60);
+        assert!(is_due(None, every, now));
+        assert!(!is_due(Some(now), every, now));
+        assert!(is_due(Some(now), every, now + every));
+    }
+
+    #[tokio::test]
+    async fn test_process_feed_options_and_exclude() {
+        let mock_server = MockServer::start().await;
+
+        let rss_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <rss version="2.0">
+            <channel>
+                <title>Test Feed</title>
+                <item>
+                    <title>Show 1080p</title>
+                    <link>http://example.com/item1</link>
+                    <guid>item1</guid>
+                </item>
+                <item>
+                    <title>Show 480p</title>
+                    <link>http://example.com/item2</link>
+                    <guid>item2</guid>
+                </item>
+            </channel>
+            </rss>"#;
+
+        Mock::given(method("GET"))
+            .and(path("/rss"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(rss_content))
+            .mount(&mock_server)
+            .await;
+
+        // Only answer downloads that carry the feed's options
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .and(body_string_contains(r#""dir":"/downloads/tv""#))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "1",
+                "result": "gid123"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut config = Config::default();
+        config.instances = vec![crate::config::Aria2Instance {
+            name: "test".to_string(),
+            rpc_url: format!("{}/jsonrpc", mock_server.uri()),
+            rpc_secret: None,
+            rpc_transport: None,
+        }];
+        let client = Aria2Client::new_with_instance(config.clone(), config.instances[0].clone());
+
+        let mut feed = RSSFeed {
+            url: format!("{}/rss", mock_server.uri()),
+            name: "tv".to_string(),
+            filters: vec![RSSFilter::Keyword("show".to_string())],
+            exclude: vec![RSSFilter::Keyword("480p".to_string())],
+            options: HashMap::from([("dir".to_string(), "/downloads/tv".to_string())]),
+            ..RSSFeed::default()
+        };
+
+        process_feed(&client, &mut feed).await.unwrap();
+
+        assert!(feed.has_downloaded("item1"));
+        assert!(!feed.has_downloaded("item2"));
+    }
+
+    #[test]
+    fn test_feed_item_decide() {
+        let item = FeedItem {
+            id: "1".to_string(),
+            title: "Show 480p".to_string(),
+            link: Some("http://example.com/1".to_string()),
+            categories: vec!["TV".to_string()],
+            ..FeedItem::default()
+        };
+        let mut feed = RSSFeed::default();
+        let decide = |feed: &RSSFeed| item.decide(feed, &FeedFilters::new(feed).unwrap());
+
+        assert_eq!(
+            decide(&feed),
+            ItemDecision {
+                download: true,
+                reason: "no filters, every item matches".to_string()
+            }
+        );
+
+        feed.filters = vec![
+            RSSFilter::Keyword("show".to_string()),
+            RSSFilter::Keyword("movie".to_string()),
+            RSSFilter::Category("tv".to_string()),
+        ];
+        assert_eq!(decide(&feed).reason, "matched 'show', 'category:tv'");
+
+        feed.filters.push(RSSFilter::Size("1GB-".to_string()));
+        assert_eq!(
+            decide(&feed),
+            ItemDecision {
+                download: false,
+                reason: "no size filter matched".to_string()
+            }
+        );
+
+        feed.filters.pop();
+        feed.exclude = vec![RSSFilter::Regex("\\d+p$".to_string())];
+        assert_eq!(decide(&feed).reason, "excluded by 'regex:\\d+p$'");
+
+        feed.mark_downloaded("1".to_string());
+        assert_eq!(decide(&feed).reason, "already downloaded");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_filters_are_rejected() {
+        assert!(FeedFilter::new(&RSSFilter::Regex("[".to_string())).is_err());
+        assert!(FeedFilter::new(&RSSFilter::Size("2GB".to_string())).is_err());
+        assert!(FeedFilter::new(&RSSFilter::Size("1XB-2GB".to_string())).is_err());
+        assert!(FeedFilter::new(&RSSFilter::Size("-2GB".to_string())).is_ok());
+
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_state(&dir);
+        let err = AddRssFeedTool
+            .run(
+                &client,
+                json!({ "url": "http://example.com/rss", "name": "bad", "filters": ["regex:("] }),
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("Invalid regex filter '('"),
+            "{err}"
+        );
+
+        AddRssFeedTool
+            .run(
+                &client,
+                json!({ "url": "http://example.com/rss", "name": "good" }),
+            )
+            .await
+            .unwrap();
+        let err = UpdateRssFeedTool
+            .run(&client, json!({ "name": "good", "exclude": ["size:big"] }))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("Invalid size filter 'big'"),
+            "{err}"
+        );
+
+        let config = client.config();
+        let config_guard = config.read().await;
+        assert_eq!(config_guard.rss_config.feeds.len(), 1);
+        assert!(config_guard.rss_config.feeds[0].exclude.is_empty());
+    }
+
+    /// Serves `rss` at `/rss` and answers aria2 `addUri` calls at `/jsonrpc`,
+    /// expecting `adds` of them.
+    async fn mock_feed_server(rss: &'static str, adds: u64) -> MockServer {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rss"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(rss))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "1",
+                "result": "gid123"
+            })))
+            .expect(adds)
+            .mount(&mock_server)
+            .await;
+        mock_server
+    }
+
+    const PREVIEW_FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <rss version="2.0">
+        <channel>
+            <title>Test Feed</title>
+            <item>
+                <title>Ubuntu 24.04</title>
+                <link>http://example.com/ubuntu</link>
+                <guid>ubuntu</guid>
+            </item>
+            <item>
+                <title>Fedora 40</title>
+                <link>http://example.com/fedora</link>
+                <guid>fedora</guid>
+            </item>
+        </channel>
+        </rss>"#;
+
+    #[tokio::test]
+    async fn test_preview_rss_feed_tool() {
+        let mock_server = mock_feed_server(PREVIEW_FEED, 0).await;
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_rpc(&dir, format!("{}/jsonrpc", mock_server.uri()));
+        let feed_url = format!("{}/rss", mock_server.uri());
+
+        // An unmonitored feed with ad-hoc filters
+        let result = PreviewRssFeedTool
+            .run(&client, json!({ "url": feed_url, "filters": ["ubuntu"] }))
+            .await
+            .unwrap();
+        assert_eq!(result["total"], 2);
+        assert_eq!(result["matched"], 1);
+        assert_eq!(result["items"][0]["title"], "Ubuntu 24.04");
+        assert_eq!(result["items"][0]["wouldDownload"], true);
+        assert_eq!(result["items"][0]["reason"], "matched 'ubuntu'");
+        assert_eq!(result["items"][1]["wouldDownload"], false);
+        assert_eq!(result["items"][1]["reason"], "no title filter matched");
+
+        // A monitored feed takes its history into account
+        AddRssFeedTool
+            .run(&client, json!({ "url": feed_url, "name": "isos" }))
+            .await
+            .unwrap();
+        {
+            let config = client.config();
+            let mut config_guard = config.write().await;
+            config_guard.rss_config.feeds[0].mark_downloaded("fedora".to_string());
+        }
+        let result = PreviewRssFeedTool
+            .run(&client, json!({ "name": "isos", "exclude": ["24.04"] }))
+            .await
+            .unwrap();
+        assert_eq!(result["matched"], 0);
+        assert_eq!(result["items"][0]["reason"], "excluded by '24.04'");
+        assert_eq!(result["items"][1]["reason"], "already downloaded");
+
+        assert!(PreviewRssFeedTool.run(&client, json!({})).await.is_err());
+        assert!(PreviewRssFeedTool
+            .run(&client, json!({ "name": "missing" }))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_poll_rss_feed_tool() {
+        let mock_server = mock_feed_server(PREVIEW_FEED, 1).await;
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_rpc(&dir, format!("{}/jsonrpc", mock_server.uri()));
+        AddRssFeedTool
+            .run(
+                &client,
+                json!({
+                    "url": format!("{}/rss", mock_server.uri()),
+                    "name": "isos",
+                    "filters": ["ubuntu"]
+                }),
+            )
+            .await
+            .unwrap();
+
+        let result = PollRssFeedTool
+            .run(&client, json!({ "name": "isos" }))
+            .await
+            .unwrap();
+        assert_eq!(result["feeds"][0]["added"], json!(["Ubuntu 24.04"]));
+
+        // Already downloaded items are not added again
+        let result = PollRssFeedTool.run(&client, json!({})).await.unwrap();
+        assert_eq!(result["feeds"][0]["added"], json!([]));
+
+        let state = client.state_manager().load().await.unwrap();
+        assert!(state.rss_feeds["default"][0].has_downloaded("ubuntu"));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_polls_add_items_once() {
+        let mock_server = MockServer::start().await;
+        // Slow enough that both polls would read the history before either records it
+        Mock::given(method("GET"))
+            .and(path("/rss"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(PREVIEW_FEED)
+                    .set_delay(std::time::Duration::from_millis(200)),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/jsonrpc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "1",
+                "result": "gid123"
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_rpc(&dir, format!("{}/jsonrpc", mock_server.uri()));
+        AddRssFeedTool
+            .run(
+                &client,
+                json!({
+                    "url": format!("{}/rss", mock_server.uri()),
+                    "name": "isos",
+                    "filters": ["ubuntu"]
+                }),
+            )
+            .await
+            .unwrap();
+
+        let (first, second) = tokio::join!(poll_feed(&client, "isos"), poll_feed(&client, "isos"));
+        assert_eq!(first.unwrap().len() + second.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_record_history_merges_added_items() {
+        let client = Aria2Client::new(Config::default());
+        let polled = RSSFeed {
+            name: "isos".to_string(),
+            download_history: HashMap::from([("old".to_string(), 1)]),
+            ..RSSFeed::default()
+        };
+        {
+            // The history was cleared while the feed was being polled
+            let config = client.config();
+            let mut config_guard = config.write().await;
+            config_guard.rss_config.feeds.push(RSSFeed {
+                download_history: HashMap::new(),
+                ..polled.clone()
+            });
+        }
+
+        let added = vec![FeedItem {
+            id: "new".to_string(),
+            ..FeedItem::default()
+        }];
+        record_history(&client, &polled.name, &added).await;
+        // A feed removed meanwhile is left removed
+        record_history(&client, "removed", &added).await;
+
+        let config = client.config();
+        let config_guard = config.read().await;
+        let feed = &config_guard.rss_config.feeds[0];
+        assert!(feed.has_downloaded("new"));
+        assert!(!feed.has_downloaded("old"));
+    }
+
+    /// A client whose state file lives in `dir` instead of the working directory.
+    fn client_with_state(dir: &tempfile::TempDir) -> Aria2Client {
+        client_with_rpc(dir, Config::default().rpc_url)
+    }
+
+    fn client_with_rpc(dir: &tempfile::TempDir, rpc_url: String) -> Aria2Client {
+        let mut client = Aria2Client::new(Config {
+            rpc_url,
+            ..Config::default()
+        });
+        client.state_manager = Arc::new(crate::state::StateManager::new(
+            dir.path().join("state.json"),
+        ));
+        client
+    }
+
     #[tokio::test]
     async fn test_add_rss_feed_tool() {
-        let config = Config::default();
-        let client = Aria2Client::new(config);
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_state(&dir);
         let tool = AddRssFeedTool;
         let args = json!({
             "url": "http://example.com/rss",
             "name": "test",
             "filters": ["keyword", "regex:.*"]
         });
-        let result = tool.run(&client, args).await.unwrap();
+        let result = tool.run(&client, args.clone()).await.unwrap();
         assert_eq!(result["status"], "success");
 
-        let config = client.config();
+        {
+            let config = client.config();
+            let config_guard = config.read().await;
+            assert_eq!(config_guard.rss_config.feeds.len(), 1);
+            assert_eq!(config_guard.rss_config.feeds[0].name, "test");
+            assert_eq!(
+                config_guard.rss_config.feeds[0].filters[1],
+                RSSFilter::Regex(".*".to_string())
+            );
+        }
+
+        let state = client.state_manager().load().await.unwrap();
+        assert_eq!(state.rss_feeds["default"][0].name, "test");
+
+        // Feed names are unique
+        assert!(tool.run(&client, args).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rss_feeds_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_state(&dir);
+        AddRssFeedTool
+            .run(
+                &client,
+                json!({ "url": "http://example.com/rss", "name": "test" }),
+            )
+            .await
+            .unwrap();
+        {
+            let config = client.config();
+            let mut config_guard = config.write().await;
+            config_guard.rss_config.feeds[0].mark_downloaded("item1".to_string());
+        }
+        client.save_state().await.unwrap();
+
+        let restarted = client_with_state(&dir);
+        restarted.restore_rss_feeds().await.unwrap();
+        let config = restarted.config();
+        let config_guard = config.read().await;
+        assert_eq!(config_guard.rss_config.feeds.len(), 1);
+        assert!(config_guard.rss_config.feeds[0].has_downloaded("item1"));
+    }
+
+    #[tokio::test]
+    async fn test_restore_keeps_config_feed_definition() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_state(&dir);
+        {
+            let config = client.config();
+            let mut config_guard = config.write().await;
+            let mut feed = RSSFeed {
+                url: "http://old.example.com/rss".to_string(),
+                name: "test".to_string(),
+                filters: vec![],
+                download_history: HashMap::new(),
+                ..RSSFeed::default()
+            };
+            feed.mark_downloaded("item1".to_string());
+            config_guard.rss_config.feeds.push(feed);
+        }
+        client.save_state().await.unwrap();
+
+        let restarted = client_with_state(&dir);
+        {
+            let config = restarted.config();
+            let mut config_guard = config.write().await;
+            config_guard.rss_config.feeds.push(RSSFeed {
+                url: "http://new.example.com/rss".to_string(),
+                name: "test".to_string(),
+                filters: vec![],
+                download_history: HashMap::new(),
+                ..RSSFeed::default()
+            });
+        }
+        restarted.restore_rss_feeds().await.unwrap();
+
+        let config = restarted.config();
         let config_guard = config.read().await;
         assert_eq!(config_guard.rss_config.feeds.len(), 1);
-        assert_eq!(config_guard.rss_config.feeds[0].name, "test");
+        assert_eq!(
+            config_guard.rss_config.feeds[0].url,
+            "http://new.example.com/rss"
+        );
+        assert!(config_guard.rss_config.feeds[0].has_downloaded("item1"));
+    }
+
+    #[tokio::test]
+    async fn test_removed_config_feed_stays_removed_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_feed = || RSSFeed {
+            url: "http://example.com/rss".to_string(),
+            name: "from_config".to_string(),
+            ..RSSFeed::default()
+        };
+        let client = client_with_state(&dir);
+        client
+            .config()
+            .write()
+            .await
+            .rss_config
+            .feeds
+            .push(config_feed());
+        RemoveRssFeedTool
+            .run(&client, json!({ "name": "from_config" }))
+            .await
+            .unwrap();
+
+        // The config file still defines the feed
+        let restarted = client_with_state(&dir);
+        restarted
+            .config()
+            .write()
+            .await
+            .rss_config
+            .feeds
+            .push(config_feed());
+        restarted.restore_rss_feeds().await.unwrap();
+        assert!(restarted.config().read().await.rss_config.feeds.is_empty());
+
+        // Adding it again brings it back for good
+        AddRssFeedTool
+            .run(
+                &restarted,
+                json!({ "url": "http://example.com/rss", "name": "from_config" }),
+            )
+            .await
+            .unwrap();
+        let state = restarted.state_manager().load().await.unwrap();
+        assert!(state.removed_rss_feeds.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_update_rss_feed_tool() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_state(&dir);
+        AddRssFeedTool
+            .run(
+                &client,
+                json!({ "url": "http://example.com/rss", "name": "test", "filters": ["a"] }),
+            )
+            .await
+            .unwrap();
+
+        let result = UpdateRssFeedTool
+            .run(
+                &client,
+                json!({ "name": "test", "newName": "renamed", "filters": ["regex:^b"] }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result["feed"]["name"], "renamed");
+        assert_eq!(result["feed"]["url"], "http://example.com/rss");
+        assert_eq!(result["feed"]["filters"], json!(["regex:^b"]));
+
+        let state = client.state_manager().load().await.unwrap();
+        assert_eq!(state.rss_feeds["default"][0].name, "renamed");
+
+        let result = UpdateRssFeedTool
+            .run(
+                &client,
+                json!({
+                    "name": "renamed",
+                    "intervalSecs": 300,
+                    "exclude": ["category:xxx"],
+                    "options": { "dir": "/downloads", "split": 4 }
+                }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result["feed"]["intervalSecs"], 300);
+        assert_eq!(result["feed"]["exclude"], json!(["category:xxx"]));
+        assert_eq!(
+            result["feed"]["options"],
+            json!({ "dir": "/downloads", "split": "4" })
+        );
+
+        let result = UpdateRssFeedTool
+            .run(&client, json!({ "name": "renamed", "intervalSecs": null }))
+            .await
+            .unwrap();
+        assert!(result["feed"]["intervalSecs"].is_null());
+        assert_eq!(result["feed"]["options"]["dir"], "/downloads");
+
+        assert!(UpdateRssFeedTool
+            .run(&client, json!({ "name": "renamed", "intervalSecs": 0 }))
+            .await
+            .is_err());
+
+        let err = UpdateRssFeedTool
+            .run(&client, json!({ "name": "test" }))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not found"));
+    }
+
+    #[tokio::test]
+    async fn test_remove_rss_feed_tool() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_state(&dir);
+        AddRssFeedTool
+            .run(
+                &client,
+                json!({ "url": "http://example.com/rss", "name": "test" }),
+            )
+            .await
+            .unwrap();
+
+        let result = RemoveRssFeedTool
+            .run(&client, json!({ "name": "test" }))
+            .await
+            .unwrap();
+        assert_eq!(result["status"], "success");
+        assert!(client.config().read().await.rss_config.feeds.is_empty());
+
+        let state = client.state_manager().load().await.unwrap();
+        assert!(!state.rss_feeds.contains_key("default"));
+
+        assert!(RemoveRssFeedTool
+            .run(&client, json!({ "name": "test" }))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_clear_history_tool() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = client_with_state(&dir);
+        for name in ["a", "b"] {
+            AddRssFeedTool
+                .run(
+                    &client,
+                    json!({ "url": "http://example.com/rss", "name": name }),
+                )
+                .await
+                .unwrap();
+        }
+        {
+            let config = client.config();
+            let mut config_guard = config.write().await;
+            for feed in &mut config_guard.rss_config.feeds {
+                feed.mark_downloaded("item1".to_string());
+                feed.mark_downloaded("item2".to_string());
+            }
+        }
+
+        let result = ClearHistoryTool
+            .run(&client, json!({ "name": "a" }))
+            .await
+            .unwrap();
+        assert_eq!(result["cleared"], 2);
+        {
+            let config = client.config();
+            let config_guard = config.read().await;
+            assert!(config_guard.rss_config.feeds[0].download_history.is_empty());
+            assert_eq!(config_guard.rss_config.feeds[1].download_history.len(), 2);
+        }
+
+        let result = ClearHistoryTool.run(&client, json!({})).await.unwrap();
+        assert_eq!(result["cleared"], 2);
+
+        assert!(ClearHistoryTool
+            .run(&client, json!({ "name": "missing" }))
+            .await
+            .is_err());
     }
 
     #[tokio::test]
@@ -1,6 +1,7 @@
 use super::McpeTool;
 use crate::aria2::Aria2Client;
-use crate::config::{BandwidthProfile, BandwidthSchedule};
+use crate::bandwidth;
+use crate::config::{BandwidthProfile, BandwidthSchedule, DownloadLimit};
 use anyhow::{Context, Result};
 use serde_json::json;
 
@@ -13,7 +14,7 @@ impl McpeTool for ScheduleLimitsTool {
     }
 
     fn description(&self) -> String {
-        "Manage bandwidth speed profiles and schedules".to_string()
+        "Manage bandwidth profiles and the schedules that activate them. A profile sets overall speed limits, and optionally max concurrent downloads, seeding limits, limits for downloads matching a tag or pattern, and the instances it applies to".to_string()
     }
 
     fn schema(&self) -> Result<serde_json::Value> {
@@ -37,6 +38,45 @@ impl McpeTool for ScheduleLimitsTool {
                     "type": "string",
                     "description": "Max overall upload limit"
                 },
+                "max_concurrent_downloads": {
+                    "type": "integer",
+                    "description": "Max concurrent downloads while the profile is active"
+                },
+                "seed_ratio": {
+                    "type": "number",
+                    "description": "Seed ratio for torrents (e.g., 1.0)"
+                },
+                "seed_time": {
+                    "type": "integer",
+                    "description": "Seed time for torrents, in minutes"
+                },
+                "download_limits": {
+                    "type": "array",
+                    "description": "Limits for the downloads matching a rule; the first matching rule applies",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "tag": {
+                                "type": "string",
+                                "enum": ["torrent", "uri"],
+                                "description": "torrent: BitTorrent downloads. uri: HTTP(S)/FTP downloads"
+                            },
+                            "pattern": {
+                                "type": "string",
+                                "description": "Regular expression matched against the name, directory, file paths and URIs"
+                            },
+                            "max_download": { "type": "string", "description": "Max download limit per download" },
+                            "max_upload": { "type": "string", "description": "Max upload limit per download" },
+                            "seed_ratio": { "type": "number" },
+                            "seed_time": { "type": "integer", "description": "Minutes" }
+                        }
+                    }
+                },
+                "instances": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Instances the scheduler applies the profile to. Omit for every instance"
+                },
                 "schedule": {
                     "type": "object",
                     "properties": {
@@ -92,9 +132,34 @@ impl McpeTool for ScheduleLimitsTool {
                     .and_then(|v| v.as_str())
                     .context("Missing 'max_upload'")?;
 
+                let download_limits: Vec<DownloadLimit> = match args.get("download_limits") {
+                    Some(v) => {
+                        serde_json::from_value(v.clone()).context("Invalid 'download_limits'")?
+                    }
+                    None => Vec::new(),
+                };
+                for rule in &download_limits {
+                    if let Some(pattern) = &rule.pattern {
+                        regex::Regex::new(pattern)
+                            .with_context(|| format!("Invalid pattern '{pattern}'"))?;
+                    }
+                }
+                let instances: Vec<String> = match args.get("instances") {
+                    Some(v) => serde_json::from_value(v.clone()).context("Invalid 'instances'")?,
+                    None => Vec::new(),
+                };
+
                 let profile = BandwidthProfile {
                     max_download: max_download.to_string(),
                     max_upload: max_upload.to_string(),
+                    max_concurrent_downloads: args
+                        .get("max_concurrent_downloads")
+                        .and_then(serde_json::Value::as_u64)
+                        .map(|v| v as u32),
+                    seed_ratio: args.get("seed_ratio").and_then(serde_json::Value::as_f64),
+                    seed_time: args.get("seed_time").and_then(serde_json::Value::as_u64),
+                    download_limits,
+                    instances,
                 };
 
                 let config = client.config();
@@ -206,12 +271,14 @@ impl McpeTool for ScheduleLimitsTool {
                         .context(format!("Profile '{name}' not found"))?
                 };
 
-                let options = json!({
-                    "max-overall-download-limit": profile.max_download,
-                    "max-overall-upload-limit": profile.max_upload,
-                });
+                if !profile.applies_to(&client.name) {
+                    return Err(anyhow::anyhow!(
+                        "Profile '{name}' does not apply to instance '{}'",
+                        client.name
+                    ));
+                }
 
-                client.change_global_option(options).await?;
+                bandwidth::apply_profile(client, &profile).await?;
 
                 Ok(
                     json!({ "status": "success", "message": format!("Profile '{}' activated", name) }),
@@ -3,7 +3,7 @@ use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::{json, Value};
 
-use crate::aria2::Aria2Client;
+use crate::aria2::{Aria2Client, DownloadStatus, MultiCall};
 use crate::tools::registry::McpeTool;
 
 pub struct SearchDownloadsTool;
@@ -75,49 +75,38 @@ impl McpeTool for SearchDownloadsTool {
                         k.push("bittorrent".to_string());
                     }
                 }
-                if args.status.is_some() {
-                    if !k.contains(&"status".to_string()) {
-                        k.push("status".to_string());
-                    }
-                    if !k.contains(&"paused".to_string()) {
-                        k.push("paused".to_string());
-                    }
+                if args.status.is_some() && !k.contains(&"status".to_string()) {
+                    k.push("status".to_string());
                 }
             }
         }
 
+        let keys_param: Vec<Value> = keys.iter().map(|k| json!(k)).collect();
+        let paged_params = [vec![json!(0), json!(1000)], keys_param.clone()].concat();
+
+        let mut calls = MultiCall::new();
         match args.status.as_deref() {
             Some("active") => {
-                let active = client.tell_active(keys).await?;
-                if let Some(arr) = active.as_array() {
-                    all_downloads.extend(arr.clone());
-                }
+                calls.push("aria2.tellActive", keys_param);
             }
             Some("waiting" | "paused") => {
-                let waiting = client.tell_waiting(0, 1000, keys).await?;
-                if let Some(arr) = waiting.as_array() {
-                    all_downloads.extend(arr.clone());
-                }
+                calls.push("aria2.tellWaiting", paged_params);
             }
             Some("error" | "complete" | "removed") => {
-                let stopped = client.tell_stopped(0, 1000, keys).await?;
-                if let Some(arr) = stopped.as_array() {
-                    all_downloads.extend(arr.clone());
-                }
+                calls.push("aria2.tellStopped", paged_params);
             }
             _ => {
-                let active = client.tell_active(keys.clone()).await?;
-                if let Some(arr) = active.as_array() {
-                    all_downloads.extend(arr.clone());
-                }
-                let waiting = client.tell_waiting(0, 1000, keys.clone()).await?;
-                if let Some(arr) = waiting.as_array() {
-                    all_downloads.extend(arr.clone());
-                }
-                let stopped = client.tell_stopped(0, 1000, keys).await?;
-                if let Some(arr) = stopped.as_array() {
-                    all_downloads.extend(arr.clone());
-                }
+                calls
+                    .push("aria2.tellActive", keys_param)
+                    .push("aria2.tellWaiting", paged_params.clone())
+                    .push("aria2.tellStopped", paged_params);
+            }
+        }
+
+        // One round trip for all the lists that need searching
+        for result in client.multicall(&calls).await? {
+            if let Value::Array(arr) = result? {
+                all_downloads.extend(arr);
             }
         }
 
@@ -127,149 +116,114 @@ impl McpeTool for SearchDownloadsTool {
 }
 
 impl SearchDownloadsTool {
+    /// Filters raw aria2 status objects, returning them untouched so any
+    /// caller-selected `keys` are preserved in the output.
     fn filter_downloads(&self, downloads: Vec<Value>, args: &SearchDownloadsArgs) -> Vec<Value> {
         let regex = args.regex.as_ref().and_then(|r| regex::Regex::new(r).ok());
+        let query = args.query.as_ref().map(|q| q.to_lowercase());
 
         downloads
             .into_iter()
-            .filter(|item| {
-                // Filter by status if provided (paused is special as it's within waiting)
-                if let Some(status_filter) = &args.status {
-                    let item_status = item.get("status").and_then(|s| s.as_str()).unwrap_or("");
-                    if status_filter == "paused" {
-                        let paused_flag = item
-                            .get("paused")
-                            .and_then(|p| p.as_str().map(|s| s == "true").or_else(|| p.as_bool()))
-                            .unwrap_or(false);
-                        if item_status != "paused" && (item_status != "waiting" || !paused_flag) {
-                            return false;
-                        }
-                    } else if item_status != status_filter {
-                        return false;
-                    }
+            .filter(|item| match DownloadStatus::deserialize(item) {
+                Ok(download) => Self::matches(
+                    &download,
+                    args.status.as_deref(),
+                    regex.as_ref(),
+                    query.as_deref(),
+                ),
+                Err(e) => {
+                    // Still searchable, so a schema mismatch cannot hide downloads
+                    log::warn!(
+                        "Unexpected status for download {}: {e}",
+                        item.get("gid").and_then(Value::as_str).unwrap_or("?")
+                    );
+                    Self::matches_raw(
+                        item,
+                        args.status.as_deref(),
+                        regex.as_ref(),
+                        query.as_deref(),
+                    )
                 }
+            })
+            .collect()
+    }
 
-                // Filter by regex if provided
-                if let Some(re) = &regex {
-                    // Check files for path/name
-                    let files_match =
-                        if let Some(files) = item.get("files").and_then(|f| f.as_array()) {
-                            files.iter().any(|file| {
-                                file.get("path")
-                                    .and_then(|p| p.as_str())
-                                    .is_some_and(|p| re.is_match(p))
-                                    || file.get("uris").and_then(|u| u.as_array()).is_some_and(
-                                        |uris| {
-                                            uris.iter().any(|uri| {
-                                                uri.get("uri")
-                                                    .and_then(|u| u.as_str())
-                                                    .is_some_and(|u| re.is_match(u))
-                                            })
-                                        },
-                                    )
-                            })
-                        } else {
-                            false
-                        };
-
-                    if files_match {
-                        return true;
-                    }
+    fn matches(
+        download: &DownloadStatus,
+        status: Option<&str>,
+        regex: Option<&regex::Regex>,
+        query: Option<&str>,
+    ) -> bool {
+        if let Some(status) = status {
+            if download.status.map(|s| s.as_str()) != Some(status) {
+                return false;
+            }
+        }
 
-                    // Check bittorrent name and trackers if available
-                    if let Some(bt) = item.get("bittorrent") {
-                        if let Some(info) = bt.get("info") {
-                            if let Some(name) = info.get("name").and_then(|n| n.as_str()) {
-                                if re.is_match(name) {
-                                    return true;
-                                }
-                            }
-                        }
-
-                        // Check trackers in announceList
-                        if let Some(announce_list) =
-                            bt.get("announceList").and_then(|a| a.as_array())
-                        {
-                            for tier in announce_list {
-                                if let Some(urls) = tier.as_array() {
-                                    for url in urls {
-                                        if let Some(u) = url.as_str() {
-                                            if re.is_match(u) {
-                                                return true;
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+        // File paths, file URIs, torrent name and tracker URLs
+        let fields = download
+            .files
+            .iter()
+            .map(|f| f.path.as_str())
+            .chain(download.uris())
+            .chain(
+                download
+                    .bittorrent
+                    .iter()
+                    .filter_map(|bt| bt.info.as_ref())
+                    .map(|info| info.name.as_str()),
+            )
+            .chain(download.trackers());
+
+        Self::matches_fields(fields, regex, query)
+    }
 
-                    return false;
-                }
+    /// Fallback for status objects that do not deserialize: matches every
+    /// string under `files` and `bittorrent`.
+    fn matches_raw(
+        item: &Value,
+        status: Option<&str>,
+        regex: Option<&regex::Regex>,
+        query: Option<&str>,
+    ) -> bool {
+        if let Some(status) = status {
+            if item.get("status").and_then(Value::as_str) != Some(status) {
+                return false;
+            }
+        }
 
-                // Filter by query if provided (substring match, case-insensitive)
-                if let Some(query) = &args.query {
-                    let query = query.to_lowercase();
-
-                    // Check files for path/name
-                    let files_match =
-                        if let Some(files) = item.get("files").and_then(|f| f.as_array()) {
-                            files.iter().any(|file| {
-                                file.get("path")
-                                    .and_then(|p| p.as_str())
-                                    .is_some_and(|p| p.to_lowercase().contains(&query))
-                                    || file.get("uris").and_then(|u| u.as_array()).is_some_and(
-                                        |uris| {
-                                            uris.iter().any(|uri| {
-                                                uri.get("uri").and_then(|u| u.as_str()).is_some_and(
-                                                    |u| u.to_lowercase().contains(&query),
-                                                )
-                                            })
-                                        },
-                                    )
-                            })
-                        } else {
-                            false
-                        };
-
-                    if files_match {
-                        return true;
-                    }
+        let mut fields = Vec::new();
+        for key in ["files", "bittorrent"] {
+            if let Some(value) = item.get(key) {
+                collect_strings(value, &mut fields);
+            }
+        }
+        Self::matches_fields(fields.into_iter(), regex, query)
+    }
 
-                    // Check bittorrent name and trackers if available
-                    if let Some(bt) = item.get("bittorrent") {
-                        if let Some(info) = bt.get("info") {
-                            if let Some(name) = info.get("name").and_then(|n| n.as_str()) {
-                                if name.to_lowercase().contains(&query) {
-                                    return true;
-                                }
-                            }
-                        }
-
-                        // Check trackers in announceList
-                        if let Some(announce_list) =
-                            bt.get("announceList").and_then(|a| a.as_array())
-                        {
-                            for tier in announce_list {
-                                if let Some(urls) = tier.as_array() {
-                                    for url in urls {
-                                        if let Some(u) = url.as_str() {
-                                            if u.to_lowercase().contains(&query) {
-                                                return true;
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+    fn matches_fields<'a>(
+        mut fields: impl Iterator<Item = &'a str>,
+        regex: Option<&regex::Regex>,
+        query: Option<&str>,
+    ) -> bool {
+        if let Some(re) = regex {
+            return fields.any(|f| re.is_match(f));
+        }
 
-                    return false;
-                }
+        if let Some(query) = query {
+            return fields.any(|f| f.to_lowercase().contains(query));
+        }
 
-                true
-            })
-            .collect()
+        true
+    }
+}
+
+fn collect_strings<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+    match value {
+        Value::String(s) => out.push(s),
+        Value::Array(items) => items.iter().for_each(|v| collect_strings(v, out)),
+        Value::Object(map) => map.values().for_each(|v| collect_strings(v, out)),
+        _ => {}
     }
 }
 
@@ -307,8 +261,8 @@ mod tests {
         let tool = SearchDownloadsTool;
         let downloads = vec![
             json!({ "gid": "1", "status": "active" }),
-            json!({ "gid": "2", "status": "waiting", "paused": "false" }),
-            json!({ "gid": "3", "status": "waiting", "paused": "true" }),
+            json!({ "gid": "2", "status": "waiting" }),
+            json!({ "gid": "3", "status": "paused" }),
             json!({ "gid": "4", "status": "complete" }),
         ];
 
@@ -458,4 +412,28 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert_eq!(results[0]["gid"], "3");
     }
+
+    #[test]
+    fn test_filter_downloads_keeps_undeserializable_items() {
+        let tool = SearchDownloadsTool;
+        // `files` of the wrong shape fails `DownloadStatus` deserialization
+        let downloads = vec![
+            json!({
+                "gid": "1",
+                "status": "active",
+                "files": { "path": "/downloads/movie.mp4" }
+            }),
+            json!({ "gid": "2", "status": "active", "files": "other.txt" }),
+        ];
+
+        let args = SearchDownloadsArgs {
+            query: Some("movie".to_string()),
+            status: Some("active".to_string()),
+            keys: None,
+            regex: None,
+        };
+        let results = tool.filter_downloads(downloads, &args);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["gid"], "1");
+    }
 }
@@ -116,6 +116,7 @@ fn test_to_mcp_notification() {
         params: vec![Aria2EventParams {
             gid: "123".to_string(),
         }],
+        instance: None,
     };
 
     let mcp = notification.to_mcp_notification();
@@ -1,7 +1,10 @@
-use aria2_mcp_rs::aria2::recovery::{ErrorAnalyzer, RecoveryManager, RetryConfig, TrackerScraper};
-use aria2_mcp_rs::aria2::Aria2Client;
+use aria2_mcp_rs::aria2::recovery::{
+    magnet_link, merge_trackers, ErrorAnalyzer, ErrorPolicy, RecoveryAction, RecoveryManager,
+    RecoveryStrategy, RetryConfig, RetryLineage, RetryState, TrackerInjections, TrackerScraper,
+};
+use aria2_mcp_rs::aria2::{Aria2Client, DownloadStatus};
 use aria2_mcp_rs::config::Config;
-use wiremock::matchers::{method, path};
+use wiremock::matchers::{body_partial_json, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 #[test]
@@ -57,6 +60,7 @@ fn test_retry_config_defaults() {
     assert_eq!(config.max_retries, 3);
     assert_eq!(config.initial_backoff_secs, 5);
     assert!(!config.tracker_injection_enabled);
+    assert_eq!(config.state_max_age_secs, 604_800);
 }
 
 #[tokio::test]
@@ -152,7 +156,9 @@ async fn test_recovery_manager_perform_retry() -> anyhow::Result<()> {
             match method {
                 "aria2.tellStatus" => ResponseTemplate::new(200).set_body_json(&status_response),
                 "aria2.addUri" => ResponseTemplate::new(200).set_body_json(&add_uri_response),
-                "aria2.remove" => ResponseTemplate::new(200).set_body_json(&remove_response),
+                "aria2.removeDownloadResult" => {
+                    ResponseTemplate::new(200).set_body_json(&remove_response)
+                }
                 _ => ResponseTemplate::new(404),
             }
         })
@@ -230,6 +236,7 @@ async fn test_recovery_manager_retry_limit() -> anyhow::Result<()> {
         initial_backoff_secs: 1,
         ..Default::default()
     });
+    let client = Aria2Client::new(Config::default());
 
     let status = serde_json::json!({
         "status": "error",
@@ -239,26 +246,688 @@ async fn test_recovery_manager_retry_limit() -> anyhow::Result<()> {
 
     // First retry
     let backoff1 = manager
-        .analyze_and_get_retry_backoff("test-gid", &status)
+        .analyze_and_get_retry_backoff(&client, "test-gid", &status)
         .await;
     assert_eq!(backoff1, Some(1));
 
     // Clear pending for test (normally perform_retry would do this)
-    manager.perform_retry_cleanup_for_test("test-gid").await;
+    manager
+        .perform_retry_cleanup_for_test(&client, "test-gid")
+        .await;
 
     // Second retry
     let backoff2 = manager
-        .analyze_and_get_retry_backoff("test-gid", &status)
+        .analyze_and_get_retry_backoff(&client, "test-gid", &status)
         .await;
     assert_eq!(backoff2, Some(2));
 
-    manager.perform_retry_cleanup_for_test("test-gid").await;
+    manager
+        .perform_retry_cleanup_for_test(&client, "test-gid")
+        .await;
 
     // Third retry - should be None (limit reached)
     let backoff3 = manager
-        .analyze_and_get_retry_backoff("test-gid", &status)
+        .analyze_and_get_retry_backoff(&client, "test-gid", &status)
         .await;
     assert_eq!(backoff3, None);
+    let state = client.retries.get("test-gid").unwrap();
+    assert_eq!(state.attempts, 2);
+    assert!(state.gave_up);
+
+    Ok(())
+}
+
+#[test]
+fn test_error_analyzer_policies() {
+    let analyzer = ErrorAnalyzer::with_policies(&[
+        ErrorPolicy {
+            error_codes: vec![1],
+            message_pattern: Some("(?i)too many connections".to_string()),
+            strategy: RecoveryStrategy::ReduceConnections,
+        },
+        ErrorPolicy {
+            error_codes: vec![3],
+            message_pattern: None,
+            strategy: RecoveryStrategy::AlternateMirror,
+        },
+        ErrorPolicy {
+            error_codes: vec![],
+            message_pattern: Some("[invalid".to_string()),
+            strategy: RecoveryStrategy::Backoff,
+        },
+        ErrorPolicy {
+            error_codes: vec![24],
+            message_pattern: None,
+            strategy: RecoveryStrategy::GiveUp,
+        },
+    ]);
+
+    assert_eq!(
+        analyzer.classify("1", "Too many connections to host"),
+        Some(RecoveryStrategy::ReduceConnections)
+    );
+    assert_eq!(analyzer.classify("1", "Unknown error"), None);
+    assert_eq!(
+        analyzer.classify("3", ""),
+        Some(RecoveryStrategy::AlternateMirror)
+    );
+    // The policy with the invalid pattern is skipped rather than matching everything
+    assert_eq!(analyzer.classify("2", "Timeout"), None);
+    assert_eq!(analyzer.classify("24", ""), Some(RecoveryStrategy::GiveUp));
+    assert!(!analyzer.is_retryable("24"));
+    assert!(!analyzer.should_retry(&serde_json::json!({
+        "status": "error",
+        "errorCode": "1",
+        "errorMessage": "Unknown error"
+    })));
+}
+
+#[tokio::test]
+async fn test_plan_recovery_give_up_policy() {
+    let manager = RecoveryManager::new(RetryConfig {
+        policies: vec![ErrorPolicy {
+            error_codes: vec![9],
+            message_pattern: None,
+            strategy: RecoveryStrategy::GiveUp,
+        }],
+        ..Default::default()
+    });
+    let client = Aria2Client::new(Config::default());
+    let status = serde_json::json!({
+        "status": "error",
+        "errorCode": "9",
+        "errorMessage": "No space left on device"
+    });
+
+    assert!(matches!(
+        manager.plan_recovery(&client, "full-gid", &status).await,
+        Some(RecoveryAction::GiveUp { .. })
+    ));
+    // Only reported once
+    assert_eq!(
+        manager.plan_recovery(&client, "full-gid", &status).await,
+        None
+    );
+    let state = client.retries.get("full-gid").unwrap();
+    assert_eq!(state.attempts, 0);
+    assert_eq!(state.error_code, Some(9));
+    assert!(state.gave_up);
+}
+
+#[tokio::test]
+async fn test_plan_recovery_skips_old_failures() {
+    let manager = RecoveryManager::new(RetryConfig::default());
+    let client = Aria2Client::new(Config::default());
+    let now = chrono::Utc::now().timestamp();
+    client
+        .completions()
+        .restore(&std::collections::HashMap::from([
+            ("old".to_string(), now - 30 * 86_400),
+            ("recent".to_string(), now - 60),
+        ]));
+    let status = serde_json::json!({ "status": "error", "errorCode": "2" });
+
+    assert_eq!(manager.plan_recovery(&client, "old", &status).await, None);
+    assert!(client.retries.get("old").is_none());
+    assert!(matches!(
+        manager.plan_recovery(&client, "recent", &status).await,
+        Some(RecoveryAction::Retry { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_prune_state_forgets_settled_and_old_downloads() -> anyhow::Result<()> {
+    let mock_server = MockServer::start().await;
+    // One entry per settled download, in GID order
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "system.multicall" }),
+        ))
+        .respond_with(rpc_result(serde_json::json!([
+            [{ "status": "complete" }],
+            [{ "status": "error" }],
+            [{ "status": "error" }],
+            { "code": 1, "message": "GID gone is not found" },
+            [{ "status": "active" }]
+        ])))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Aria2Client::new(Config::new(mock_server.uri(), None));
+    let now = chrono::Utc::now().timestamp();
+    let old = now - 30 * 86_400;
+    client
+        .completions()
+        .restore(&std::collections::HashMap::from([
+            ("failed-new".to_string(), now - 60),
+            ("failed-old".to_string(), old),
+            ("retried-old".to_string(), old),
+        ]));
+    let gave_up = RetryState {
+        attempts: 3,
+        gave_up: true,
+        ..Default::default()
+    };
+    client.retries.insert("done", RetryState::default());
+    client.retries.insert("failed-new", gave_up.clone());
+    client.retries.insert("failed-old", gave_up);
+    client.retries.insert("gone", RetryState::default());
+    client.retries.insert("running", RetryState::default());
+    client.retries.insert(
+        "pending",
+        RetryState {
+            due_at: Some(now + 60),
+            ..Default::default()
+        },
+    );
+    client.lineage.record("retried-old", "done", 1, old, None);
+    client
+        .lineage
+        .record("retried-new", "running", 1, now - 60, None);
+    client.retries.take_dirty();
+    client.lineage.take_dirty();
+
+    let manager = RecoveryManager::new(RetryConfig::default());
+    manager.prune_state(&client, now).await?;
+
+    let remaining: Vec<String> = client
+        .retries
+        .all()
+        .into_iter()
+        .map(|(gid, _)| gid)
+        .collect();
+    assert_eq!(remaining, vec!["failed-new", "pending", "running"]);
+    let links: Vec<String> = client.lineage.links().into_iter().map(|l| l.from).collect();
+    assert_eq!(links, vec!["retried-new"]);
+    assert!(client.retries.take_dirty());
+    assert!(client.lineage.take_dirty());
+    Ok(())
+}
+
+#[test]
+fn test_merge_trackers() {
+    let new = vec![
+        "udp://b.example/announce".to_string(),
+        "udp://c.example/announce".to_string(),
+    ];
+    let (merged, added) =
+        merge_trackers("udp://a.example/announce, udp://b.example/announce", &new);
+    assert_eq!(
+        merged,
+        vec![
+            "udp://a.example/announce",
+            "udp://b.example/announce",
+            "udp://c.example/announce"
+        ]
+    );
+    assert_eq!(added, 1);
+
+    let (merged, added) = merge_trackers("", &new);
+    assert_eq!(merged, new);
+    assert_eq!(added, 2);
+}
+
+#[tokio::test]
+async fn test_tracker_scraper_from_path() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("trackers.txt");
+    std::fs::write(
+        &path,
+        "# public trackers\nudp://a.example/announce\n\nudp://b.example/announce\n",
+    )?;
+
+    let trackers = TrackerScraper::from_path(&path).fetch_trackers().await?;
+    assert_eq!(
+        trackers,
+        vec!["udp://a.example/announce", "udp://b.example/announce"]
+    );
+
+    assert!(TrackerScraper::from_path(dir.path().join("missing.txt"))
+        .fetch_trackers()
+        .await
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_tracker_injections_stall_timeout() {
+    let injections = TrackerInjections::default();
+    let active: Vec<DownloadStatus> = serde_json::from_value(serde_json::json!([
+        { "gid": "stalled", "infoHash": "abc", "totalLength": "100", "completedLength": "10", "connections": "0", "numSeeders": "0" },
+        { "gid": "http", "totalLength": "100", "completedLength": "10", "connections": "0" },
+        { "gid": "seeding", "infoHash": "def", "seeder": "true", "connections": "0", "numSeeders": "0" },
+        { "gid": "peers", "infoHash": "123", "connections": "4", "numSeeders": "1" }
+    ]))
+    .unwrap();
+
+    // Stalled, but not for long enough yet
+    assert!(injections.update(&active, 1000, 600).is_empty());
+    assert_eq!(injections.update(&active, 1600, 600), vec!["stalled"]);
+
+    // Injected downloads are not returned again
+    injections.record("stalled", 5, 1600);
+    assert!(injections.update(&active, 2000, 600).is_empty());
+    assert_eq!(injections.injections()[0].trackers_added, 5);
+
+    // Finished downloads are forgotten
+    assert!(injections.update(&[], 2100, 600).is_empty());
+    assert!(injections.injections().is_empty());
+}
+
+#[tokio::test]
+async fn test_recovery_manager_inject_trackers_merges() -> anyhow::Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "aria2.getOption" }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "aria2-mcp",
+            "jsonrpc": "2.0",
+            "result": { "bt-tracker": "udp://a.example/announce" }
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "method": "aria2.changeOption",
+            "params": ["torrent-gid", { "bt-tracker": "udp://a.example/announce,udp://b.example/announce" }]
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "aria2-mcp",
+            "jsonrpc": "2.0",
+            "result": "OK"
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Aria2Client::new(Config::new(mock_server.uri(), None));
+    let manager = RecoveryManager::new(RetryConfig::default());
+
+    let trackers = vec![
+        "udp://a.example/announce".to_string(),
+        "udp://b.example/announce".to_string(),
+    ];
+    assert_eq!(
+        manager
+            .inject_trackers(&client, "torrent-gid", trackers.clone())
+            .await?,
+        1
+    );
+
+    // Nothing is changed when every tracker is already present
+    assert_eq!(
+        manager
+            .inject_trackers(&client, "torrent-gid", trackers[..1].to_vec())
+            .await?,
+        0
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_recovery_manager_inject_stalled_trackers() -> anyhow::Result<()> {
+    let mock_server = MockServer::start().await;
+    let respond = |result: serde_json::Value| {
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "id": "aria2-mcp",
+            "jsonrpc": "2.0",
+            "result": result
+        }))
+    };
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "aria2.tellActive" }),
+        ))
+        .respond_with(respond(serde_json::json!([
+            { "gid": "stalled", "infoHash": "abc", "totalLength": "100", "completedLength": "10", "connections": "0", "numSeeders": "0" },
+            { "gid": "healthy", "infoHash": "def", "totalLength": "100", "completedLength": "10", "connections": "3", "numSeeders": "2" }
+        ])))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "aria2.getOption" }),
+        ))
+        .respond_with(respond(serde_json::json!({})))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "method": "aria2.changeOption",
+            "params": ["stalled", { "bt-tracker": "udp://a.example/announce" }]
+        })))
+        .respond_with(respond(serde_json::json!("OK")))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let dir = tempfile::tempdir()?;
+    let tracker_file = dir.path().join("trackers.txt");
+    std::fs::write(&tracker_file, "udp://a.example/announce\n")?;
+
+    let client = Aria2Client::new(Config::new(mock_server.uri(), None));
+    let manager = RecoveryManager::new(RetryConfig {
+        tracker_injection_enabled: true,
+        tracker_list_path: Some(tracker_file.display().to_string()),
+        stall_timeout_secs: 0,
+        ..Default::default()
+    });
+
+    manager.inject_stalled_trackers(&client).await?;
+    let injections = client.trackers.injections();
+    assert_eq!(injections.len(), 1);
+    assert_eq!(injections[0].gid, "stalled");
+    assert_eq!(injections[0].trackers_added, 1);
+
+    // The cached list is used and the download is not injected into twice
+    std::fs::remove_file(&tracker_file)?;
+    assert_eq!(manager.tracker_list().await?.len(), 1);
+    manager.inject_stalled_trackers(&client).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_recovery_manager_tracker_list_needs_source() {
+    let manager = RecoveryManager::new(RetryConfig::default());
+    assert!(manager.tracker_list().await.is_err());
+}
+
+fn rpc_result(result: serde_json::Value) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(serde_json::json!({
+        "id": "aria2-mcp",
+        "jsonrpc": "2.0",
+        "result": result
+    }))
+}
+
+#[tokio::test]
+async fn test_perform_retry_preserves_options_and_position() -> anyhow::Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "aria2.tellStatus" }),
+        ))
+        .respond_with(rpc_result(serde_json::json!({
+            "gid": "old-gid",
+            "status": "error",
+            "files": [{
+                "index": "1",
+                "uris": [
+                    { "uri": "http://mirror1.example/file.iso", "status": "used" },
+                    { "uri": "http://mirror2.example/file.iso", "status": "waiting" },
+                    { "uri": "http://mirror1.example/file.iso", "status": "waiting" }
+                ]
+            }]
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "aria2.getOption" }),
+        ))
+        .respond_with(rpc_result(serde_json::json!({
+            "dir": "/data/isos",
+            "out": "file.iso",
+            "header": "Authorization: Bearer token",
+            "max-download-limit": "1048576"
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "method": "aria2.addUri",
+            "params": [
+                ["http://mirror1.example/file.iso", "http://mirror2.example/file.iso"],
+                {
+                    "dir": "/data/isos",
+                    "out": "file.iso",
+                    "header": "Authorization: Bearer token",
+                    "max-download-limit": "1048576"
+                },
+                0
+            ]
+        })))
+        .respond_with(rpc_result(serde_json::json!("new-gid")))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "aria2.removeDownloadResult" }),
+        ))
+        .respond_with(rpc_result(serde_json::json!("OK")))
+        // The failed result leaves the stopped list, so it is not retried again
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Aria2Client::new(Config::new(mock_server.uri(), None));
+    let manager = RecoveryManager::new(RetryConfig::default());
+
+    assert_eq!(manager.perform_retry(&client, "old-gid").await?, "new-gid");
+    assert_eq!(
+        client.lineage.successor("old-gid").as_deref(),
+        Some("new-gid")
+    );
+    let chain = client.lineage.chain("new-gid");
+    assert_eq!(chain.len(), 1);
+    assert_eq!(chain[0].from, "old-gid");
+    assert_eq!(chain[0].attempt, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_perform_retry_torrent_uses_magnet() -> anyhow::Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "aria2.tellStatus" }),
+        ))
+        .respond_with(rpc_result(serde_json::json!({
+            "gid": "torrent-gid",
+            "status": "error",
+            "infoHash": "0123456789abcdef0123456789abcdef01234567",
+            "bittorrent": {
+                "announceList": [["udp://tracker.example:1337/announce"]],
+                "info": { "name": "Linux ISO" }
+            },
+            "files": [
+                { "index": "1", "path": "/data/Linux ISO/a.iso", "uris": [] },
+                { "index": "2", "path": "/data/Linux ISO/b.iso", "uris": [] }
+            ]
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "aria2.getOption" }),
+        ))
+        .respond_with(rpc_result(
+            serde_json::json!({ "dir": "/data", "select-file": "2" }),
+        ))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "method": "aria2.addUri",
+            "params": [
+                ["magnet:?xt=urn:btih:0123456789abcdef0123456789abcdef01234567&dn=Linux+ISO&tr=udp%3A%2F%2Ftracker.example%3A1337%2Fannounce"],
+                { "dir": "/data", "select-file": "2" },
+                0
+            ]
+        })))
+        .respond_with(rpc_result(serde_json::json!("magnet-gid")))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Aria2Client::new(Config::new(mock_server.uri(), None));
+    let manager = RecoveryManager::new(RetryConfig::default());
+
+    assert_eq!(
+        manager.perform_retry(&client, "torrent-gid").await?,
+        "magnet-gid"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_magnet_link() {
+    assert_eq!(
+        magnet_link("abc", None, std::iter::empty()),
+        "magnet:?xt=urn:btih:abc"
+    );
+    assert_eq!(
+        magnet_link("abc", Some("a & b"), ["http://t.example/a?x=1"].into_iter()),
+        "magnet:?xt=urn:btih:abc&dn=a+%26+b&tr=http%3A%2F%2Ft.example%2Fa%3Fx%3D1"
+    );
+}
+
+#[test]
+fn test_retry_lineage_chain() {
+    let lineage = RetryLineage::default();
+    lineage.record("a", "b", 1, 100, None);
+    lineage.record("b", "c", 2, 200, None);
+    lineage.record("x", "y", 1, 150, None);
+
+    for gid in ["a", "b", "c"] {
+        let chain = lineage.chain(gid);
+        let gids: Vec<_> = chain.iter().map(|l| l.to.as_str()).collect();
+        assert_eq!(gids, vec!["b", "c"], "chain for {gid}");
+    }
+    assert!(lineage.chain("unrelated").is_empty());
+    assert_eq!(lineage.successor("b").as_deref(), Some("c"));
+    assert_eq!(lineage.successor("c"), None);
+    assert_eq!(lineage.links().len(), 3);
+}
+
+async fn mount_failed_download(mock_server: &MockServer, options: serde_json::Value) {
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "aria2.tellStatus" }),
+        ))
+        .respond_with(rpc_result(serde_json::json!({
+            "gid": "old-gid",
+            "status": "error",
+            "files": [{
+                "index": "1",
+                "uris": [
+                    { "uri": "http://mirror1.example/file.iso", "status": "used" },
+                    { "uri": "http://mirror2.example/file.iso", "status": "waiting" }
+                ]
+            }]
+        })))
+        .mount(mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "aria2.getOption" }),
+        ))
+        .respond_with(rpc_result(options))
+        .mount(mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "aria2.removeDownloadResult" }),
+        ))
+        .respond_with(rpc_result(serde_json::json!("OK")))
+        .mount(mock_server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_perform_retry_reduce_connections() -> anyhow::Result<()> {
+    let mock_server = MockServer::start().await;
+    mount_failed_download(
+        &mock_server,
+        serde_json::json!({ "split": "8", "max-connection-per-server": "1" }),
+    )
+    .await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "method": "aria2.addUri",
+            "params": [
+                ["http://mirror1.example/file.iso", "http://mirror2.example/file.iso"],
+                { "split": "4", "max-connection-per-server": "1" },
+                0
+            ]
+        })))
+        .respond_with(rpc_result(serde_json::json!("new-gid")))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Aria2Client::new(Config::new(mock_server.uri(), None));
+    client.retries.insert(
+        "old-gid",
+        RetryState {
+            attempts: 2,
+            strategy: Some(RecoveryStrategy::ReduceConnections),
+            ..Default::default()
+        },
+    );
+    let manager = RecoveryManager::new(RetryConfig::default());
+
+    assert_eq!(manager.perform_retry(&client, "old-gid").await?, "new-gid");
+    assert_eq!(client.lineage.chain("new-gid")[0].attempt, 2);
+    assert_eq!(client.retries.get("old-gid"), None);
+    assert_eq!(client.retries.get("new-gid").unwrap().attempts, 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_perform_retry_on_other_instance_with_alternate_mirror() -> anyhow::Result<()> {
+    let source_server = MockServer::start().await;
+    mount_failed_download(&source_server, serde_json::json!({ "dir": "/data" })).await;
+    let target_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(serde_json::json!({
+            "method": "aria2.addUri",
+            "params": [
+                ["http://mirror2.example/file.iso", "http://mirror1.example/file.iso"],
+                { "dir": "/data" },
+                0
+            ]
+        })))
+        .respond_with(rpc_result(serde_json::json!("backup-gid")))
+        .expect(1)
+        .mount(&target_server)
+        .await;
+
+    let client = Aria2Client::new(Config::new(source_server.uri(), None));
+    let config = Config::default();
+    let target = Aria2Client::new_with_instance(
+        config,
+        aria2_mcp_rs::config::Aria2Instance {
+            name: "backup".to_string(),
+            rpc_url: target_server.uri(),
+            rpc_secret: None,
+            rpc_transport: None,
+        },
+    );
+    client.retries.insert(
+        "old-gid",
+        RetryState {
+            attempts: 1,
+            strategy: Some(RecoveryStrategy::AlternateMirror),
+            ..Default::default()
+        },
+    );
+    let manager = RecoveryManager::new(RetryConfig::default());
 
+    assert_eq!(
+        manager
+            .perform_retry_on(&client, &target, "old-gid")
+            .await?,
+        "backup-gid"
+    );
+    let chain = client.lineage.chain("old-gid");
+    assert_eq!(chain[0].instance.as_deref(), Some("backup"));
+    assert_eq!(target.retries.get("backup-gid").unwrap().attempts, 1);
     Ok(())
 }
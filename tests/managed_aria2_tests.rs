@@ -0,0 +1,228 @@
+#![cfg(unix)]
+
+use anyhow::Result;
+use aria2_mcp_rs::aria2::{Aria2Supervisor, ProcessState};
+use aria2_mcp_rs::config::ManagedAria2Config;
+use aria2_mcp_rs::{Aria2Client, Config};
+use serde_json::json;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tokio::time::{sleep, Duration, Instant};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// Writing an executable while another test forks can fail the exec with
+// ETXTBSY, so these tests run one at a time.
+static LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Stands in for aria2's RPC interface, as the fake aria2c does not listen.
+async fn fake_rpc() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/jsonrpc"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": "aria2-mcp",
+            "result": { "version": "1.37.0", "enabledFeatures": [] }
+        })))
+        .mount(&server)
+        .await;
+    server
+}
+
+/// Writes a fake aria2c that records each start and its arguments, then
+/// runs `body`.
+fn fake_aria2c(dir: &Path, body: &str) -> Result<String> {
+    let script = dir.join("aria2c");
+    std::fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> \"{}\"\n{body}\n",
+            dir.join("starts.log").display()
+        ),
+    )?;
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))?;
+    Ok(script.display().to_string())
+}
+
+fn starts(dir: &Path) -> Vec<String> {
+    std::fs::read_to_string(dir.join("starts.log"))
+        .unwrap_or_default()
+        .lines()
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn managed_config(dir: &Path, binary: String, port: u16) -> ManagedAria2Config {
+    ManagedAria2Config {
+        enabled: true,
+        binary,
+        port: Some(port),
+        session_file: dir.join("state/aria2.session").display().to_string(),
+        restart_delay_secs: 0,
+        startup_timeout_secs: 5,
+        ..ManagedAria2Config::default()
+    }
+}
+
+async fn wait_for(supervisor: &Aria2Supervisor, state: ProcessState, restarts: u32) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        let status = supervisor.status();
+        if status.state == state && status.restarts == restarts {
+            return true;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn test_managed_aria2_start_register_and_stop() -> Result<()> {
+    let _guard = LOCK.lock().await;
+    let dir = tempfile::tempdir()?;
+    let rpc = fake_rpc().await;
+    let binary = fake_aria2c(dir.path(), "exec sleep 30")?;
+
+    let supervisor =
+        Aria2Supervisor::start(managed_config(dir.path(), binary, rpc.address().port())).await?;
+
+    let instance = supervisor.instance().clone();
+    let secret = instance.rpc_secret.clone().unwrap();
+    assert_eq!(secret.len(), 32);
+    assert_eq!(
+        instance.rpc_url,
+        format!("http://127.0.0.1:{}/jsonrpc", rpc.address().port())
+    );
+    assert!(dir.path().join("state/aria2.session").exists());
+
+    let args = starts(dir.path());
+    assert_eq!(args.len(), 1);
+    // The secret is passed in a private configuration file, not in argv
+    let conf_path = dir.path().join("state/aria2.session.conf");
+    assert!(args[0].contains(&format!("--conf-path={}", conf_path.display())));
+    assert!(!args[0].contains(&secret));
+    let conf = std::fs::read_to_string(&conf_path)?;
+    assert!(conf.contains("enable-rpc=true"));
+    assert!(conf.contains(&format!("rpc-secret={secret}")));
+    assert_eq!(
+        std::fs::metadata(&conf_path)?.permissions().mode() & 0o777,
+        0o600
+    );
+
+    let status = supervisor.status();
+    assert_eq!(status.state, ProcessState::Running);
+    assert!(status.pid.is_some());
+
+    // The managed instance replaces the default one
+    let mut config = Config::default();
+    supervisor.register(&mut config);
+    assert_eq!(config.instances.len(), 1);
+    assert_eq!(config.instances[0], instance);
+    let client = Aria2Client::new_with_instance(config.clone(), instance);
+    assert_eq!(client.get_version().await?, "1.37.0");
+
+    supervisor.stop().await;
+    let status = supervisor.status();
+    assert_eq!(status.state, ProcessState::Stopped);
+    assert_eq!(status.pid, None);
+    assert!(!conf_path.exists());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_managed_aria2_restarts_after_crash() -> Result<()> {
+    let _guard = LOCK.lock().await;
+    let dir = tempfile::tempdir()?;
+    let rpc = fake_rpc().await;
+    // The first run crashes shortly after starting; later runs stay up
+    let crashed = dir.path().join("crashed");
+    let binary = fake_aria2c(
+        dir.path(),
+        &format!(
+            "if [ ! -e \"{0}\" ]; then touch \"{0}\"; sleep 1; exit 1; fi\nexec sleep 30",
+            crashed.display()
+        ),
+    )?;
+
+    let supervisor =
+        Aria2Supervisor::start(managed_config(dir.path(), binary, rpc.address().port())).await?;
+    let first_pid = supervisor.status().pid;
+
+    assert!(wait_for(&supervisor, ProcessState::Running, 1).await);
+    let status = supervisor.status();
+    assert_ne!(status.pid, first_pid);
+    assert!(status.last_exit.unwrap().contains('1'));
+    // The restarted process reuses the port and secret
+    let args = starts(dir.path());
+    assert_eq!(args.len(), 2);
+    assert_eq!(args[0], args[1]);
+
+    supervisor.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_managed_aria2_not_restarted_after_clean_exit() -> Result<()> {
+    let _guard = LOCK.lock().await;
+    let dir = tempfile::tempdir()?;
+    let rpc = fake_rpc().await;
+    // As after `aria2.shutdown`
+    let binary = fake_aria2c(dir.path(), "sleep 1\nexit 0")?;
+
+    let supervisor =
+        Aria2Supervisor::start(managed_config(dir.path(), binary, rpc.address().port())).await?;
+
+    assert!(wait_for(&supervisor, ProcessState::Stopped, 0).await);
+    sleep(Duration::from_millis(500)).await;
+    let status = supervisor.status();
+    assert_eq!(status.state, ProcessState::Stopped);
+    assert_eq!(status.pid, None);
+    assert!(status.last_exit.unwrap().contains('0'));
+    assert_eq!(starts(dir.path()).len(), 1);
+
+    supervisor.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_managed_aria2_gives_up_after_max_restarts() -> Result<()> {
+    let _guard = LOCK.lock().await;
+    let dir = tempfile::tempdir()?;
+    let rpc = fake_rpc().await;
+    let binary = fake_aria2c(dir.path(), "sleep 1\nexit 3")?;
+
+    let supervisor = Aria2Supervisor::start(ManagedAria2Config {
+        max_restarts: 1,
+        ..managed_config(dir.path(), binary, rpc.address().port())
+    })
+    .await?;
+
+    assert!(wait_for(&supervisor, ProcessState::Failed, 1).await);
+    assert_eq!(starts(dir.path()).len(), 2);
+
+    supervisor.stop().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_managed_aria2_startup_failure() -> Result<()> {
+    let _guard = LOCK.lock().await;
+    let dir = tempfile::tempdir()?;
+    let rpc = fake_rpc().await;
+    let binary = fake_aria2c(dir.path(), "exit 2")?;
+
+    let err = Aria2Supervisor::start(managed_config(dir.path(), binary, rpc.address().port()))
+        .await
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("exited during startup"));
+
+    let missing = dir.path().join("missing").display().to_string();
+    let err = Aria2Supervisor::start(managed_config(dir.path(), missing, rpc.address().port()))
+        .await
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("Failed to start"));
+    Ok(())
+}
@@ -8,11 +8,13 @@ fn test_mcp_server_multi_client_initialization() {
             name: "instance1".to_string(),
             rpc_url: "http://127.0.0.1:6800/jsonrpc".to_string(),
             rpc_secret: None,
+            rpc_transport: None,
         },
         aria2_mcp_rs::config::Aria2Instance {
             name: "instance2".to_string(),
             rpc_url: "http://localhost:6801/jsonrpc".to_string(),
             rpc_secret: Some("secret".to_string()),
+            rpc_transport: None,
         },
     ];
 
@@ -69,6 +69,7 @@ async fn test_notification_flow_multi_instance() -> Result<()> {
         .await?
         .unwrap();
     assert_eq!(n1.method, Aria2Event::DownloadStart);
+    assert_eq!(n1.instance.as_deref(), Some("box1"));
 
     // Trigger event on box2
     client2
@@ -78,6 +79,7 @@ async fn test_notification_flow_multi_instance() -> Result<()> {
         .await?
         .unwrap();
     assert_eq!(n2.method, Aria2Event::DownloadStart);
+    assert_eq!(n2.instance.as_deref(), Some("box2"));
 
     Ok(())
 }
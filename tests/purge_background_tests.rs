@@ -2,6 +2,7 @@ mod common;
 
 use anyhow::Result;
 use aria2_mcp_rs::server::start_purge_task;
+use aria2_mcp_rs::state::StateManager;
 use common::Aria2Container;
 use std::sync::Arc;
 use tokio::time::{sleep, Duration};
@@ -12,7 +13,10 @@ async fn test_background_purge_logic() -> Result<()> {
         return Ok(());
     }
     let container = Aria2Container::new().await?;
-    let client = Arc::new(container.client());
+    let state_dir = tempfile::tempdir()?;
+    let mut client = container.client();
+    client.state_manager = Arc::new(StateManager::new(state_dir.path().join("state.json")));
+    let client = Arc::new(client);
 
     // 1. Configure purge: enabled, 1s interval, 0s min age
     {
@@ -63,6 +67,9 @@ async fn test_background_purge_logic() -> Result<()> {
 
     assert!(purged, "Download should have been purged from stopped list");
 
+    let state = client.state_manager().load().await?;
+    assert!(state.purge_history.iter().any(|record| record.gid == gid));
+
     purge_handle.abort();
     Ok(())
 }
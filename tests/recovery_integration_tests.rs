@@ -37,7 +37,7 @@ async fn test_automated_recovery_integration() -> Result<()> {
 
     // Trigger recovery analysis
     let backoff = recovery_manager
-        .analyze_and_get_retry_backoff(&gid, &status)
+        .analyze_and_get_retry_backoff(&client, &gid, &status)
         .await;
     assert!(backoff.is_some(), "Should be retryable");
 
@@ -151,3 +151,49 @@ async fn test_mcp_handler_resources_read_not_found() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_mcp_handler_resources_read_download_template() -> Result<()> {
+    if !common::should_run_docker_tests() {
+        return Ok(());
+    }
+    let container = Aria2Container::new().await?;
+    let client = container.client();
+    let registry = Arc::new(RwLock::new(ToolRegistry::new(&Config::default())));
+    let resource_registry = Arc::new(RwLock::new(ResourceRegistry::default()));
+    let prompt_registry = Arc::new(RwLock::new(aria2_mcp_rs::PromptRegistry::default()));
+    let handler = McpHandler::new(
+        registry,
+        resource_registry,
+        prompt_registry,
+        vec![Arc::new(client.clone())],
+    );
+
+    let gid = client
+        .add_uri(vec!["https://p3terx.com".to_string()], None)
+        .await?;
+
+    let params = serde_json::json!({
+        "uri": format!("aria2://instances/default/downloads/{gid}")
+    });
+    let result = handler
+        .handle_method("resources/read", Some(params))
+        .await?;
+    let text = result["contents"][0]["text"]
+        .as_str()
+        .expect("Content should have text");
+    let status: serde_json::Value = serde_json::from_str(text)?;
+    assert_eq!(status["gid"], gid);
+
+    let params = serde_json::json!({
+        "uri": format!("aria2://instances/default/downloads/{gid}/files")
+    });
+    let result = handler
+        .handle_method("resources/read", Some(params))
+        .await?;
+    let text = result["contents"][0]["text"].as_str().unwrap();
+    let files: serde_json::Value = serde_json::from_str(text)?;
+    assert!(files.is_array());
+
+    Ok(())
+}
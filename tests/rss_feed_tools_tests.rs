@@ -1,9 +1,11 @@
 mod common;
 
 use anyhow::Result;
+use aria2_mcp_rs::state::StateManager;
 use aria2_mcp_rs::{AddRssFeedTool, ListRssFeedsTool, McpeTool};
 use common::Aria2Container;
 use serde_json::json;
+use std::sync::Arc;
 
 #[tokio::test]
 async fn test_add_rss_feed_tool() -> Result<()> {
@@ -11,7 +13,9 @@ async fn test_add_rss_feed_tool() -> Result<()> {
         return Ok(());
     }
     let container = Aria2Container::new().await?;
-    let client = container.client();
+    let state_dir = tempfile::tempdir()?;
+    let mut client = container.client();
+    client.state_manager = Arc::new(StateManager::new(state_dir.path().join("state.json")));
     let tool = AddRssFeedTool;
 
     let args = json!({
@@ -34,7 +38,9 @@ async fn test_list_rss_feeds_tool() -> Result<()> {
         return Ok(());
     }
     let container = Aria2Container::new().await?;
-    let client = container.client();
+    let state_dir = tempfile::tempdir()?;
+    let mut client = container.client();
+    client.state_manager = Arc::new(StateManager::new(state_dir.path().join("state.json")));
     let add_tool = AddRssFeedTool;
     let list_tool = ListRssFeedsTool;
 
@@ -76,7 +82,7 @@ async fn test_process_feed_mock() -> Result<()> {
     use aria2_mcp_rs::config::{Config, RSSFeed, RSSFilter};
     use aria2_mcp_rs::tools::rss::process_feed;
     use aria2_mcp_rs::Aria2Client;
-    use std::collections::HashSet;
+    use std::collections::HashMap;
     use wiremock::matchers::{method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
@@ -114,7 +120,8 @@ async fn test_process_feed_mock() -> Result<()> {
         url: format!("{}/rss", mock_server.uri()),
         name: "Test Feed".to_string(),
         filters: vec![RSSFilter::Keyword("match".to_string())],
-        download_history: HashSet::new(),
+        download_history: HashMap::new(),
+        ..RSSFeed::default()
     };
 
     let client = Aria2Client::new(Config::default());
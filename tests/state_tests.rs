@@ -19,6 +19,7 @@ async fn test_state_manager_save_and_load() {
         BandwidthProfile {
             max_download: "1M".to_string(),
             max_upload: "500K".to_string(),
+            ..Default::default()
         },
     );
     initial_data.bandwidth_schedules.push(BandwidthSchedule {
@@ -153,3 +154,46 @@ async fn test_state_manager_read_failure() {
         .to_string()
         .contains("Failed to read state file"));
 }
+
+#[tokio::test]
+async fn test_state_manager_concurrent_updates_keep_each_change() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = std::sync::Arc::new(StateManager::new(dir.path().join("state.json")));
+
+    let mut tasks = Vec::new();
+    for i in 0..20 {
+        let manager = std::sync::Arc::clone(&manager);
+        tasks.push(tokio::spawn(async move {
+            manager
+                .update(|data| {
+                    data.rules.insert(format!("rule{i}"), i.to_string());
+                })
+                .await
+                .unwrap();
+        }));
+    }
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    let data = manager.load().await.unwrap();
+    assert_eq!(data.rules.len(), 20);
+    assert!(!dir.path().join("state.json.tmp").exists());
+}
+
+#[tokio::test]
+async fn test_state_manager_update_keeps_unreadable_file() {
+    use std::fs;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state.json");
+    fs::write(&path, "{ \"rules\": {").unwrap();
+
+    let manager = StateManager::new(path.clone());
+    let result = manager
+        .update(|data| {
+            data.rules.insert("a".to_string(), "b".to_string());
+        })
+        .await;
+    assert!(result.is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "{ \"rules\": {");
+}
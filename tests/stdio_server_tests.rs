@@ -1,8 +1,10 @@
-use aria2_mcp_rs::aria2::Aria2Client;
+use aria2_mcp_rs::aria2::{Aria2Client, Aria2Notification};
 use aria2_mcp_rs::config::Config;
 use aria2_mcp_rs::prompts::PromptRegistry;
 use aria2_mcp_rs::resources::ResourceRegistry;
-use aria2_mcp_rs::server::mcp::{handle_request, JsonRpcRequest, McpState};
+use aria2_mcp_rs::server::mcp::{
+    forward_events, handle_request, EventFilter, JsonRpcRequest, McpState,
+};
 use aria2_mcp_rs::tools::ToolRegistry;
 use serde_json::json;
 use std::sync::Arc;
@@ -46,6 +48,8 @@ async fn test_stdio_flow_initialize_and_tools() {
     assert!(result_init["serverInfo"].is_object());
     assert_eq!(result_init["serverInfo"]["name"], "aria2-mcp-rs");
     assert!(result_init["serverInfo"]["version"].is_string());
+    assert_eq!(result_init["capabilities"]["tools"]["listChanged"], true);
+    assert_eq!(result_init["capabilities"]["resources"]["subscribe"], true);
 
     // 2. List Tools
     let req_tools = JsonRpcRequest {
@@ -90,3 +94,347 @@ async fn test_stdio_notifications_queuing() {
     assert_eq!(notif.method, "test/event");
     assert_eq!(notif.params.unwrap()["foo"], "bar");
 }
+
+#[tokio::test]
+async fn test_stdio_subscribe_sets_event_filter() {
+    let state = Arc::new(RwLock::new(McpState::new(false)));
+    let config = Config::default();
+    let registry = Arc::new(RwLock::new(ToolRegistry::new(&config)));
+    let resource_registry = Arc::new(RwLock::new(ResourceRegistry::default()));
+    let prompt_registry = Arc::new(RwLock::new(PromptRegistry::default()));
+    let clients = vec![Arc::new(Aria2Client::new(config.clone()))];
+
+    let subscribe = |params| JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        method: "aria2/subscribe".to_string(),
+        params: Some(params),
+        id: Some(aria2_mcp_rs::server::mcp::RequestId::Number(1)),
+    };
+
+    let res = handle_request(
+        subscribe(json!({ "events": ["download_complete", "download_error"] })),
+        Arc::clone(&state),
+        Arc::clone(&registry),
+        Arc::clone(&resource_registry),
+        Arc::clone(&prompt_registry),
+        clients.clone(),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    let result = res.result.unwrap();
+    assert_eq!(
+        result["filter"]["events"],
+        json!(["download_complete", "download_error"])
+    );
+    assert!(result["filter"]["instances"].is_null());
+
+    let res = handle_request(
+        subscribe(json!({ "events": ["download_finished"] })),
+        Arc::clone(&state),
+        Arc::clone(&registry),
+        Arc::clone(&resource_registry),
+        Arc::clone(&prompt_registry),
+        clients,
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(res.error.unwrap().code, -32602);
+
+    // The invalid request leaves the previous filter in place
+    let filter = state.read().await.event_filter.clone();
+    assert_eq!(filter.events.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_stdio_forward_events_applies_filter() {
+    let state = Arc::new(RwLock::new(McpState::new(false)));
+    state.write().await.event_filter =
+        EventFilter::from_params(&json!({ "events": ["download_complete"] })).unwrap();
+    let clients = vec![Arc::new(Aria2Client::new(Config::default()))];
+
+    let (tx, rx) = tokio::sync::mpsc::channel(10);
+    for method in ["aria2.onDownloadStart", "aria2.onDownloadComplete"] {
+        let mut notification: Aria2Notification = serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": [{ "gid": "abc" }]
+        }))
+        .unwrap();
+        notification.instance = Some("default".to_string());
+        tx.send(notification).await.unwrap();
+    }
+    drop(tx);
+
+    forward_events(
+        Arc::clone(&state),
+        Arc::new(RwLock::new(ResourceRegistry::default())),
+        clients,
+        rx,
+    )
+    .await;
+
+    let mut state_guard = state.write().await;
+    let notif = state_guard.pop_notification().unwrap();
+    assert_eq!(notif.method, "notifications/aria2/event");
+    let params = notif.params.unwrap();
+    assert_eq!(params["event"], "download_complete");
+    assert_eq!(params["gid"], "abc");
+    assert_eq!(params["instance"], "default");
+    // The instance is unreachable, so there is no snapshot
+    assert!(params["download"].is_null());
+    assert!(state_guard.pop_notification().is_none());
+}
+
+#[tokio::test]
+async fn test_stdio_resource_subscription_updates() {
+    let state = Arc::new(RwLock::new(McpState::new(false)));
+    let config = Config::default();
+    let registry = Arc::new(RwLock::new(ToolRegistry::new(&config)));
+    let resource_registry = Arc::new(RwLock::new(ResourceRegistry::default()));
+    let prompt_registry = Arc::new(RwLock::new(PromptRegistry::default()));
+    let clients = vec![Arc::new(Aria2Client::new(config.clone()))];
+
+    let request = |method: &str, uri: &str| JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        method: method.to_string(),
+        params: Some(json!({ "uri": uri })),
+        id: Some(aria2_mcp_rs::server::mcp::RequestId::Number(1)),
+    };
+
+    for uri in ["aria2://downloads/active", "aria2://status/global"] {
+        let res = handle_request(
+            request("resources/subscribe", uri),
+            Arc::clone(&state),
+            Arc::clone(&registry),
+            Arc::clone(&resource_registry),
+            Arc::clone(&prompt_registry),
+            clients.clone(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(res.error.is_none());
+    }
+
+    let res = handle_request(
+        request("resources/unsubscribe", "aria2://status/global"),
+        Arc::clone(&state),
+        Arc::clone(&registry),
+        Arc::clone(&resource_registry),
+        Arc::clone(&prompt_registry),
+        clients.clone(),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert!(res.error.is_none());
+
+    let res = handle_request(
+        request("resources/subscribe", "aria2://unknown"),
+        Arc::clone(&state),
+        Arc::clone(&registry),
+        Arc::clone(&resource_registry),
+        Arc::clone(&prompt_registry),
+        clients.clone(),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(res.error.unwrap().code, -32602);
+
+    // Only download events are wanted; the two updates for the same resource
+    // are coalesced while they are pending
+    state.write().await.event_filter = EventFilter::from_params(&json!({ "events": [] })).unwrap();
+    let (tx, rx) = tokio::sync::mpsc::channel(10);
+    for method in ["aria2.onDownloadStart", "aria2.onDownloadComplete"] {
+        let notification: Aria2Notification = serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": [{ "gid": "abc" }]
+        }))
+        .unwrap();
+        tx.send(notification).await.unwrap();
+    }
+    drop(tx);
+    forward_events(Arc::clone(&state), resource_registry, clients, rx).await;
+
+    let mut state_guard = state.write().await;
+    let notif = state_guard.pop_notification().unwrap();
+    assert_eq!(notif.method, "notifications/resources/updated");
+    assert_eq!(notif.params.unwrap()["uri"], "aria2://downloads/active");
+    assert!(state_guard.pop_notification().is_none());
+}
+
+#[tokio::test]
+async fn test_stdio_manage_tools_in_lazy_mode() {
+    let state = Arc::new(RwLock::new(McpState::new(true)));
+    let config = Config {
+        lazy_mode: true,
+        ..Default::default()
+    };
+    let registry = Arc::new(RwLock::new(ToolRegistry::new(&config)));
+    let resource_registry = Arc::new(RwLock::new(ResourceRegistry::default()));
+    let prompt_registry = Arc::new(RwLock::new(PromptRegistry::default()));
+    let clients = vec![Arc::new(Aria2Client::new(config.clone()))];
+    let mut changes = registry.read().await.subscribe_changes();
+
+    let res = handle_request(
+        JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "tools/list".to_string(),
+            params: None,
+            id: Some(aria2_mcp_rs::server::mcp::RequestId::Number(1)),
+        },
+        Arc::clone(&state),
+        Arc::clone(&registry),
+        Arc::clone(&resource_registry),
+        Arc::clone(&prompt_registry),
+        clients.clone(),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    let tools = res.result.unwrap()["tools"].as_array().unwrap().clone();
+    assert!(tools.iter().any(|t| t["name"] == "manage_tools"));
+
+    let res = handle_request(
+        JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": "manage_tools",
+                "arguments": { "action": "enable", "tools": ["manage_downloads"] }
+            })),
+            id: Some(aria2_mcp_rs::server::mcp::RequestId::Number(2)),
+        },
+        Arc::clone(&state),
+        Arc::clone(&registry),
+        resource_registry,
+        prompt_registry,
+        clients,
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+        res.result.unwrap()["content"][0]["text"],
+        "Enabled 1 tools."
+    );
+    assert!(registry.read().await.is_tool_enabled("manage_downloads"));
+    assert!(changes.try_recv().is_ok());
+}
+
+#[tokio::test]
+async fn test_stdio_resource_templates_list() {
+    let state = Arc::new(RwLock::new(McpState::new(false)));
+    let config = Config::default();
+    let registry = Arc::new(RwLock::new(ToolRegistry::new(&config)));
+    let resource_registry = Arc::new(RwLock::new(ResourceRegistry::default()));
+    let prompt_registry = Arc::new(RwLock::new(PromptRegistry::default()));
+    let clients = vec![Arc::new(Aria2Client::new(config.clone()))];
+
+    let res = handle_request(
+        JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "resources/templates/list".to_string(),
+            params: None,
+            id: Some(aria2_mcp_rs::server::mcp::RequestId::Number(1)),
+        },
+        state,
+        registry,
+        resource_registry,
+        prompt_registry,
+        clients,
+    )
+    .await
+    .unwrap()
+    .unwrap();
+
+    let result = res.result.unwrap();
+    let templates: Vec<&str> = result["resourceTemplates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["uriTemplate"].as_str().unwrap())
+        .collect();
+    for template in [
+        "aria2://instances/{instance}/status/global",
+        "aria2://instances/{instance}/downloads/active",
+        "aria2://instances/{instance}/downloads/stopped",
+        "aria2://instances/{instance}/downloads/{gid}",
+        "aria2://instances/{instance}/downloads/{gid}/files",
+        "aria2://instances/{instance}/downloads/{gid}/peers",
+    ] {
+        assert!(templates.contains(&template), "missing {template}");
+    }
+}
+
+#[tokio::test]
+async fn test_stdio_resources_read_multi_instance() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mut clients = Vec::new();
+    let mut servers = Vec::new();
+    for name in ["box1", "box2"] {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": "aria2-mcp",
+                "result": [{ "gid": format!("{name}-gid") }]
+            })))
+            .mount(&server)
+            .await;
+        let mut client = Aria2Client::new(Config {
+            rpc_url: format!("http://{}", server.address()),
+            ..Config::default()
+        });
+        client.name = name.to_string();
+        clients.push(Arc::new(client));
+        servers.push(server);
+    }
+
+    let state = Arc::new(RwLock::new(McpState::new(false)));
+    let registry = Arc::new(RwLock::new(ToolRegistry::new(&Config::default())));
+    let resource_registry = Arc::new(RwLock::new(ResourceRegistry::default()));
+    let prompt_registry = Arc::new(RwLock::new(PromptRegistry::default()));
+
+    let read = |uri: &str| {
+        handle_request(
+            JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                method: "resources/read".to_string(),
+                params: Some(json!({ "uri": uri })),
+                id: Some(aria2_mcp_rs::server::mcp::RequestId::Number(1)),
+            },
+            Arc::clone(&state),
+            Arc::clone(&registry),
+            Arc::clone(&resource_registry),
+            Arc::clone(&prompt_registry),
+            clients.clone(),
+        )
+    };
+    let text = |res: aria2_mcp_rs::server::mcp::JsonRpcResponse| -> serde_json::Value {
+        let result = res.result.unwrap();
+        serde_json::from_str(result["contents"][0]["text"].as_str().unwrap()).unwrap()
+    };
+
+    // The aggregated view covers every instance
+    let all = text(read("aria2://downloads/active").await.unwrap().unwrap());
+    assert_eq!(all.as_array().unwrap().len(), 2);
+    assert_eq!(all[0]["instance"], "box1");
+    assert_eq!(all[1]["instance"], "box2");
+    assert_eq!(all[1]["data"][0]["gid"], "box2-gid");
+
+    // The per-instance view reads a single instance
+    let one = text(
+        read("aria2://instances/box2/downloads/active")
+            .await
+            .unwrap()
+            .unwrap(),
+    );
+    assert_eq!(one[0]["gid"], "box2-gid");
+}